@@ -25,8 +25,13 @@ impl<T: GameLogic> ApplicationHandler for App<T>{
         let window_attributes = Window::default_attributes();
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
         if let Some(logic) = self.game_logic.take() {
-            let state = pollster::block_on(State::new(window, logic)).unwrap();
-            self.state = Some(state);
+            match pollster::block_on(State::new(window, logic)) {
+                Ok(state) => self.state = Some(state),
+                Err(e) => {
+                    log::error!("Failed to initialize: {}", e);
+                    event_loop.exit();
+                }
+            }
         }
     }
    
@@ -85,7 +90,11 @@ impl<T: GameLogic> ApplicationHandler for App<T>{
 
                 {
                     puffin::profile_scope!("State_Update");
-                    state.update();
+                    if let Err(e) = state.update() {
+                        log::error!("Failed to update: {}", e);
+                        event_loop.exit();
+                        return;
+                    }
                 }
                 {
                     puffin::profile_scope!("State_Render");
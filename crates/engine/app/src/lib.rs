@@ -1,11 +1,20 @@
 pub mod state;
 pub mod app;
-use engine_assets::AssetManager;
+use engine_assets::{AssetManager, LoadProgress};
+use engine_assets::memory::MemoryStats;
 use engine_gpu_types::CameraUniform;
 
 pub trait GameLogic {
-    fn init(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, asset_manager: &mut AssetManager);
+    fn init(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, asset_manager: &mut AssetManager) -> anyhow::Result<()>;
     fn update(&mut self);
+    // Called once per frame with the state of the background asset load
+    fn on_load_progress(&mut self, _progress: &LoadProgress) {}
+    // Called once when the background asset load has finished
+    fn on_assets_loaded(&mut self, _asset_manager: &mut AssetManager) -> anyhow::Result<()> {
+        Ok(())
+    }
+    // Called once per frame with the GPU memory used by loaded assets
+    fn on_memory_stats(&mut self, _stats: &MemoryStats) {}
     fn world(&mut self) -> &mut bevy_ecs::world::World;
     fn draw_ui(&mut self, ctx: &egui::Context);
     fn on_window_input(&mut self, event: &winit::event::WindowEvent, ui_consumed: bool);
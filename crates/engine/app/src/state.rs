@@ -49,7 +49,8 @@ impl<T: GameLogic> State<T> {
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                required_features: wgpu::Features::empty(),
+                // BC textures are decompressed on the CPU where the feature is missing
+                required_features: adapter.features() & wgpu::Features::TEXTURE_COMPRESSION_BC,
                 experimental_features: wgpu::ExperimentalFeatures::disabled(),
                 required_limits: wgpu::Limits::default(),
                 memory_hints: Default::default(),
@@ -83,6 +84,8 @@ impl<T: GameLogic> State<T> {
         let mut asset_manager = AssetManager::new(&device, &queue);
         let standard_pipeline = PipelineBuilder::build_standard_pipeline(&device, &config);
         asset_manager.pipeline_cache.insert("standard".to_string(), standard_pipeline);
+        let skinned_pipeline = PipelineBuilder::build_skinned_pipeline(&device, &config);
+        asset_manager.pipeline_cache.insert("skinned".to_string(), skinned_pipeline);
 
 
         let egui_ctx = egui::Context::default();
@@ -100,7 +103,7 @@ impl<T: GameLogic> State<T> {
             egui_wgpu::RendererOptions::default(), 
         );
 
-        game_logic.init(&device, &queue, &mut asset_manager);
+        game_logic.init(&device, &queue, &mut asset_manager)?;
 
         let depth_texture = Texture::create_depth_texture(&device, &config, "depth_texture");
         
@@ -132,12 +135,21 @@ impl<T: GameLogic> State<T> {
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self) -> anyhow::Result<()> {
+        if self.asset_manager.poll_loading(&self.device, &self.queue)? {
+            self.game_logic.on_assets_loaded(&mut self.asset_manager)?;
+        }
+        self.game_logic.on_load_progress(self.asset_manager.load_progress());
+        self.game_logic.on_memory_stats(&self.asset_manager.memory_stats());
+
         self.game_logic.update();
 
-        self.renderer.update_global_uniforms(&self.queue, self.game_logic.world());
+        self.asset_manager.poll_hot_reload(&self.device, &self.queue);
+
+        self.renderer.update_global_uniforms(&self.device, &self.queue, self.game_logic.world());
 
         self.sync_cursor_state();
+        Ok(())
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -206,7 +218,7 @@ impl<T: GameLogic> State<T> {
 
             self.renderer.draw_world(
                 &mut render_pass,
-                &mut self.game_logic.world(),
+                self.game_logic.world(),
                 &self.asset_manager,
             );
         } 
@@ -0,0 +1,274 @@
+use std::fmt;
+use std::path::PathBuf;
+
+// Every failure the AssetManager can report. Each variant carries the name of the
+// asset (or the manifest path) so broken content can be fixed without a backtrace.
+#[derive(Debug)]
+pub enum AssetError {
+    ManifestNotFound {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    // The serde message carries the line and column
+    ManifestParse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    ManifestIncludeCycle {
+        path: PathBuf,
+    },
+    ManifestConflict {
+        kind: &'static str,
+        name: String,
+        path: PathBuf,
+        other: PathBuf,
+    },
+    InvalidNamespace {
+        path: PathBuf,
+        namespace: String,
+    },
+    LoadInProgress {
+        path: PathBuf,
+    },
+    UnknownPack {
+        path: PathBuf,
+    },
+    FileNotFound {
+        asset: String,
+        path: PathBuf,
+    },
+    FileRead {
+        asset: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    ImageDecode {
+        asset: String,
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    InvalidTexture {
+        asset: String,
+        path: PathBuf,
+        reason: String,
+    },
+    InvalidCubemap {
+        asset: String,
+        reason: String,
+    },
+    TextureUpload {
+        asset: String,
+        reason: String,
+    },
+    ObjParse {
+        asset: String,
+        path: PathBuf,
+        source: tobj::LoadError,
+    },
+    GltfImport {
+        asset: String,
+        path: PathBuf,
+        source: gltf::Error,
+    },
+    EmptyMesh {
+        asset: String,
+        path: PathBuf,
+    },
+    IncompleteMaterial {
+        material: String,
+        field: &'static str,
+    },
+    MissingParentMaterial {
+        material: String,
+        parent: String,
+    },
+    MaterialParentCycle {
+        // From the material that closes the cycle back to it
+        chain: Vec<String>,
+    },
+    MissingPipeline {
+        material: String,
+        pipeline: String,
+    },
+    MissingTexture {
+        material: String,
+        slot: &'static str,
+        texture: String,
+    },
+    WrongTextureDimension {
+        material: String,
+        slot: &'static str,
+        texture: String,
+    },
+    MissingSampler {
+        material: String,
+        sampler: String,
+    },
+    InvalidSampler {
+        sampler: String,
+        reason: &'static str,
+    },
+    Watch {
+        path: PathBuf,
+        source: notify::Error,
+    },
+    BundleRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    BundleWrite {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    InvalidBundle {
+        path: PathBuf,
+        reason: String,
+    },
+    LoaderPanicked,
+    StaleHandle {
+        kind: &'static str,
+    },
+    UnknownMesh {
+        name: String,
+    },
+    UnknownMaterial {
+        name: String,
+    },
+    UnknownTexture {
+        name: String,
+    },
+    UnknownModel {
+        name: String,
+    },
+    UnknownSkin {
+        name: String,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::ManifestNotFound { path, source } => {
+                write!(f, "Asset manifest '{}' could not be read: {}", path.display(), source)
+            }
+            AssetError::ManifestParse { path, source } => {
+                write!(f, "Asset manifest '{}' is invalid: {}", path.display(), source)
+            }
+            AssetError::ManifestIncludeCycle { path } => {
+                write!(f, "Asset manifest '{}' includes itself.", path.display())
+            }
+            AssetError::ManifestConflict { kind, name, path, other } => write!(
+                f,
+                "{} '{}' is defined by both '{}' and '{}'.",
+                kind, name, other.display(), path.display()
+            ),
+            AssetError::InvalidNamespace { path, namespace } => write!(
+                f,
+                "Namespace '{}' of asset manifest '{}' is invalid, it can't be empty or contain ':'.",
+                namespace, path.display()
+            ),
+            AssetError::LoadInProgress { path } => {
+                write!(f, "Assets can't be loaded while '{}' is still loading.", path.display())
+            }
+            AssetError::UnknownPack { path } => write!(f, "Asset pack '{}' is not loaded.", path.display()),
+            AssetError::FileNotFound { asset, path } => {
+                write!(f, "File '{}' for asset '{}' does not exist.", path.display(), asset)
+            }
+            AssetError::FileRead { asset, path, source } => {
+                write!(f, "File '{}' for asset '{}' could not be read: {}", path.display(), asset, source)
+            }
+            AssetError::ImageDecode { asset, path, source } => write!(
+                f,
+                "Image '{}' for texture '{}' could not be decoded: {}",
+                path.display(), asset, source
+            ),
+            AssetError::InvalidTexture { asset, path, reason } => write!(
+                f,
+                "Texture file '{}' for texture '{}' is invalid: {}.",
+                path.display(), asset, reason
+            ),
+            AssetError::InvalidCubemap { asset, reason } => {
+                write!(f, "Cubemap '{}' is invalid: {}.", asset, reason)
+            }
+            AssetError::TextureUpload { asset, reason } => {
+                write!(f, "Texture '{}' could not be uploaded: {}.", asset, reason)
+            }
+            AssetError::ObjParse { asset, path, source } => write!(
+                f,
+                "OBJ file '{}' for mesh '{}' could not be parsed: {}",
+                path.display(), asset, source
+            ),
+            AssetError::GltfImport { asset, path, source } => write!(
+                f,
+                "glTF file '{}' for asset '{}' could not be imported: {}",
+                path.display(), asset, source
+            ),
+            AssetError::EmptyMesh { asset, path } => {
+                write!(f, "File '{}' for mesh '{}' contains no geometry.", path.display(), asset)
+            }
+            AssetError::IncompleteMaterial { material, field } => {
+                write!(f, "Material '{}' has neither a parent nor a '{}'.", material, field)
+            }
+            AssetError::MissingParentMaterial { material, parent } => {
+                write!(f, "Parent material '{}' of material '{}' missing.", parent, material)
+            }
+            AssetError::MaterialParentCycle { chain } => {
+                write!(f, "Material '{}' inherits from itself: {}.", chain[0], chain.join(" -> "))
+            }
+            AssetError::MissingPipeline { material, pipeline } => {
+                write!(f, "Pipeline '{}' for material '{}' missing.", pipeline, material)
+            }
+            AssetError::MissingTexture { material, slot, texture } => write!(
+                f,
+                "{} texture '{}' for material '{}' missing.",
+                slot, texture, material
+            ),
+            AssetError::WrongTextureDimension { material, slot, texture } => write!(
+                f,
+                "{} texture '{}' for material '{}' is a cubemap, the slot needs a 2D texture.",
+                slot, texture, material
+            ),
+            AssetError::MissingSampler { material, sampler } => {
+                write!(f, "Sampler '{}' for material '{}' missing.", sampler, material)
+            }
+            AssetError::InvalidSampler { sampler, reason } => {
+                write!(f, "Sampler '{}' is invalid: {}.", sampler, reason)
+            }
+            AssetError::Watch { path, source } => {
+                write!(f, "Directory '{}' could not be watched for changes: {}", path.display(), source)
+            }
+            AssetError::BundleRead { path, source } => {
+                write!(f, "Asset bundle '{}' could not be read: {}", path.display(), source)
+            }
+            AssetError::BundleWrite { path, source } => {
+                write!(f, "Asset bundle '{}' could not be written: {}", path.display(), source)
+            }
+            AssetError::InvalidBundle { path, reason } => {
+                write!(f, "Asset bundle '{}' is invalid: {}.", path.display(), reason)
+            }
+            AssetError::LoaderPanicked => write!(f, "An asset loader thread panicked."),
+            AssetError::StaleHandle { kind } => write!(f, "{} was unloaded, its id is no longer valid.", kind),
+            AssetError::UnknownMesh { name } => write!(f, "Mesh '{}' is not loaded.", name),
+            AssetError::UnknownMaterial { name } => write!(f, "Material '{}' is not loaded.", name),
+            AssetError::UnknownTexture { name } => write!(f, "Texture '{}' is not loaded.", name),
+            AssetError::UnknownModel { name } => write!(f, "Model '{}' is not loaded.", name),
+            AssetError::UnknownSkin { name } => write!(f, "Skin '{}' is not loaded.", name),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::ManifestNotFound { source, .. } => Some(source),
+            AssetError::FileRead { source, .. } => Some(source),
+            AssetError::ImageDecode { source, .. } => Some(source),
+            AssetError::ObjParse { source, .. } => Some(source),
+            AssetError::GltfImport { source, .. } => Some(source),
+            AssetError::Watch { source, .. } => Some(source),
+            AssetError::BundleRead { source, .. } => Some(source),
+            AssetError::BundleWrite { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
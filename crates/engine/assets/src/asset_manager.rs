@@ -1,37 +1,361 @@
-use std::collections::HashMap;
-use std::f32::consts::PI;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use image::GenericImageView;
+use std::sync::Arc;
+use std::sync::mpsc::{TryRecvError, channel};
+use std::time::{Duration, Instant};
 use wgpu::util::DeviceExt;
-use crate::data_structures::{MaterialData, MeshBuffers,  MeshId, MaterialId, TextureId};
-use engine_gpu_types::{VertexPTN, MaterialUniform};
-use serde::Deserialize;
+use crate::data_structures::{Handle, MaterialBindings, MaterialData, MaterialParams, MeshBuffers, MeshLod, MeshSkin, MeshId, MaterialId, TextureId, ModelPart};
+use crate::asset_error::AssetError;
+use crate::bounds::MeshBounds;
+use crate::primitives::Primitive;
+use crate::hot_reload::{AssetSources, FileWatcher, TextureSource, normalize_path};
+use crate::storage::AssetStorage;
+use crate::samplers::{SamplerCache, SamplerConfig};
+use crate::skin::Skin;
+use crate::loader::{DecodedAsset, DecodedGltf, DecodedImage, DecodedObj, LoadFailure, LoadJob, LoadProgress, ModelParts, PendingLoad, ResolvedImport, spawn_workers};
+use crate::bundle::{AssetBundle, BundleEntry, is_bundle_path};
+use crate::memory::{AssetMemory, MemoryCounter, MemoryStats, MemoryUsage, format_bytes};
+use crate::packs::{LoadedPack, ManifestTree, PackHandles, extend_manifest, find_conflict, read_manifest_tree};
+use crate::compressed::level_size;
+use engine_gpu_types::{VertexJW, VertexPTNT, MaterialUniform};
+use engine_textures::{decompress_bc, mip_level_count};
+use engine_vfs::Vfs;
+use serde::{Deserialize, Serialize};
+
+// Upload time per frame while loading in the background
+const UPLOAD_BUDGET: Duration = Duration::from_millis(4);
+
+// Placeholders that stand in for missing content, see `set_placeholders`
+const ERROR_ASSET: &str = "internal:error";
+pub(crate) const ERROR_TEXTURE: &str = "internal:error_diffuse";
+const ERROR_CUBEMAP: &str = "internal:error_cube";
 
 // Structs for deserializing the asset manifest JSON
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Default)]
 pub struct AssetManifest {
-    pub textures: HashMap<String, String>,
-    pub meshes: HashMap<String, String>,
+    pub textures: HashMap<String, TextureEntry>,
+    pub meshes: HashMap<String, MeshEntry>,
+    // Filled by `read_manifest_tree` from the entries below, with their parents applied
+    #[serde(skip)]
     pub materials: HashMap<String, MaterialConfig>,
+    #[serde(rename = "materials")]
+    pub(crate) material_entries: HashMap<String, MaterialEntry>,
+    #[serde(default)]
+    pub gltf: HashMap<String, String>,
+    #[serde(default)]
+    pub samplers: HashMap<String, SamplerConfig>,
+    #[serde(default)]
+    pub primitives: HashMap<String, Primitive>,
+    #[serde(default)]
+    pub cubemaps: HashMap<String, CubemapConfig>,
+    // Other manifests, relative to this one, whose entries are loaded along with it
+    #[serde(default)]
+    pub include: Vec<String>,
+    // Prefixes every name this manifest defines with "<namespace>:"
+    #[serde(default)]
+    pub namespace: Option<String>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+// A texture is either a plain path or an object with per-texture options
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TextureEntry {
+    Path(String),
+    Config(TextureConfig),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TextureConfig {
+    pub path: String,
+    #[serde(default)]
+    pub kind: TextureKind,
+    // Defaults to sRGB for color textures and linear for everything else
+    pub color_space: Option<ColorSpace>,
+    #[serde(default = "default_mipmaps")]
+    pub mipmaps: bool,
+    // Used by materials that have this texture as diffuse and no sampler of their own
+    pub sampler: Option<String>,
+}
+
+fn default_mipmaps() -> bool {
+    true
+}
+
+// Either six faces or one equirectangular panorama. Radiance .hdr sources give an
+// Rgba16Float cubemap, LDR images an RGBA8 one.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CubemapConfig {
+    // Square images of the same size in the order +X, -X, +Y, -Y, +Z, -Z
+    pub faces: Option<[String; 6]>,
+    pub equirect: Option<String>,
+    // Face size a panorama is resampled to, defaults to a quarter of its width
+    pub size: Option<u32>,
+    // Of LDR sources, defaults to sRGB. HDR sources are always linear.
+    pub color_space: Option<ColorSpace>,
+    #[serde(default = "default_mipmaps")]
+    pub mipmaps: bool,
+}
+
+// What a texture holds, checked against the material slot it is bound to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureKind {
+    #[default]
+    Color,
+    Normal,
+    Data,
+    Mask,
+}
+
+impl TextureKind {
+    pub fn default_color_space(self) -> ColorSpace {
+        match self {
+            TextureKind::Color => ColorSpace::Srgb,
+            TextureKind::Normal | TextureKind::Data | TextureKind::Mask => ColorSpace::Linear,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorSpace {
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    pub fn format(self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+impl TextureEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            TextureEntry::Path(path) => path,
+            TextureEntry::Config(config) => &config.path,
+        }
+    }
+
+    pub fn mipmaps(&self) -> bool {
+        match self {
+            TextureEntry::Path(_) => default_mipmaps(),
+            TextureEntry::Config(config) => config.mipmaps,
+        }
+    }
+
+    // The plain string form keeps the old file name convention: "_n." marks normal maps
+    // and "_data." data textures
+    pub fn kind(&self) -> TextureKind {
+        match self {
+            TextureEntry::Path(path) => {
+                let file_name = Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if file_name.contains("_n.") {
+                    TextureKind::Normal
+                } else if file_name.contains("_data.") {
+                    TextureKind::Data
+                } else {
+                    TextureKind::Color
+                }
+            }
+            TextureEntry::Config(config) => config.kind,
+        }
+    }
+
+    pub fn sampler(&self) -> Option<&str> {
+        match self {
+            TextureEntry::Path(_) => None,
+            TextureEntry::Config(config) => config.sampler.as_deref(),
+        }
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        match self {
+            TextureEntry::Path(_) => self.kind().default_color_space(),
+            TextureEntry::Config(config) => config.color_space.unwrap_or(config.kind.default_color_space()),
+        }
+    }
+}
+
+// A mesh is either a plain OBJ path or an object with a chain of coarser levels
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MeshEntry {
+    Path(String),
+    Config(MeshConfig),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MeshConfig {
+    pub path: String,
+    // Level 1 onwards, from near to far. Level n is registered as "<mesh>/lod<n>".
+    #[serde(default)]
+    pub lods: Vec<LodConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LodConfig {
+    // OBJ file of the level. Without one the level is simplified from the mesh's own file.
+    pub path: Option<String>,
+    // Fraction of the full mesh's triangles a simplified level keeps, halves with every
+    // level by default
+    pub simplify: Option<f32>,
+    #[serde(flatten)]
+    pub threshold: LodThreshold,
+}
+
+// From where on a level replaces the one before it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LodThreshold {
+    // Distance between the camera and the center of the entity's bounds
+    Distance(f32),
+    // Diameter of the entity's bounding sphere as a fraction of the screen height, the
+    // level is used below it
+    ScreenSize(f32),
+}
+
+impl MeshEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            MeshEntry::Path(path) => path,
+            MeshEntry::Config(config) => &config.path,
+        }
+    }
+
+    pub fn lods(&self) -> &[LodConfig] {
+        match self {
+            MeshEntry::Path(_) => &[],
+            MeshEntry::Config(config) => &config.lods,
+        }
+    }
+}
+
+impl LodConfig {
+    pub fn simplify_ratio(&self, level: usize) -> f32 {
+        self.simplify.unwrap_or(0.5f32.powi(level as i32))
+    }
+}
+
+// Name level `level` of a mesh's LOD chain is registered under, level 0 is the mesh itself
+pub fn lod_name(mesh: &str, level: usize) -> String {
+    format!("{}/lod{}", mesh, level)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct MaterialConfig {
     pub pipeline: String,
     pub diffuse: String,
     pub normal: Option<String>,
     pub roughness: f32,
     pub metallic: f32,
+    #[serde(default)]
+    pub sampler: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureSlot {
+    Diffuse,
+    Normal,
+}
+
+// A material as written in the manifest. Fields that are left out come from `parent`, an
+// entry without one has to set pipeline, diffuse, roughness and metallic. A `null` normal
+// or sampler clears the parent's.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub(crate) struct MaterialEntry {
+    pub parent: Option<String>,
+    pub pipeline: Option<String>,
+    pub diffuse: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub normal: Option<Option<String>>,
+    pub roughness: Option<f32>,
+    pub metallic: Option<f32>,
+    #[serde(default, deserialize_with = "present")]
+    pub sampler: Option<Option<String>>,
+}
+
+// Tells a field set to null apart from one that is missing
+fn present<'de, D: serde::Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+impl MaterialEntry {
+    // `base` is the resolved parent, if there is one
+    pub fn resolve(&self, name: &str, base: Option<MaterialConfig>) -> Result<MaterialConfig, AssetError> {
+        let required = |field: &'static str| AssetError::IncompleteMaterial { material: name.to_string(), field };
+        let base = match base {
+            Some(base) => base,
+            None => MaterialConfig {
+                pipeline: self.pipeline.clone().ok_or_else(|| required("pipeline"))?,
+                diffuse: self.diffuse.clone().ok_or_else(|| required("diffuse"))?,
+                normal: None,
+                roughness: self.roughness.ok_or_else(|| required("roughness"))?,
+                metallic: self.metallic.ok_or_else(|| required("metallic"))?,
+                sampler: None,
+            },
+        };
+        Ok(MaterialConfig {
+            pipeline: self.pipeline.clone().unwrap_or(base.pipeline),
+            diffuse: self.diffuse.clone().unwrap_or(base.diffuse),
+            normal: self.normal.clone().unwrap_or(base.normal),
+            roughness: self.roughness.unwrap_or(base.roughness),
+            metallic: self.metallic.unwrap_or(base.metallic),
+            sampler: self.sampler.clone().unwrap_or(base.sampler),
+        })
+    }
+}
+
+// A name that could not be found or loaded and was replaced by a placeholder
+#[derive(Debug, Clone)]
+pub struct MissingAsset {
+    pub kind: &'static str,
+    pub name: String,
+    pub reason: String,
+    pub substitutions: usize,
 }
 
 pub struct AssetManager {
-    meshes: Vec<MeshBuffers>,
-    materials: Vec<MaterialData>,
-    texture_views: Vec<wgpu::TextureView>, 
+    meshes: AssetStorage<MeshBuffers>,
+    materials: AssetStorage<MaterialData>,
+    texture_views: AssetStorage<wgpu::TextureView>,
 
     mesh_registry: HashMap<String, MeshId>,
     material_registry: HashMap<String, MaterialId>,
     texture_registry: HashMap<String, TextureId>,
+    texture_kinds: HashMap<String, TextureKind>,
+    texture_dimensions: HashMap<String, wgpu::TextureViewDimension>,
+    texture_samplers: HashMap<String, String>,
+    sampler_configs: HashMap<String, SamplerConfig>,
+    sampler_cache: SamplerCache,
+    model_registry: HashMap<String, Vec<ModelPart>>,
+    // CPU-only, shared by the skinned meshes that use them
+    skins: HashMap<String, Arc<Skin>>,
+    // Coarser levels of every mesh with an LOD chain, from near to far
+    mesh_lods: HashMap<MeshId, Vec<MeshLod>>,
+
+    // Kept to rebuild assets in place when their source files change
+    material_configs: HashMap<String, MaterialConfig>,
+    packs: Vec<LoadedPack>,
+    sources: AssetSources,
+    watcher: Option<FileWatcher>,
+    vfs: Vfs,
+
+    loading: Option<PendingLoad>,
+    load_progress: LoadProgress,
+
+    placeholders: bool,
+    // Filled by lookups too, which only borrow the manager
+    missing: RefCell<Vec<MissingAsset>>,
+
+    // GPU memory of every loaded asset, kept up to date as assets are registered and freed
+    memory: MemoryCounter,
+    memory_budget: Option<u64>,
+    over_budget: bool,
 
     pub default_sampler: wgpu::Sampler,
     pub default_normal_view: wgpu::TextureView,
@@ -40,17 +364,45 @@ pub struct AssetManager {
 
 impl AssetManager {
     pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
-        let default_sampler = Self::create_default_sampler(device);
+        let sampler_cache = SamplerCache::new(device);
+        let default_sampler = sampler_cache.default_sampler();
         let default_normal_view = Self::create_default_normal_view(device, queue);
+        if !device.features().contains(wgpu::Features::TEXTURE_COMPRESSION_BC) {
+            log::warn!("The device can't sample BC textures, KTX2/DDS textures are decompressed to RGBA8 when loaded.");
+        }
 
         Self {
-            meshes: Vec::new(),
-            materials: Vec::new(),
-            texture_views: Vec::new(),
+            meshes: AssetStorage::default(),
+            materials: AssetStorage::default(),
+            texture_views: AssetStorage::default(),
 
             mesh_registry: HashMap::new(),
             material_registry: HashMap::new(),
             texture_registry: HashMap::new(),
+            texture_kinds: HashMap::new(),
+            texture_dimensions: HashMap::new(),
+            texture_samplers: HashMap::new(),
+            sampler_configs: HashMap::new(),
+            sampler_cache,
+            model_registry: HashMap::new(),
+            skins: HashMap::new(),
+            mesh_lods: HashMap::new(),
+
+            material_configs: HashMap::new(),
+            packs: Vec::new(),
+            sources: AssetSources::default(),
+            watcher: None,
+            vfs: Vfs::working_dir(),
+
+            loading: None,
+            load_progress: LoadProgress::default(),
+
+            placeholders: cfg!(debug_assertions),
+            missing: RefCell::new(Vec::new()),
+
+            memory: MemoryCounter::default(),
+            memory_budget: None,
+            over_budget: false,
 
             default_sampler,
             default_normal_view,
@@ -58,88 +410,1100 @@ impl AssetManager {
         }
     }
 
-   pub fn get_mesh_id(&self, name: &str) -> MeshId {
-        *self.mesh_registry.get(name).expect(&format!("Mesh '{}' could not be loaded.", name))
+    // Unknown names resolve to the "internal:error" placeholders while placeholders are
+    // enabled, see `set_placeholders`
+    pub fn get_mesh_id(&self, name: &str) -> Result<MeshId, AssetError> {
+        match self.mesh_registry.get(name) {
+            Some(&id) => Ok(id),
+            None => self.substitute("Mesh", name, &self.mesh_registry, ERROR_ASSET, AssetError::UnknownMesh { name: name.to_string() })
+                .copied(),
+        }
+    }
+
+    pub fn get_material_id(&self, name: &str) -> Result<MaterialId, AssetError> {
+        match self.material_registry.get(name) {
+            Some(&id) => Ok(id),
+            None => self.substitute("Material", name, &self.material_registry, ERROR_ASSET, AssetError::UnknownMaterial { name: name.to_string() })
+                .copied(),
+        }
+    }
+
+    // Textures and cubemaps share one id space, `get_texture_dimension` tells them apart
+    pub fn get_texture_id(&self, name: &str) -> Result<TextureId, AssetError> {
+        match self.texture_registry.get(name) {
+            Some(&id) => Ok(id),
+            None => self.substitute("Texture", name, &self.texture_registry, ERROR_TEXTURE, AssetError::UnknownTexture { name: name.to_string() })
+                .copied(),
+        }
+    }
+
+    // Every sub-mesh of a multi-part OBJ or glTF asset together with its material
+    pub fn get_model(&self, name: &str) -> Result<&[ModelPart], AssetError> {
+        match self.model_registry.get(name) {
+            Some(parts) => Ok(parts),
+            None => self.substitute("Model", name, &self.model_registry, ERROR_ASSET, AssetError::UnknownModel { name: name.to_string() })
+                .map(Vec::as_slice),
+        }
+    }
+
+    // Joint hierarchy of a glTF skin, "<asset>/<skin>". Skinned meshes carry theirs in
+    // `MeshBuffers::skin` as well.
+    pub fn get_skin(&self, name: &str) -> Result<Arc<Skin>, AssetError> {
+        self.skins.get(name).cloned().ok_or_else(|| AssetError::UnknownSkin { name: name.to_string() })
+    }
+
+    // Levels 1 onwards of the mesh's LOD chain, empty for meshes without one. Every part of
+    // a multi-part model has a chain of its own.
+    pub fn get_mesh_lods(&self, id: MeshId) -> &[MeshLod] {
+        self.mesh_lods.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    fn substitute<'a, T>(
+        &self,
+        kind: &'static str,
+        name: &str,
+        registry: &'a HashMap<String, T>,
+        placeholder: &str,
+        error: AssetError,
+    ) -> Result<&'a T, AssetError> {
+        match registry.get(placeholder) {
+            Some(entry) if self.placeholders => {
+                self.record_missing(kind, name, &error);
+                Ok(entry)
+            }
+            _ => Err(error),
+        }
+    }
+
+    // Development builds replace missing meshes, materials, textures and models with a
+    // magenta/black checkered placeholder instead of failing, release builds fail. Applies
+    // to lookups and to assets that fail to load.
+    pub fn set_placeholders(&mut self, enabled: bool) {
+        self.placeholders = enabled;
+    }
+
+    // Everything that is currently drawn as a placeholder, in the order it went missing.
+    // Entries disappear once the asset is loaded, e.g. by hot reload.
+    pub fn missing_assets(&self) -> Vec<MissingAsset> {
+        self.missing.borrow().clone()
+    }
+
+    // Logs the first substitution of a name only, later ones are just counted
+    fn record_missing(&self, kind: &'static str, name: &str, reason: &AssetError) {
+        let mut missing = self.missing.borrow_mut();
+        if let Some(entry) = missing.iter_mut().find(|entry| entry.kind == kind && entry.name == name) {
+            entry.substitutions += 1;
+            return;
+        }
+        log::warn!("{} '{}' is replaced by a placeholder: {}", kind, name, reason);
+        missing.push(MissingAsset { kind, name: name.to_string(), reason: reason.to_string(), substitutions: 1 });
+    }
+
+    fn clear_missing(&mut self, kind: &'static str, name: &str) {
+        self.missing.get_mut().retain(|entry| entry.kind != kind || entry.name != name);
+    }
+
+    // Strong handles keep the asset loaded across `unload_unused`
+    pub fn get_mesh_handle(&self, name: &str) -> Result<Handle<MeshBuffers>, AssetError> {
+        self.mesh_handle(self.get_mesh_id(name)?)
+    }
+
+    pub fn get_material_handle(&self, name: &str) -> Result<Handle<MaterialData>, AssetError> {
+        self.material_handle(self.get_material_id(name)?)
+    }
+
+    pub fn get_texture_handle(&self, name: &str) -> Result<Handle<wgpu::TextureView>, AssetError> {
+        self.texture_handle(self.get_texture_id(name)?)
+    }
+
+    pub fn mesh_handle(&self, id: MeshId) -> Result<Handle<MeshBuffers>, AssetError> {
+        self.meshes.handle(id).ok_or(AssetError::StaleHandle { kind: "Mesh" })
+    }
+
+    pub fn material_handle(&self, id: MaterialId) -> Result<Handle<MaterialData>, AssetError> {
+        self.materials.handle(id).ok_or(AssetError::StaleHandle { kind: "Material" })
     }
 
-    pub fn get_material_id(&self, name: &str) -> MaterialId {
-        *self.material_registry.get(name).expect(&format!("Material '{}' could not be loaded.", name))
+    pub fn texture_handle(&self, id: TextureId) -> Result<Handle<wgpu::TextureView>, AssetError> {
+        self.texture_views.handle(id).ok_or(AssetError::StaleHandle { kind: "Texture" })
     }
 
-    pub fn get_mesh(&self, id: MeshId) -> &MeshBuffers {
-        &self.meshes[id.0]
+    // None once the asset was unloaded, even if the slot has been reused since
+    pub fn get_mesh(&self, id: MeshId) -> Option<&MeshBuffers> {
+        self.meshes.get(id)
     }
 
-    pub fn get_material(&self, id: MaterialId) -> &MaterialData {
-        &self.materials[id.0]
+    // Local-space AABB and bounding sphere of the mesh
+    pub fn get_mesh_bounds(&self, id: MeshId) -> Option<MeshBounds> {
+        self.meshes.get(id).map(|mesh| mesh.bounds)
     }
 
-    fn load_internal_assets(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
-        self.create_cube_mesh("internal:cube", device);
-        self.create_sphere_mesh("internal:sphere", device, 0.5, 16, 32);
-        self.create_single_color_material(
+    pub fn get_material(&self, id: MaterialId) -> Option<&MaterialData> {
+        self.materials.get(id)
+    }
+
+    pub fn get_texture_view(&self, id: TextureId) -> Option<&wgpu::TextureView> {
+        self.texture_views.get(id)
+    }
+
+    // D2 for textures, Cube for cubemaps
+    pub fn get_texture_dimension(&self, name: &str) -> Option<wgpu::TextureViewDimension> {
+        self.texture_dimensions.get(name).copied()
+    }
+
+    // Frees every mesh, material and texture no Handle refers to anymore, e.g. after the
+    // entities of a level were despawned. Internal assets stay loaded, so do the assets of
+    // loaded packs, which hold handles to them. Materials go first, they hold handles to
+    // their textures. Returns the number of freed assets.
+    pub fn unload_unused(&mut self) -> usize {
+        let materials = self.materials.unused();
+        for &id in &materials {
+            if let Some(material) = self.materials.remove(id) {
+                self.memory.remove_material(&material);
+            }
+        }
+        let textures = self.texture_views.unused();
+        for &id in &textures {
+            if let Some(view) = self.texture_views.remove(id) {
+                self.memory.remove_texture(&view);
+            }
+        }
+        let meshes = self.meshes.unused();
+        for &id in &meshes {
+            if let Some(mesh) = self.meshes.remove(id) {
+                self.memory.remove_mesh(&mesh);
+            }
+        }
+
+        self.material_registry.retain(|_, id| self.materials.contains(*id));
+        self.texture_registry.retain(|_, id| self.texture_views.contains(*id));
+        self.texture_kinds.retain(|name, _| self.texture_registry.contains_key(name));
+        self.texture_dimensions.retain(|name, _| self.texture_registry.contains_key(name));
+        self.texture_samplers.retain(|name, _| self.texture_registry.contains_key(name));
+        self.mesh_registry.retain(|_, id| self.meshes.contains(*id));
+        self.model_registry.retain(|_, parts| {
+            parts.iter().all(|part| self.meshes.contains(part.mesh) && self.materials.contains(part.material))
+        });
+        self.material_configs.retain(|name, _| self.material_registry.contains_key(name));
+        self.mesh_lods.retain(|id, lods| self.meshes.contains(*id) && lods.iter().all(|lod| self.meshes.contains(lod.mesh)));
+
+        // Forget the sources too, so hot reload and the next manifest load bring them back
+        self.sources.textures.retain(|name, _| self.texture_registry.contains_key(name));
+        self.sources.meshes.retain(|name, _| self.model_registry.contains_key(name));
+        self.sources.simplified.retain(|name, _| self.model_registry.contains_key(name));
+        self.sources.gltf.retain(|name, _| self.model_registry.contains_key(name));
+        for pack in &mut self.packs {
+            let manifest = &mut pack.manifest;
+            manifest.textures.retain(|name, _| self.texture_registry.contains_key(name));
+            manifest.meshes.retain(|name, _| self.model_registry.contains_key(name));
+            manifest.materials.retain(|name, _| self.material_registry.contains_key(name));
+            manifest.gltf.retain(|name, _| self.model_registry.contains_key(name));
+            manifest.primitives.retain(|name, _| self.mesh_registry.contains_key(name));
+            manifest.cubemaps.retain(|name, _| self.texture_registry.contains_key(name));
+        }
+
+        let freed = materials.len() + textures.len() + meshes.len();
+        if freed > 0 {
+            log::info!(
+                "Unloaded {} meshes, {} materials and {} textures",
+                meshes.len(), materials.len(), textures.len()
+            );
+        }
+        freed
+    }
+
+    // GPU memory of everything loaded, in total and per pack. The totals are kept up to
+    // date as assets come and go, so this is cheap enough to call every frame.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let packs = self.packs.iter().map(|pack| (pack.path.clone(), pack.memory.usage)).collect();
+        MemoryStats { total: self.memory.usage, packs, budget: self.memory_budget }
+    }
+
+    // Every loaded texture, mesh and material by name, largest first. Mesh aliases are
+    // listed once.
+    pub fn asset_memory(&self) -> Vec<AssetMemory> {
+        let textures = self.texture_registry.iter()
+            .filter_map(|(name, &id)| Some(("Texture", name, MemoryUsage::of_texture(self.texture_views.get(id)?))));
+        let mut listed = HashSet::new();
+        let meshes = self.mesh_registry.iter()
+            .filter(|&(_, &id)| listed.insert(id))
+            .filter_map(|(name, &id)| Some(("Mesh", name, MemoryUsage::of_mesh(self.meshes.get(id)?))));
+        let materials = self.material_registry.iter()
+            .filter_map(|(name, &id)| Some(("Material", name, MemoryUsage::of_material(self.materials.get(id)?))));
+
+        let mut assets: Vec<AssetMemory> = textures.chain(meshes).chain(materials)
+            .map(|(kind, name, usage)| AssetMemory { kind, name: name.clone(), usage })
+            .collect();
+        assets.sort_by(|a, b| b.usage.total().cmp(&a.usage.total()).then_with(|| a.name.cmp(&b.name)));
+        assets
+    }
+
+    // Logs a warning whenever loading pushes the assets over `bytes` of GPU memory.
+    // `None` turns the check off.
+    pub fn set_memory_budget(&mut self, bytes: Option<u64>) {
+        self.memory_budget = bytes;
+        self.over_budget = false;
+        self.check_memory_budget();
+    }
+
+    fn check_memory_budget(&mut self) {
+        let Some(budget) = self.memory_budget else { return };
+        let used = self.memory.usage.total();
+        if used > budget && !self.over_budget {
+            log::warn!(
+                "Assets use {} of GPU memory, over the budget of {}.",
+                format_bytes(used), format_bytes(budget)
+            );
+        }
+        self.over_budget = used > budget;
+    }
+
+    fn load_internal_assets(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        // Internal assets are never unloaded, loaders fall back to them
+        let meshes = [
+            ("internal:cube", Primitive::unit_box()),
+            ("internal:sphere", Primitive::Sphere { radius: 0.5, rings: 16, segments: 32 }),
+            ("internal:plane", Primitive::Plane { size: [1.0, 1.0], subdivisions: [1, 1], uv_scale: [1.0, 1.0] }),
+            ("internal:cylinder", Primitive::Cylinder { radius: 0.5, height: 1.0, segments: 32 }),
+            ("internal:capsule", Primitive::Capsule { radius: 0.25, height: 0.5, rings: 8, segments: 32 }),
+        ];
+        for (name, primitive) in &meshes {
+            let id = self.create_primitive_mesh(name, primitive, device);
+            self.meshes.set_persistent(id);
+        }
+        let white = self.create_single_color_material(
             "internal:white",
             [255, 255, 255, 255],
             0.5,
             0.0,
             device,
             queue,
-        );
+        )?;
+
+        self.materials.set_persistent(white);
+
+        let checker = DecodedImage::new(checkerboard(64, 8), 64, 64, wgpu::TextureFormat::Rgba8UnormSrgb, true);
+        let view = Self::create_texture(device, queue, &checker, ERROR_TEXTURE)?;
+        let texture = self.register_texture(ERROR_TEXTURE, view, TextureKind::Color, wgpu::TextureViewDimension::D2);
+        self.texture_views.set_persistent(texture);
+        let cube = DecodedImage {
+            layers: 6,
+            levels: checker.levels.iter().map(|level| level.repeat(6)).collect(),
+            ..checker
+        };
+        let view = Self::create_texture(device, queue, &cube, ERROR_CUBEMAP)?;
+        let texture = self.register_texture(ERROR_CUBEMAP, view, TextureKind::Color, wgpu::TextureViewDimension::Cube);
+        self.texture_views.set_persistent(texture);
+
+        let material = self.create_material(ERROR_ASSET, &error_material_config(), device)?;
+        self.materials.set_persistent(material);
+        let mesh = self.create_primitive_mesh(ERROR_ASSET, &Primitive::unit_box(), device);
+        self.meshes.set_persistent(mesh);
+        self.model_registry.insert(ERROR_ASSET.to_string(), vec![ModelPart { mesh, material }]);
+        Ok(())
+    }
+
+    // Loads the whole manifest before returning. Decoding still runs on worker threads.
+    pub fn initialize_assets(&mut self, path: &str, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        self.begin_loading(path, device, queue)?;
+        self.upload_loaded(None, device, queue)?;
+        Ok(())
     }
 
-    pub fn initialize_assets(&mut self, manifest_path: &str, device: &wgpu::Device, queue: &wgpu::Queue) {
+    // Every file is read through the VFS, which defaults to the working directory. Set it
+    // before loading, packs that are already loaded keep the files they were read from.
+    pub fn set_vfs(&mut self, vfs: Vfs) {
+        self.vfs = vfs;
+    }
+
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    // Starts loading a JSON manifest or a bundle written by the asset-cooker in the
+    // background, replacing everything that was loaded before. Internal assets are
+    // available right away, everything else once `poll_loading` reports that the load has
+    // finished.
+    pub fn begin_loading(&mut self, path: &str, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
         self.clear_assets();
-        self.load_internal_assets(device, queue);
+        self.load_internal_assets(device, queue)?;
+        self.start_pack_load(Path::new(path))
+    }
+
+    // Loads a manifest or bundle as a pack next to the ones that are already loaded, e.g. a
+    // level's assets next to the shared core pack. Runs in the background like
+    // `begin_loading`, which has to be called first. Entries another pack already loaded
+    // are shared, a name that another pack defines differently is an error.
+    pub fn load_pack(&mut self, path: &str) -> Result<(), AssetError> {
+        if let Some(load) = &self.loading {
+            return Err(AssetError::LoadInProgress { path: load.manifest_path.clone() });
+        }
+        self.start_pack_load(Path::new(path))
+    }
+
+    // Releases a pack and frees its assets like `unload_unused`. Assets that entities or
+    // other packs still hold stay loaded. A pack that is still loading is cancelled.
+    pub fn unload_pack(&mut self, path: &str) -> Result<usize, AssetError> {
+        let path = normalize_path(&self.vfs, Path::new(path));
+        if self.loading.as_ref().is_some_and(|load| load.manifest_path == path) {
+            self.loading = None;
+            self.load_progress = LoadProgress::default();
+        } else {
+            let index = self.packs.iter().position(|pack| pack.path == path)
+                .ok_or(AssetError::UnknownPack { path })?;
+            self.packs.remove(index);
+        }
+        Ok(self.unload_unused())
+    }
+
+    pub fn is_pack_loaded(&self, path: &str) -> bool {
+        let path = normalize_path(&self.vfs, Path::new(path));
+        self.packs.iter().any(|pack| pack.path == path)
+    }
+
+    // Packs are keyed by their normalized path, manifests are read through it too so hot
+    // reload resolves their entries the same way
+    fn start_pack_load(&mut self, path: &Path) -> Result<(), AssetError> {
+        let path = normalize_path(&self.vfs, path);
+        if is_bundle_path(&path) {
+            let bundle = AssetBundle::open(&self.vfs, &path)?;
+            self.start_bundle_load(bundle);
+            Ok(())
+        } else {
+            let tree = read_manifest_tree(&self.vfs, &path)?;
+            self.start_manifest_load(&path, tree)
+        }
+    }
+
+    // Uploads decoded assets for a few milliseconds per call. Returns true on the call that
+    // finishes the load, errors abort it.
+    pub fn poll_loading(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<bool, AssetError> {
+        self.upload_loaded(Some(UPLOAD_BUDGET), device, queue)
+    }
 
-        let file_content = std::fs::read_to_string(manifest_path).expect("Manifest not found");
-        let manifest: AssetManifest = serde_json::from_str(&file_content).expect("JSON error");
-        let base_path = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+    pub fn load_progress(&self) -> &LoadProgress {
+        &self.load_progress
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    // Queues every entry that is new or changed compared to the previously applied version
+    // of the pack and to the other loaded packs. Entries that were removed stay loaded so
+    // existing ids remain valid.
+    fn start_manifest_load(&mut self, manifest_path: &Path, tree: ManifestTree) -> Result<(), AssetError> {
+        let ManifestTree { manifest, files, mut broken_materials } = tree;
+        // Release builds fail on them like on any other broken asset
+        if !self.placeholders && !broken_materials.is_empty() {
+            return Err(broken_materials.swap_remove(0).1);
+        }
+        let mut previous = AssetManifest::default();
+        if let Some(pack) = self.packs.iter().find(|pack| pack.path == manifest_path) {
+            previous = pack.manifest.clone();
+        }
+        for pack in self.packs.iter().filter(|pack| pack.path != manifest_path) {
+            if let Some((kind, name)) = find_conflict(&manifest, &pack.manifest) {
+                return Err(AssetError::ManifestConflict {
+                    kind,
+                    name,
+                    path: manifest_path.to_path_buf(),
+                    other: pack.path.clone(),
+                });
+            }
+            extend_manifest(&mut previous, &pack.manifest);
+        }
+
+        let samplers_changed = manifest.samplers.iter().any(|(name, config)| previous.samplers.get(name) != Some(config));
+        self.sampler_configs.extend(manifest.samplers.iter().map(|(name, config)| (name.clone(), config.clone())));
+        for (name, entry) in &manifest.textures {
+            match entry.sampler() {
+                Some(sampler) => self.texture_samplers.insert(name.clone(), sampler.to_string()),
+                None => self.texture_samplers.remove(name),
+            };
+        }
+
+        let jobs = manifest_jobs(&manifest, &previous);
+        // Bind groups still reference the replaced texture views or old samplers
+        let rebuild_materials = samplers_changed || jobs.iter().any(|job| matches!(job, LoadJob::Texture { .. }));
+
+        let materials: Vec<(String, MaterialConfig)> = manifest.materials.iter()
+            .filter(|(name, config)| previous.materials.get(*name) != Some(*config))
+            .map(|(name, config)| (name.clone(), config.clone()))
+            .collect();
+
+        self.load_progress = LoadProgress {
+            loaded: 0,
+            total: jobs.len() + materials.len(),
+            current: None,
+        };
+        self.loading = Some(PendingLoad {
+            manifest_path: manifest_path.to_path_buf(),
+            manifest,
+            files,
+            materials,
+            rebuild_materials,
+            remaining: jobs.len(),
+            results: spawn_workers(jobs, &self.vfs),
+            bundle: None,
+            broken_materials,
+        });
+        Ok(())
+    }
+
+    // Streams the bundle's textures and meshes through the same upload path as decoded
+    // assets. Nothing is decoded, so every entry is ready right away.
+    fn start_bundle_load(&mut self, bundle: AssetBundle) {
+        let bundle = Arc::new(bundle);
+        self.skins.extend(bundle.toc.skins.iter().map(|(name, skin)| (name.clone(), Arc::new(skin.clone()))));
+        self.sampler_configs.extend(bundle.toc.samplers.iter().map(|(name, config)| (name.clone(), config.clone())));
+        for texture in &bundle.toc.textures {
+            if let Some(sampler) = &texture.sampler {
+                self.texture_samplers.insert(texture.name.clone(), sampler.clone());
+            }
+        }
+
+        let entries = bundle.entries();
+        let (sender, results) = channel();
+        for (name, entry) in &entries {
+            let asset = DecodedAsset::Bundled { name: name.clone(), bundle: Arc::clone(&bundle), entry: *entry };
+            // The receiver is still alive, it is only stored below
+            let _ = sender.send(Ok(asset));
+        }
+
+        let materials = bundle.toc.materials.clone();
+        self.load_progress = LoadProgress {
+            loaded: 0,
+            total: entries.len() + materials.len(),
+            current: None,
+        };
+        self.loading = Some(PendingLoad {
+            manifest_path: normalize_path(&self.vfs, &bundle.path),
+            manifest: AssetManifest::default(),
+            files: Vec::new(),
+            materials,
+            rebuild_materials: false,
+            remaining: entries.len(),
+            results,
+            bundle: Some(bundle),
+            broken_materials: Vec::new(),
+        });
+    }
+
+    // Applies a manifest synchronously, used when it changes on disk
+    fn apply_manifest(&mut self, manifest_path: &Path, tree: ManifestTree, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        self.start_manifest_load(manifest_path, tree)?;
+        self.upload_loaded(None, device, queue)?;
+        Ok(())
+    }
+
+    // Uploads decoded assets until `budget` is used up, or blocks until the load is done
+    // without one. Dropping the pending load on error stops the workers.
+    fn upload_loaded(&mut self, budget: Option<Duration>, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<bool, AssetError> {
+        let Some(mut load) = self.loading.take() else { return Ok(false) };
+        let start = Instant::now();
+
+        while load.remaining > 0 {
+            let received = match budget {
+                Some(budget) if start.elapsed() >= budget => None,
+                Some(_) => match load.results.try_recv() {
+                    Ok(result) => Some(result),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return Err(AssetError::LoaderPanicked),
+                },
+                None => Some(load.results.recv().map_err(|_| AssetError::LoaderPanicked)?),
+            };
+
+            let Some(result) = received else {
+                self.loading = Some(load);
+                return Ok(false);
+            };
+
+            match result {
+                Ok(asset) => {
+                    self.load_progress.current = Some(asset.name().to_string());
+                    self.upload_decoded(asset, device, queue)?;
+                }
+                Err(failure) => self.register_failed(failure)?,
+            }
+            self.load_progress.loaded += 1;
+            load.remaining -= 1;
+        }
+
+        if load.rebuild_materials {
+            self.rebuild_materials(device)?;
+        }
 
-        for (name, rel_path) in &manifest.textures {
-            let full_path = base_path.join(rel_path);
-            let view = self.load_texture_from_path(full_path, device, queue);
-            
-            let id = TextureId(self.texture_views.len());
-            self.texture_views.push(view);
-            self.texture_registry.insert(name.clone(), id);
+        for (name, config) in &load.materials {
+            self.load_progress.current = Some(name.clone());
+            self.create_material_or_placeholder(name, config, device)?;
+            self.load_progress.loaded += 1;
+        }
+        // Built from the error material's config above, or by an earlier load if unchanged
+        for (name, error) in &load.broken_materials {
+            self.clear_missing("Material", name);
+            self.record_missing("Material", name, error);
         }
 
-        for (name, rel_path) in &manifest.meshes {
-            let full_path = base_path.join(rel_path);
-            let mesh_data = self.load_mesh_from_path(full_path, device);
-            
-            let id = MeshId(self.meshes.len());
-            self.meshes.push(mesh_data);
-            self.mesh_registry.insert(name.clone(), id);
+        match &load.bundle {
+            Some(bundle) => {
+                self.register_models(&bundle.toc.models, &bundle.toc.aliases)?;
+                self.register_lod_chains(&bundle.toc.lods);
+            }
+            None => self.register_lod_chains(&lod_chains(&load.manifest)),
         }
 
-        let mat_configs: Vec<(String, MaterialConfig)> = manifest.materials.into_iter().collect();
-        for (name, config) in mat_configs {
-            self.create_material(&name, &config, device);
+        let handles = self.pack_handles(&load.manifest, load.bundle.as_deref());
+        let memory = self.pack_memory(&handles);
+        let is_new = !self.packs.iter().any(|pack| pack.path == load.manifest_path);
+        self.packs.retain(|pack| pack.path != load.manifest_path);
+        self.packs.push(LoadedPack {
+            path: load.manifest_path,
+            manifest: load.manifest,
+            files: load.files,
+            handles,
+            memory,
+        });
+        // A new pack can bring new directories to watch
+        if is_new && self.watcher.is_some() {
+            self.enable_hot_reload()?;
         }
+        self.check_memory_budget();
+        Ok(true)
     }
 
-    pub fn create_material(&mut self, name: &str, config: &MaterialConfig, device: &wgpu::Device) {
+    // Handles to everything a pack defines. Materials hold their textures, models their
+    // meshes and materials.
+    fn pack_handles(&self, manifest: &AssetManifest, bundle: Option<&AssetBundle>) -> PackHandles {
+        let mut handles = PackHandles::default();
+        let bundle_toc = bundle.map(|bundle| &bundle.toc);
+
+        let textures = manifest.textures.keys()
+            .chain(manifest.cubemaps.keys())
+            .chain(bundle_toc.into_iter().flat_map(|toc| toc.textures.iter().map(|texture| &texture.name)));
+        // Straight from the registries, the lookups would hand out placeholders for names
+        // that aren't loaded
+        handles.textures.extend(textures.filter_map(|name| self.texture_views.handle(*self.texture_registry.get(name)?)));
+
+        let meshes = manifest.primitives.keys()
+            .chain(bundle_toc.into_iter().flat_map(|toc| toc.meshes.iter().map(|mesh| &mesh.name)));
+        handles.meshes.extend(meshes.filter_map(|name| self.meshes.handle(*self.mesh_registry.get(name)?)));
+
+        let materials = manifest.materials.keys()
+            .chain(bundle_toc.into_iter().flat_map(|toc| toc.materials.iter().map(|(name, _)| name)));
+        handles.materials.extend(materials.filter_map(|name| self.materials.handle(*self.material_registry.get(name)?)));
+
+        let lod_models: Vec<String> = lod_chains(manifest).into_iter()
+            .flat_map(|(name, thresholds)| (1..=thresholds.len()).map(move |level| lod_name(&name, level)))
+            .collect();
+        let models = manifest.meshes.keys()
+            .chain(&lod_models)
+            .chain(manifest.gltf.keys())
+            .chain(bundle_toc.into_iter().flat_map(|toc| toc.models.iter().map(|(name, _)| name)));
+        for part in models.filter_map(|name| self.model_registry.get(name)).flatten() {
+            handles.meshes.extend(self.meshes.handle(part.mesh));
+            handles.materials.extend(self.materials.handle(part.material));
+        }
+        handles
+    }
+
+    fn pack_memory(&self, handles: &PackHandles) -> MemoryCounter {
+        let mut memory = MemoryCounter::default();
+        handles.textures.iter().filter_map(|handle| self.texture_views.get(handle.id())).for_each(|view| memory.add_texture(view));
+        handles.meshes.iter().filter_map(|handle| self.meshes.get(handle.id())).for_each(|mesh| memory.add_mesh(mesh));
+        handles.materials.iter().filter_map(|handle| self.materials.get(handle.id())).for_each(|material| memory.add_material(material));
+        memory
+    }
+
+    fn upload_decoded(&mut self, asset: DecodedAsset, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        match asset {
+            DecodedAsset::Texture { name, source, image } => {
+                self.upload_texture(&name, source, &image, device, queue)?;
+            }
+            DecodedAsset::Obj { name, path, obj } => self.upload_obj(&name, &path, obj, device, queue)?,
+            DecodedAsset::Gltf { name, path, gltf } => self.upload_gltf(&name, &path, gltf, device, queue)?,
+            DecodedAsset::Mesh { name, vertices, indices } => {
+                let mesh = Self::create_mesh_buffers(device, &vertices, &indices, &name);
+                self.register_mesh(&name, mesh);
+            }
+            DecodedAsset::Cubemap { name, image } => {
+                let view = Self::create_texture(device, queue, &image, &name)?;
+                self.register_texture(&name, view, TextureKind::Color, view_dimension(image.layers));
+            }
+            DecodedAsset::Bundled { bundle, entry, .. } => self.upload_bundled(&bundle, entry, device, queue)?,
+        }
+        Ok(())
+    }
+
+    fn upload_bundled(&mut self, bundle: &AssetBundle, entry: BundleEntry, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        match entry {
+            BundleEntry::Texture(index) => {
+                let texture = &bundle.toc.textures[index];
+                let levels = bundle.texture_levels(texture)?;
+                let view = Self::create_texture_from_levels(device, queue, texture.size(), texture.format(), &levels, &texture.name)?;
+                self.register_texture(&texture.name, view, texture.kind, view_dimension(texture.layers));
+            }
+            BundleEntry::Mesh(index) => {
+                let mesh = &bundle.toc.meshes[index];
+                let (vertices, indices) = bundle.mesh_data(mesh)?;
+                let mut buffers = Self::create_mesh_buffers(device, &vertices, &indices, &mesh.name);
+                if let Some((skin, weights)) = bundle.mesh_skin(mesh)? {
+                    buffers.skin = Some(self.create_mesh_skin(device, skin, &weights, &mesh.name)?);
+                }
+                self.register_mesh(&mesh.name, buffers);
+            }
+        }
+        Ok(())
+    }
+
+    // Registers a placeholder under the name of an asset that failed to load. Its source is
+    // kept, so hot reload picks the file up once it is fixed.
+    fn register_failed(&mut self, failure: LoadFailure) -> Result<(), AssetError> {
+        if !self.placeholders {
+            return Err(failure.error);
+        }
+        let (kind, name) = match failure.job {
+            LoadJob::Texture { name, source } => {
+                // A checkered normal map would only garble the lighting
+                let view = match source.kind {
+                    TextureKind::Normal => self.default_normal_view.clone(),
+                    _ => self.placeholder_view(ERROR_TEXTURE)?,
+                };
+                self.register_texture(&name, view, source.kind, wgpu::TextureViewDimension::D2);
+                self.sources.textures.insert(name.clone(), TextureSource { path: normalize_path(&self.vfs, &source.path), ..source });
+                ("Texture", name)
+            }
+            LoadJob::Cubemap { name, .. } => {
+                let view = self.placeholder_view(ERROR_CUBEMAP)?;
+                self.register_texture(&name, view, TextureKind::Color, wgpu::TextureViewDimension::Cube);
+                ("Texture", name)
+            }
+            LoadJob::Obj { name, path, simplify } => {
+                self.register_placeholder_model(&name)?;
+                self.sources.meshes.insert(name.clone(), normalize_path(&self.vfs, &path));
+                self.sources.simplified.insert(name.clone(), simplify);
+                ("Model", name)
+            }
+            LoadJob::Gltf { name, path } => {
+                self.register_placeholder_model(&name)?;
+                self.sources.gltf.insert(name.clone(), normalize_path(&self.vfs, &path));
+                ("Model", name)
+            }
+            LoadJob::Primitive { .. } => return Err(failure.error),
+        };
+        self.record_missing(kind, &name, &failure.error);
+        Ok(())
+    }
+
+    // Internal textures are persistent, they are only missing before `load_internal_assets`
+    fn placeholder_view(&self, name: &str) -> Result<wgpu::TextureView, AssetError> {
+        self.texture_registry.get(name)
+            .and_then(|&id| self.texture_views.get(id))
+            .cloned()
+            .ok_or_else(|| AssetError::UnknownTexture { name: name.to_string() })
+    }
+
+    // A copy of the error mesh, so the model's mesh keeps its own id once the file loads
+    fn register_placeholder_model(&mut self, name: &str) -> Result<(), AssetError> {
+        let error = self.model_registry.get(ERROR_ASSET).and_then(|parts| parts.first())
+            .and_then(|part| Some((self.meshes.get(part.mesh)?.clone(), part.material)));
+        let Some((mesh, material)) = error else {
+            return Err(AssetError::UnknownModel { name: ERROR_ASSET.to_string() });
+        };
+        let mesh = self.register_mesh(name, mesh);
+        self.model_registry.insert(name.to_string(), vec![ModelPart { mesh, material }]);
+        Ok(())
+    }
+
+    // Materials that can't be built, e.g. because of a missing pipeline or sampler, are
+    // built like the error material instead. The config stays, `rebuild_materials` retries it.
+    fn create_material_or_placeholder(&mut self, name: &str, config: &MaterialConfig, device: &wgpu::Device) -> Result<MaterialId, AssetError> {
+        let error = match self.create_material(name, config, device) {
+            Ok(id) => return Ok(id),
+            Err(error) => error,
+        };
+        let placeholder = self.material_configs.get(ERROR_ASSET).filter(|_| self.placeholders).cloned();
+        let Some(placeholder) = placeholder else {
+            return Err(error);
+        };
+        // Built from the error config, so it gets a uniform buffer of its own
+        let id = self.create_material(name, &placeholder, device)?;
+        self.material_configs.insert(name.to_string(), config.clone());
+        self.record_missing("Material", name, &error);
+        Ok(id)
+    }
+
+    // Development helper: watches the directory of the first manifest pack, every included
+    // manifest and every directory an asset was loaded from. Changes are applied by
+    // `poll_hot_reload`. Only files on disk are watched, not ones from archives or the binary.
+    pub fn enable_hot_reload(&mut self) -> Result<(), AssetError> {
+        let Some(pack) = self.packs.iter().find(|pack| !pack.files.is_empty()) else {
+            if self.packs.is_empty() {
+                log::warn!("Hot reload requested before a manifest was loaded.");
+            } else {
+                log::warn!("Hot reload is not available for cooked bundles.");
+            }
+            return Ok(());
+        };
+        // Packs on disk are keyed by their absolute path
+        if !pack.path.is_absolute() {
+            log::warn!("Hot reload is not available for '{}', it is not a file on disk.", pack.path.display());
+            return Ok(());
+        }
+
+        let root = pack.path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let mut directories = self.sources.directories();
+        directories.extend(
+            self.packs.iter()
+                .flat_map(|pack| &pack.files)
+                .filter(|file| file.is_absolute())
+                .filter_map(|file| file.parent().map(Path::to_path_buf)),
+        );
+        self.watcher = Some(FileWatcher::new(&root, &directories)?);
+        Ok(())
+    }
+
+    // Rebuilds every asset whose source file changed since the last call. Ids stay the same,
+    // so entities holding MeshHandle/MaterialHandle see the new data without respawning.
+    // Failures are logged and leave the previous data in place.
+    pub fn poll_hot_reload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Some(watcher) = &self.watcher else { return };
+        if self.loading.is_some() {
+            return;
+        }
+        let changed = watcher.changed_paths();
+        if changed.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.reload_changed(&changed, device, queue) {
+            log::error!("Hot reload failed: {}", e);
+        }
+        self.check_memory_budget();
+    }
+
+    fn reload_changed(&mut self, changed: &HashSet<PathBuf>, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        // A changed include reloads every pack that includes it
+        let packs: Vec<PathBuf> = self.packs.iter()
+            .filter(|pack| pack.files.iter().any(|file| changed.contains(file)))
+            .map(|pack| pack.path.clone())
+            .collect();
+        for manifest_path in &packs {
+            log::info!("Reloading asset manifest '{}'", manifest_path.display());
+            let tree = read_manifest_tree(&self.vfs, manifest_path)?;
+            self.apply_manifest(manifest_path, tree, device, queue)?;
+        }
+
+        let textures: Vec<(String, TextureSource)> = self.sources.textures.iter()
+            .filter(|(_, source)| changed.contains(&source.path))
+            .map(|(name, source)| (name.clone(), source.clone()))
+            .collect();
+        for (name, source) in &textures {
+            log::info!("Reloading texture '{}'", name);
+            self.load_texture_file(name, source.clone(), device, queue)?;
+        }
+        if !textures.is_empty() {
+            self.rebuild_materials(device)?;
+        }
+
+        // A changed MTL file reloads every OBJ next to it
+        let mtl_dirs: Vec<&Path> = changed.iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "mtl"))
+            .filter_map(|path| path.parent())
+            .collect();
+        let meshes: Vec<(String, PathBuf)> = self.sources.meshes.iter()
+            .filter(|(_, path)| changed.contains(*path) || path.parent().is_some_and(|dir| mtl_dirs.contains(&dir)))
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+        for (name, path) in &meshes {
+            log::info!("Reloading mesh '{}'", name);
+            self.load_obj(name, path, device, queue)?;
+        }
+
+        let gltf: Vec<(String, PathBuf)> = self.sources.gltf.iter()
+            .filter(|(_, path)| changed.contains(*path))
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+        for (name, path) in &gltf {
+            log::info!("Reloading glTF '{}'", name);
+            self.load_gltf(name, path, device, queue)?;
+        }
+
+        Ok(())
+    }
+
+    fn rebuild_materials(&mut self, device: &wgpu::Device) -> Result<(), AssetError> {
+        let configs = self.material_configs.clone();
+        for (name, config) in &configs {
+            self.create_material_or_placeholder(name, config, device)?;
+        }
+        Ok(())
+    }
+
+    // Registers every primitive as mesh "<name>/<mesh>/<primitive>", every image as texture
+    // "<name>/<image>" and every material as "<name>/<material>".
+    pub fn load_gltf(&mut self, name: &str, path: &Path, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        let gltf = DecodedGltf::load(name, path, &self.vfs)?;
+        self.upload_gltf(name, path, gltf, device, queue)
+    }
+
+    fn upload_gltf(&mut self, name: &str, path: &Path, gltf: DecodedGltf, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        self.upload_import(gltf.resolve(name), device, queue)?;
+        self.sources.gltf.insert(name.to_string(), normalize_path(&self.vfs, path));
+        Ok(())
+    }
+
+    // Loads every object/group of an OBJ file as mesh "<name>/<object>" and every MTL material
+    // as "<name>/<material>". The model "<name>" spawns all parts, the mesh "<name>" stays an
+    // alias of the first object so single-object meshes keep working. LOD levels the
+    // manifest simplified from the file before are simplified again.
+    pub fn load_obj(&mut self, name: &str, path: &Path, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        let mut obj = DecodedObj::load(name, path, &self.vfs)?;
+        if let Some(levels) = self.sources.simplified.get(name) {
+            obj.simplify(name, levels);
+        }
+        self.upload_obj(name, path, obj, device, queue)
+    }
+
+    fn upload_obj(&mut self, name: &str, path: &Path, obj: DecodedObj, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        obj.warnings.iter().for_each(|warning| log::warn!("{}", warning));
+        let simplified = obj.simplified_levels();
+        self.upload_import(obj.resolve(name), device, queue)?;
+        self.sources.meshes.insert(name.to_string(), normalize_path(&self.vfs, path));
+        self.sources.simplified.insert(name.to_string(), simplified);
+        Ok(())
+    }
+
+    fn upload_import(&mut self, import: ResolvedImport, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        for texture in import.textures {
+            match texture.source {
+                Some(source) => {
+                    self.upload_texture(&texture.name, source, &texture.image, device, queue)?;
+                }
+                None => {
+                    let view = Self::create_texture(device, queue, &texture.image, &texture.name)?;
+                    self.register_texture(&texture.name, view, texture.kind, wgpu::TextureViewDimension::D2);
+                }
+            }
+        }
+
+        for (name, config) in &import.materials {
+            self.create_material_or_placeholder(name, config, device)?;
+        }
+
+        self.skins.extend(import.skins.into_iter().map(|(name, skin)| (name, Arc::new(skin))));
+        for mesh in &import.meshes {
+            let mut buffers = Self::create_mesh_buffers(device, &mesh.vertices, &mesh.indices, &mesh.name);
+            if let Some((skin, weights)) = &mesh.skin {
+                buffers.skin = Some(self.create_mesh_skin(device, skin, weights, &mesh.name)?);
+            }
+            self.register_mesh(&mesh.name, buffers);
+        }
+
+        self.register_models(&import.models, &import.aliases)
+    }
+
+    // Models whose parts reference meshes and materials by name
+    fn register_models(&mut self, models: &[(String, ModelParts)], aliases: &[(String, String)]) -> Result<(), AssetError> {
+        let fallback_material = self.get_material_id("internal:white")?;
+        for (name, parts) in models {
+            let parts = parts.iter()
+                .map(|(mesh, material)| Ok(ModelPart {
+                    mesh: self.get_mesh_id(mesh)?,
+                    material: match material {
+                        Some(material) => self.get_material_id(material)?,
+                        None => fallback_material,
+                    },
+                }))
+                .collect::<Result<Vec<_>, AssetError>>()?;
+            self.clear_missing("Model", name);
+            self.model_registry.insert(name.clone(), parts);
+        }
+
+        for (alias, mesh) in aliases {
+            let id = self.get_mesh_id(mesh)?;
+            self.mesh_registry.insert(alias.clone(), id);
+        }
+        Ok(())
+    }
+
+    // Level k of a chain is the model "<mesh>/lod<k>", its parts are matched with the parts
+    // of the model "<mesh>" by position. A chain ends before the first level that isn't
+    // loaded, meshes whose entry lost its levels drop their chain.
+    fn register_lod_chains(&mut self, chains: &[(String, Vec<LodThreshold>)]) {
+        for (name, thresholds) in chains {
+            let Some(parts) = self.model_registry.get(name) else { continue };
+            let levels: Vec<&Vec<ModelPart>> = (1..=thresholds.len())
+                .map_while(|level| self.model_registry.get(&lod_name(name, level)))
+                .collect();
+            if levels.len() < thresholds.len() {
+                log::warn!("LOD level {} of mesh '{}' is not loaded, its chain ends before it.", levels.len() + 1, name);
+            }
+            for (index, part) in parts.iter().enumerate() {
+                let lods: Vec<MeshLod> = levels.iter().zip(thresholds)
+                    .map_while(|(level, &threshold)| Some(MeshLod { mesh: level.get(index)?.mesh, threshold }))
+                    .collect();
+                if lods.is_empty() {
+                    self.mesh_lods.remove(&part.mesh);
+                } else {
+                    self.mesh_lods.insert(part.mesh, lods);
+                }
+            }
+        }
+    }
+
+    fn load_texture_file(
+        &mut self,
+        name: &str,
+        source: TextureSource,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<TextureId, AssetError> {
+        let image = DecodedImage::open(name, &source, &self.vfs)?;
+        self.upload_texture(name, source, &image, device, queue)
+    }
+
+    fn upload_texture(
+        &mut self,
+        name: &str,
+        source: TextureSource,
+        image: &DecodedImage,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<TextureId, AssetError> {
+        let label = source.path.file_name().and_then(|n| n.to_str()).unwrap_or(name).to_string();
+        let view = Self::create_texture(device, queue, image, &label)?;
+        self.sources.textures.insert(name.to_string(), TextureSource { path: normalize_path(&self.vfs, &source.path), ..source });
+        Ok(self.register_texture(name, view, source.kind, wgpu::TextureViewDimension::D2))
+    }
+
+    // Registering an existing name replaces the asset in place and keeps its id
+    fn register_texture(
+        &mut self,
+        name: &str,
+        view: wgpu::TextureView,
+        kind: TextureKind,
+        dimension: wgpu::TextureViewDimension,
+    ) -> TextureId {
+        self.clear_missing("Texture", name);
+        self.texture_kinds.insert(name.to_string(), kind);
+        self.texture_dimensions.insert(name.to_string(), dimension);
+        if let Some(&id) = self.texture_registry.get(name)
+            && self.texture_views.contains(id)
+        {
+            let old = self.texture_views.replace(id, view);
+            let holds = |handles: &PackHandles| handles.textures.iter().any(|handle| handle.id() == id);
+            for counter in memory_counters(&mut self.memory, &mut self.packs, holds) {
+                old.iter().for_each(|view| counter.remove_texture(view));
+                self.texture_views.get(id).into_iter().for_each(|view| counter.add_texture(view));
+            }
+            return id;
+        }
+        self.memory.add_texture(&view);
+        let id = self.texture_views.insert(view);
+        self.texture_registry.insert(name.to_string(), id);
+        id
+    }
+
+    fn register_mesh(&mut self, name: &str, mesh: MeshBuffers) -> MeshId {
+        self.clear_missing("Mesh", name);
+        if let Some(&id) = self.mesh_registry.get(name)
+            && self.meshes.contains(id)
+        {
+            let old = self.meshes.replace(id, mesh);
+            let holds = |handles: &PackHandles| handles.meshes.iter().any(|handle| handle.id() == id);
+            for counter in memory_counters(&mut self.memory, &mut self.packs, holds) {
+                old.iter().for_each(|mesh| counter.remove_mesh(mesh));
+                self.meshes.get(id).into_iter().for_each(|mesh| counter.add_mesh(mesh));
+            }
+            return id;
+        }
+        self.memory.add_mesh(&mesh);
+        let id = self.meshes.insert(mesh);
+        self.mesh_registry.insert(name.to_string(), id);
+        id
+    }
+
+    // Reads a single manifest file, `read_manifest_tree` resolves its includes
+    pub(crate) fn read_manifest(vfs: &Vfs, path: &Path) -> Result<AssetManifest, AssetError> {
+        let file_content = vfs.read_to_string(path).map_err(|source| AssetError::ManifestNotFound {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        serde_json::from_str(&file_content).map_err(|source| AssetError::ManifestParse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn create_material(&mut self, name: &str, config: &MaterialConfig, device: &wgpu::Device) -> Result<MaterialId, AssetError> {
         let pipeline = self.pipeline_cache.get(&config.pipeline)
-            .expect(&format!("Pipeline '{}' missing.", config.pipeline));
+            .ok_or_else(|| AssetError::MissingPipeline {
+                material: name.to_string(),
+                pipeline: config.pipeline.clone(),
+            })?;
         
         let layout = pipeline.get_bind_group_layout(2); // Material bind group is at index 2
 
-        let diffuse_id = self.texture_registry.get(&config.diffuse)
-            .expect(&format!("Diffuse Texture '{}' for material '{}' missing.", config.diffuse, name));
-        let diffuse_view = &self.texture_views[diffuse_id.0];
+        // The material's own sampler wins over the one of its diffuse texture
+        let sampler_name = config.sampler.as_ref().or_else(|| self.texture_samplers.get(&config.diffuse));
+        let (sampler, lod_bias) = match sampler_name {
+            Some(sampler_name) => {
+                let sampler_config = self.sampler_configs.get(sampler_name)
+                    .ok_or_else(|| AssetError::MissingSampler {
+                        material: name.to_string(),
+                        sampler: sampler_name.clone(),
+                    })?;
+                // The material layout has a filtering sampler slot
+                if sampler_config.compare.is_some() {
+                    return Err(AssetError::InvalidSampler {
+                        sampler: sampler_name.clone(),
+                        reason: "comparison samplers can't be used by materials",
+                    });
+                }
+                let sampler = self.sampler_cache.get_or_create(device, sampler_config)
+                    .map_err(|reason| AssetError::InvalidSampler { sampler: sampler_name.clone(), reason })?;
+                (sampler, sampler_config.lod_bias)
+            }
+            None => (self.default_sampler.clone(), 0.0),
+        };
+
+        let mut textures = Vec::with_capacity(2);
+        let (diffuse_view, diffuse_handle) = self.material_texture(name, "Diffuse", TextureKind::Color, &config.diffuse)?;
+        textures.push(diffuse_handle);
 
         let normal_view = if let Some(normal_name) = &config.normal {
-            let n_id = self.texture_registry.get(normal_name).expect(&format!("Normal Texture '{}' for material '{}' missing.", normal_name, name));
-            &self.texture_views[n_id.0]
+            let (view, handle) = self.material_texture(name, "Normal", TextureKind::Normal, normal_name)?;
+            textures.push(handle);
+            view
         } else {
             &self.default_normal_view
         };
 
+        let bindings = MaterialBindings { diffuse: diffuse_view.clone(), normal: normal_view.clone(), sampler, lod_bias };
+        let params = MaterialParams { roughness: config.roughness, metallic: config.metallic };
+        let material = Self::build_material(name, &config.pipeline, &layout, bindings, params, textures, device);
+
+        self.material_configs.insert(name.to_string(), config.clone());
+        Ok(self.register_material(name, material))
+    }
+
+    // Uniform buffer and bind group around textures and a sampler that are already resolved
+    fn build_material(
+        name: &str,
+        pipeline: &str,
+        layout: &wgpu::BindGroupLayout,
+        bindings: MaterialBindings,
+        params: MaterialParams,
+        textures: Vec<Handle<wgpu::TextureView>>,
+        device: &wgpu::Device,
+    ) -> MaterialData {
         let uniforms = MaterialUniform {
-            roughness: config.roughness,
-            metallic: config.metallic,
-            _padding: [0.0; 2],
+            roughness: params.roughness,
+            metallic: params.metallic,
+            lod_bias: bindings.lod_bias,
+            _padding: 0.0,
         };
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -149,48 +1513,193 @@ impl AssetManager {
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &layout,
+            layout,
             entries: &[
-                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(diffuse_view) },
-                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.default_sampler) },
+                wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&bindings.diffuse) },
+                wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&bindings.sampler) },
                 wgpu::BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
-                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(normal_view)},
+                wgpu::BindGroupEntry { binding: 3, resource: wgpu::BindingResource::TextureView(&bindings.normal)},
             ],
             label: Some(&format!("BG: {}", name)),
         });
 
-        let id = MaterialId(self.materials.len());
-        self.materials.push(MaterialData {
-            pipeline_name: config.pipeline.clone(),
+        MaterialData {
+            name: name.to_string(),
+            pipeline_name: pipeline.to_string(),
             bind_group,
-        });
+            uniform_buffer,
+            params,
+            bindings,
+            _textures: textures,
+        }
+    }
+
+    // Writes new roughness and metallic values into the material's uniform buffer, entities
+    // using it see them the next frame. The config is updated as well, so a rebuild by hot
+    // reload keeps them.
+    pub fn set_material_params(&mut self, id: MaterialId, params: MaterialParams, queue: &wgpu::Queue) -> Result<(), AssetError> {
+        let material = self.materials.get_mut(id).ok_or(AssetError::StaleHandle { kind: "Material" })?;
+        material.params = params;
+        queue.write_buffer(&material.uniform_buffer, 0, bytemuck::cast_slice(&[material.uniforms()]));
+
+        if let Some(config) = self.material_configs.get_mut(&material.name) {
+            config.roughness = params.roughness;
+            config.metallic = params.metallic;
+        }
+        Ok(())
+    }
+
+    // Binds another texture to one slot of the material, e.g. for damage states. The bind
+    // group is rebuilt in place, the id stays valid. Fails without changes if the texture
+    // can't be used in that slot.
+    pub fn set_material_texture(&mut self, id: MaterialId, slot: TextureSlot, texture: &str, device: &wgpu::Device) -> Result<(), AssetError> {
+        let material = self.materials.get(id).ok_or(AssetError::StaleHandle { kind: "Material" })?;
+        let name = material.name.clone();
+        let mut config = self.material_configs.get(&name).cloned().ok_or(AssetError::UnknownMaterial { name: name.clone() })?;
+        match slot {
+            TextureSlot::Diffuse => config.diffuse = texture.to_string(),
+            TextureSlot::Normal => config.normal = Some(texture.to_string()),
+        }
+        self.create_material(&name, &config, device)?;
+        Ok(())
+    }
+
+    // Registers `name` as a copy of `base` with a uniform buffer of its own, so its params can
+    // be changed without touching the base. Textures, sampler and pipeline are shared, only
+    // the buffer and bind group are new. Starts with the base's current params.
+    pub fn create_material_instance(&mut self, name: &str, base: MaterialId, device: &wgpu::Device) -> Result<MaterialId, AssetError> {
+        let base = self.materials.get(base).ok_or(AssetError::StaleHandle { kind: "Material" })?;
+        let pipeline = self.pipeline_cache.get(&base.pipeline_name)
+            .ok_or_else(|| AssetError::MissingPipeline { material: name.to_string(), pipeline: base.pipeline_name.clone() })?;
+        let layout = pipeline.get_bind_group_layout(2);
+        let material = Self::build_material(
+            name,
+            &base.pipeline_name,
+            &layout,
+            base.bindings.clone(),
+            base.params,
+            base._textures.clone(),
+            device,
+        );
+
+        // Rebuilt from the base's config when its textures or samplers change
+        if let Some(config) = self.material_configs.get(&base.name) {
+            let config = MaterialConfig { roughness: base.params.roughness, metallic: base.params.metallic, ..config.clone() };
+            self.material_configs.insert(name.to_string(), config);
+        }
+        Ok(self.register_material(name, material))
+    }
+
+    fn register_material(&mut self, name: &str, material: MaterialData) -> MaterialId {
+        self.clear_missing("Material", name);
+        if let Some(&id) = self.material_registry.get(name)
+            && self.materials.contains(id)
+        {
+            let old = self.materials.replace(id, material);
+            let holds = |handles: &PackHandles| handles.materials.iter().any(|handle| handle.id() == id);
+            for counter in memory_counters(&mut self.memory, &mut self.packs, holds) {
+                old.iter().for_each(|material| counter.remove_material(material));
+                self.materials.get(id).into_iter().for_each(|material| counter.add_material(material));
+            }
+            return id;
+        }
+        self.memory.add_material(&material);
+        let id = self.materials.insert(material);
         self.material_registry.insert(name.to_string(), id);
+        id
     }
 
+    fn material_texture(
+        &self,
+        material: &str,
+        slot: &'static str,
+        expected: TextureKind,
+        texture: &str,
+    ) -> Result<(&wgpu::TextureView, Handle<wgpu::TextureView>), AssetError> {
+        if self.get_texture_dimension(texture).is_some_and(|dimension| dimension != wgpu::TextureViewDimension::D2) {
+            return Err(AssetError::WrongTextureDimension {
+                material: material.to_string(),
+                slot,
+                texture: texture.to_string(),
+            });
+        }
+        if let Some(&kind) = self.texture_kinds.get(texture)
+            && kind != expected
+        {
+            log::warn!(
+                "Material '{}' uses {:?} texture '{}' in its {} slot, which expects a {:?} texture.",
+                material, kind, texture, slot, expected
+            );
+        }
 
-    fn load_texture_from_path(&self, path: PathBuf, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
-        let img = image::open(&path).expect("Image could not be loaded");
-        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        
-        let format = if file_name.contains("_n.") || file_name.contains("_data.") {
-            wgpu::TextureFormat::Rgba8Unorm // Color data is not in sRGB space for normal maps or
-            // data textures
-        } else {
-            wgpu::TextureFormat::Rgba8UnormSrgb // Regular color textures in sRGB space
-        };
+        self.texture_registry.get(texture)
+            .and_then(|&id| Some((self.texture_views.get(id)?, self.texture_views.handle(id)?)))
+            .ok_or_else(|| AssetError::MissingTexture {
+                material: material.to_string(),
+                slot,
+                texture: texture.to_string(),
+            })
+    }
 
-        let rgba = img.to_rgba8();
-        let dimensions = img.dimensions();
+    fn create_texture(device: &wgpu::Device, queue: &wgpu::Queue, image: &DecodedImage, label: &str) -> Result<wgpu::TextureView, AssetError> {
+        let levels: Vec<&[u8]> = image.levels.iter().map(Vec::as_slice).collect();
         let size = wgpu::Extent3d {
-            width: dimensions.0,
-            height: dimensions.1,
-            depth_or_array_layers: 1,
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: image.layers,
         };
+        Self::create_texture_from_levels(device, queue, size, image.format, &levels, label)
+    }
+
+    // `levels` holds the base level and every mip below it, in `format`, with all layers of
+    // a level back to back. Six layers make a cubemap. BCn data is decompressed to RGBA8
+    // first when the device can't sample it.
+    fn create_texture_from_levels(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: wgpu::Extent3d,
+        format: wgpu::TextureFormat,
+        levels: &[&[u8]],
+        label: &str,
+    ) -> Result<wgpu::TextureView, AssetError> {
+        let error = |reason: String| AssetError::TextureUpload { asset: label.to_string(), reason };
+        let block_size = format.block_copy_size(None)
+            .ok_or_else(|| error(format!("{:?} has no block size", format)))?;
+        let layers = size.depth_or_array_layers as usize;
+        if levels.is_empty() || levels.len() > mip_level_count(size.width, size.height) as usize {
+            return Err(error(format!("{} mip levels don't fit a {}x{} texture", levels.len(), size.width, size.height)));
+        }
+        for (mip_level, data) in levels.iter().enumerate() {
+            let expected = level_size(format, size.width, size.height, mip_level as u32) * layers;
+            if expected == 0 || data.len() != expected {
+                return Err(error(format!("mip {} has {} bytes instead of {}", mip_level, data.len(), expected)));
+            }
+        }
+
+        if format.is_bcn() && !device.features().contains(wgpu::Features::TEXTURE_COMPRESSION_BC) {
+            let decoded: Vec<Vec<u8>> = levels.iter().enumerate()
+                .map(|(mip_level, data)| {
+                    let (width, height) = ((size.width >> mip_level).max(1), (size.height >> mip_level).max(1));
+                    let layer_data = data.chunks_exact(data.len() / layers)
+                        .map(|layer| decompress_bc(format, width, height, layer)
+                            .ok_or_else(|| error(format!("mip {} has the wrong size for {:?}", mip_level, format))))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(layer_data.concat())
+                })
+                .collect::<Result<_, AssetError>>()?;
+            let decoded: Vec<&[u8]> = decoded.iter().map(Vec::as_slice).collect();
+            let rgba_format = if format.is_srgb() {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            };
+            return Self::create_texture_from_levels(device, queue, size, rgba_format, &decoded, label);
+        }
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some(file_name),
+            label: Some(label),
             size,
-            mip_level_count: 1,
+            mip_level_count: levels.len() as u32,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
@@ -198,56 +1707,78 @@ impl AssetManager {
             view_formats: &[],
         });
 
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
-            },
-            size,
-        );
-
-        texture.create_view(&wgpu::TextureViewDescriptor::default())
-    }
+        // Rows are counted in blocks, a block is a single pixel for RGBA8
+        let (block_width, block_height) = format.block_dimensions();
+        for (mip_level, data) in levels.iter().enumerate() {
+            let level_size = size.mip_level_size(mip_level as u32, wgpu::TextureDimension::D2).physical_size(format);
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &texture,
+                    mip_level: mip_level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(level_size.width / block_width * block_size),
+                    rows_per_image: Some(level_size.height / block_height),
+                },
+                level_size,
+            );
+        }
 
-    fn load_mesh_from_path(&self, path: PathBuf, device: &wgpu::Device) -> MeshBuffers {
-        let (models, _) = tobj::load_obj(&path, &tobj::LoadOptions {
-            triangulate: true,
-            single_index: true,
+        Ok(texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(view_dimension(size.depth_or_array_layers)),
             ..Default::default()
-        }).expect("OBJ Error");
-
-        let m = &models[0];
-        let vertices = (0..m.mesh.positions.len() / 3)
-            .map(|i| VertexPTN {
-                position: [m.mesh.positions[i*3], m.mesh.positions[i*3+1], m.mesh.positions[i*3+2]],
-                tex_coords: [m.mesh.texcoords[i*2], 1.0 - m.mesh.texcoords[i*2+1]],
-                normal: if m.mesh.normals.is_empty() { [0.0; 3] } else { 
-                    [m.mesh.normals[i*3], m.mesh.normals[i*3+1], m.mesh.normals[i*3+2]] 
-                },
-            })
-            .collect::<Vec<_>>();
+        }))
+    }
 
+    fn create_mesh_buffers(device: &wgpu::Device, vertices: &[VertexPTNT], indices: &[u32], label: &str) -> MeshBuffers {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
+            label: Some(&format!("{} Vertex Buffer", label)),
+            contents: bytemuck::cast_slice(vertices),
             usage: wgpu::BufferUsages::VERTEX,
         });
 
+        // 16 bit indices halve the index buffer. 0xFFFF stays unused, Metal treats it as a
+        // primitive restart even for triangle lists.
+        let index_format = if vertices.len() < u16::MAX as usize {
+            wgpu::IndexFormat::Uint16
+        } else {
+            wgpu::IndexFormat::Uint32
+        };
+        let short_indices: Vec<u16>;
+        let index_data: &[u8] = match index_format {
+            wgpu::IndexFormat::Uint16 => {
+                short_indices = indices.iter().map(|&index| index as u16).collect();
+                bytemuck::cast_slice(&short_indices)
+            }
+            wgpu::IndexFormat::Uint32 => bytemuck::cast_slice(indices),
+        };
         let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(&m.mesh.indices),
+            label: Some(&format!("{} Index Buffer", label)),
+            contents: index_data,
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        MeshBuffers { vertex_buffer, index_buffer, num_indices: m.mesh.indices.len() as u32 }
+        MeshBuffers {
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_indices: indices.len() as u32,
+            bounds: MeshBounds::from_points(vertices.iter().map(|v| glam::Vec3::from(v.position))),
+            skin: None,
+        }
+    }
+
+    fn create_mesh_skin(&self, device: &wgpu::Device, skin: &str, weights: &[VertexJW], label: &str) -> Result<MeshSkin, AssetError> {
+        let weights_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{} Joint Weight Buffer", label)),
+            contents: bytemuck::cast_slice(weights),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        Ok(MeshSkin { weights_buffer, skin: self.get_skin(skin)? })
     }
 
     fn clear_assets(&mut self) {
@@ -257,18 +1788,21 @@ impl AssetManager {
         self.mesh_registry.clear();
         self.material_registry.clear();
         self.texture_registry.clear();
-    }
-
-    fn create_default_sampler(device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        })
+        self.texture_kinds.clear();
+        self.texture_dimensions.clear();
+        self.texture_samplers.clear();
+        self.sampler_configs.clear();
+        self.model_registry.clear();
+        self.skins.clear();
+        self.mesh_lods.clear();
+        self.material_configs.clear();
+        self.packs.clear();
+        self.memory = MemoryCounter::default();
+        self.sources.clear();
+        self.watcher = None;
+        self.loading = None;
+        self.load_progress = LoadProgress::default();
+        self.missing.get_mut().clear();
     }
 
     fn create_single_pixel_texture(
@@ -332,7 +1866,7 @@ impl AssetManager {
         metallic: f32,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) {
+    ) -> Result<MaterialId, AssetError> {
         let tex_name = format!("{}_diffuse", material_name);
 
         let view = Self::create_single_pixel_texture(
@@ -342,8 +1876,7 @@ impl AssetManager {
             wgpu::TextureFormat::Rgba8UnormSrgb, 
             &tex_name
         );
-        self.texture_registry.insert(tex_name.clone(), TextureId(self.texture_views.len()));
-        self.texture_views.push(view);
+        self.register_texture(&tex_name, view, TextureKind::Color, wgpu::TextureViewDimension::D2);
 
         let config = MaterialConfig {
             pipeline: "standard".to_string(),
@@ -351,142 +1884,116 @@ impl AssetManager {
             normal: None,
             roughness,
             metallic,
+            sampler: None,
         };
 
-        self.create_material(material_name, &config, device);
-    }
-
-    pub fn create_cube_mesh(&mut self, mesh_name: &str, device: &wgpu::Device){
-        let vertices = vec![
-            // ================= FRONT FACE (+Z) =================
-            // Normal: [0.0, 0.0, 1.0]
-            VertexPTN { position: [-0.5, -0.5,  0.5], tex_coords: [0.0, 1.0], normal: [0.0, 0.0, 1.0] }, // Unten Links
-            VertexPTN { position: [ 0.5, -0.5,  0.5], tex_coords: [1.0, 1.0], normal: [0.0, 0.0, 1.0] }, // Unten Rechts
-            VertexPTN { position: [ 0.5,  0.5,  0.5], tex_coords: [1.0, 0.0], normal: [0.0, 0.0, 1.0] }, // Oben Rechts
-            VertexPTN { position: [-0.5,  0.5,  0.5], tex_coords: [0.0, 0.0], normal: [0.0, 0.0, 1.0] }, // Oben Links
-
-            // ================= BACK FACE (-Z) =================
-            // Normal: [0.0, 0.0, -1.0]
-            VertexPTN { position: [ 0.5, -0.5, -0.5], tex_coords: [0.0, 1.0], normal: [0.0, 0.0, -1.0] }, // Unten Links (von hinten betrachtet)
-            VertexPTN { position: [-0.5, -0.5, -0.5], tex_coords: [1.0, 1.0], normal: [0.0, 0.0, -1.0] }, // Unten Rechts
-            VertexPTN { position: [-0.5,  0.5, -0.5], tex_coords: [1.0, 0.0], normal: [0.0, 0.0, -1.0] }, // Oben Rechts
-            VertexPTN { position: [ 0.5,  0.5, -0.5], tex_coords: [0.0, 0.0], normal: [0.0, 0.0, -1.0] }, // Oben Links
-
-            // ================= TOP FACE (+Y) =================
-            // Normal: [0.0, 1.0, 0.0]
-            VertexPTN { position: [-0.5,  0.5,  0.5], tex_coords: [0.0, 1.0], normal: [0.0, 1.0, 0.0] }, // Unten Links (auf der Fläche stehend)
-            VertexPTN { position: [ 0.5,  0.5,  0.5], tex_coords: [1.0, 1.0], normal: [0.0, 1.0, 0.0] }, // Unten Rechts
-            VertexPTN { position: [ 0.5,  0.5, -0.5], tex_coords: [1.0, 0.0], normal: [0.0, 1.0, 0.0] }, // Oben Rechts
-            VertexPTN { position: [-0.5,  0.5, -0.5], tex_coords: [0.0, 0.0], normal: [0.0, 1.0, 0.0] }, // Oben Links
-
-            // ================= BOTTOM FACE (-Y) =================
-            // Normal: [0.0, -1.0, 0.0]
-            VertexPTN { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0], normal: [0.0, -1.0, 0.0] }, 
-            VertexPTN { position: [ 0.5, -0.5, -0.5], tex_coords: [1.0, 1.0], normal: [0.0, -1.0, 0.0] }, 
-            VertexPTN { position: [ 0.5, -0.5,  0.5], tex_coords: [1.0, 0.0], normal: [0.0, -1.0, 0.0] }, 
-            VertexPTN { position: [-0.5, -0.5,  0.5], tex_coords: [0.0, 0.0], normal: [0.0, -1.0, 0.0] }, 
-
-            // ================= RIGHT FACE (+X) =================
-            // Normal: [1.0, 0.0, 0.0]
-            VertexPTN { position: [ 0.5, -0.5,  0.5], tex_coords: [0.0, 1.0], normal: [1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [ 0.5, -0.5, -0.5], tex_coords: [1.0, 1.0], normal: [1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [ 0.5,  0.5, -0.5], tex_coords: [1.0, 0.0], normal: [1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [ 0.5,  0.5,  0.5], tex_coords: [0.0, 0.0], normal: [1.0, 0.0, 0.0] }, 
-
-            // ================= LEFT FACE (-X) =================
-            // Normal: [-1.0, 0.0, 0.0]
-            VertexPTN { position: [-0.5, -0.5, -0.5], tex_coords: [0.0, 1.0], normal: [-1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [-0.5, -0.5,  0.5], tex_coords: [1.0, 1.0], normal: [-1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [-0.5,  0.5,  0.5], tex_coords: [1.0, 0.0], normal: [-1.0, 0.0, 0.0] }, 
-            VertexPTN { position: [-0.5,  0.5, -0.5], tex_coords: [0.0, 0.0], normal: [-1.0, 0.0, 0.0] }, 
-        ];
-
-        let indices: Vec<u32> = vec![
-            0, 1, 2,  2, 3, 0,       // Front
-            4, 5, 6,  6, 7, 4,       // Back
-            8, 9, 10, 10, 11, 8,     // Top
-            12, 13, 14, 14, 15, 12,  // Bottom
-            16, 17, 18, 18, 19, 16,  // Right
-            20, 21, 22, 22, 23, 20,  // Left
-        ];
-
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Internal Cube Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Internal Cube Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-        
-        let mesh = MeshBuffers { vertex_buffer, index_buffer, num_indices: indices.len() as u32 };
-        self.mesh_registry.insert(mesh_name.to_string(), MeshId(self.meshes.len()));
-        self.meshes.push(mesh);
+        self.create_material(material_name, &config, device)
     }
 
-    pub fn create_sphere_mesh(&mut self, mesh_name : &str,  device: &wgpu::Device, radius: f32, lat_bands: u32, lon_bands: u32){
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-
-        // 1. Vertices generieren (Breitengrade und Längengrade)
-        for lat in 0..=lat_bands {
-            let theta = (lat as f32 * PI) / lat_bands as f32;
-            let sin_theta = theta.sin();
-            let cos_theta = theta.cos();
+    // Generates `primitive` and registers it under `mesh_name`, replacing an existing mesh
+    // with that name in place
+    pub fn create_primitive_mesh(&mut self, mesh_name: &str, primitive: &Primitive, device: &wgpu::Device) -> MeshId {
+        let (vertices, indices) = primitive.generate();
+        let mesh = Self::create_mesh_buffers(device, &vertices, &indices, mesh_name);
+        self.register_mesh(mesh_name, mesh)
+    }
+}
 
-            for lon in 0..=lon_bands {
-                let phi = (lon as f32 * 2.0 * PI) / lon_bands as f32;
-                let sin_phi = phi.sin();
-                let cos_phi = phi.cos();
+// The total and the counters of every pack that `holds` an asset, all of them have to
+// follow when the asset is replaced
+fn memory_counters<'a>(
+    memory: &'a mut MemoryCounter,
+    packs: &'a mut [LoadedPack],
+    holds: impl Fn(&PackHandles) -> bool + 'a,
+) -> impl Iterator<Item = &'a mut MemoryCounter> {
+    let packs = packs.iter_mut().filter(move |pack| holds(&pack.handles)).map(|pack| &mut pack.memory);
+    std::iter::once(memory).chain(packs)
+}
 
-                let nx = cos_phi * sin_theta;
-                let ny = cos_theta;
-                let nz = sin_phi * sin_theta;
+// Six layers are a cubemap, everything else a plain 2D texture
+fn view_dimension(layers: u32) -> wgpu::TextureViewDimension {
+    if layers == 6 { wgpu::TextureViewDimension::Cube } else { wgpu::TextureViewDimension::D2 }
+}
 
-                let u = lon as f32 / lon_bands as f32;
-                let v = lat as f32 / lat_bands as f32;
+// Load jobs for every manifest entry that is new or differs from `previous`
+pub(crate) fn manifest_jobs(manifest: &AssetManifest, previous: &AssetManifest) -> Vec<LoadJob> {
+    let mut jobs = Vec::new();
+    for (name, entry) in &manifest.textures {
+        if previous.textures.get(name) == Some(entry) {
+            continue;
+        }
+        let source = TextureSource {
+            path: PathBuf::from(entry.path()),
+            format: entry.color_space().format(),
+            kind: entry.kind(),
+            mipmaps: entry.mipmaps(),
+        };
+        jobs.push(LoadJob::Texture { name: name.clone(), source });
+    }
 
-                vertices.push(VertexPTN {
-                    position: [nx * radius, ny * radius, nz * radius],
-                    tex_coords: [u, v],
-                    normal: [nx, ny, nz],
-                });
+    // LOD levels with a file of their own are loaded like separate meshes, the others are
+    // simplified from the mesh's file by its job
+    for (name, entry) in &manifest.meshes {
+        if previous.meshes.get(name) == Some(entry) {
+            continue;
+        }
+        let mut simplify = Vec::new();
+        for (level, lod) in (1..).zip(entry.lods()) {
+            match &lod.path {
+                Some(path) => jobs.push(LoadJob::Obj { name: lod_name(name, level), path: PathBuf::from(path), simplify: Vec::new() }),
+                None => simplify.push((level, lod.simplify_ratio(level))),
             }
         }
+        jobs.push(LoadJob::Obj { name: name.clone(), path: PathBuf::from(entry.path()), simplify });
+    }
 
-        for lat in 0..lat_bands {
-            for lon in 0..lon_bands {
-                let first = lat * (lon_bands + 1) + lon;
-                let second = first + lon_bands + 1;
-                indices.push(first);
-                indices.push(first + 1);
-                indices.push(second);
-
-                indices.push(first + 1);
-                indices.push(second + 1);
-                indices.push(second);
-            }
+    for (name, path) in &manifest.gltf {
+        if previous.gltf.get(name) != Some(path) {
+            jobs.push(LoadJob::Gltf { name: name.clone(), path: PathBuf::from(path) });
         }
+    }
 
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Internal Sphere Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+    for (name, primitive) in &manifest.primitives {
+        if previous.primitives.get(name) != Some(primitive) {
+            jobs.push(LoadJob::Primitive { name: name.clone(), primitive: primitive.clone() });
+        }
+    }
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Internal Sphere Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+    for (name, config) in &manifest.cubemaps {
+        if previous.cubemaps.get(name) != Some(config) {
+            jobs.push(LoadJob::Cubemap { name: name.clone(), config: config.clone() });
+        }
+    }
+    jobs
+}
 
-        let mesh = MeshBuffers { vertex_buffer, index_buffer, num_indices: indices.len() as u32 };
-        self.mesh_registry.insert(mesh_name.to_string(), MeshId(self.meshes.len()));
-        self.meshes.push(mesh);
+// Config of the "internal:error" material, which broken materials are built like
+pub(crate) fn error_material_config() -> MaterialConfig {
+    MaterialConfig {
+        pipeline: "standard".to_string(),
+        diffuse: ERROR_TEXTURE.to_string(),
+        normal: None,
+        roughness: 1.0,
+        metallic: 0.0,
+        sampler: None,
     }
 }
 
+// Thresholds of every mesh entry's LOD levels, empty for meshes without any
+pub(crate) fn lod_chains(manifest: &AssetManifest) -> Vec<(String, Vec<LodThreshold>)> {
+    let mut chains: Vec<_> = manifest.meshes.iter()
+        .map(|(name, entry)| (name.clone(), entry.lods().iter().map(|lod| lod.threshold).collect()))
+        .collect();
+    chains.sort_by(|a, b| a.0.cmp(&b.0));
+    chains
+}
 
+// Magenta and black squares of `square` pixels, hard to overlook in any scene
+fn checkerboard(size: u32, square: u32) -> Vec<u8> {
+    (0..size * size)
+        .flat_map(|i| {
+            let (x, y) = (i % size / square, i / size / square);
+            if (x + y) % 2 == 0 { [255, 0, 255, 255] } else { [0, 0, 0, 255] }
+        })
+        .collect()
+}
@@ -0,0 +1,144 @@
+use glam::{Mat4, Vec3};
+
+// Axis-aligned box. An empty point set gives a zero-sized box at the origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        let (min, max) = points.into_iter()
+            .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), p| (min.min(p), max.max(p)));
+        if min.cmpgt(max).any() {
+            return Self { min: Vec3::ZERO, max: Vec3::ZERO };
+        }
+        Self { min, max }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    // Smallest axis-aligned box around the transformed box
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let center = matrix.transform_point3(self.center());
+        let half_extents = self.half_extents();
+        let extent = matrix.x_axis.truncate().abs() * half_extents.x
+            + matrix.y_axis.truncate().abs() * half_extents.y
+            + matrix.z_axis.truncate().abs() * half_extents.z;
+        Self { min: center - extent, max: center + extent }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    // Centered on the box of the points, not minimal but cheap and close for most meshes
+    pub fn from_points(aabb: &Aabb, points: impl IntoIterator<Item = Vec3>) -> Self {
+        let center = aabb.center();
+        let radius = points.into_iter()
+            .map(|p| p.distance_squared(center))
+            .fold(0.0, f32::max)
+            .sqrt();
+        Self { center, radius }
+    }
+
+    // Non-uniform scale grows the radius by the largest axis
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        let scale = matrix.x_axis.truncate().length()
+            .max(matrix.y_axis.truncate().length())
+            .max(matrix.z_axis.truncate().length());
+        Self { center: matrix.transform_point3(self.center), radius: self.radius * scale }
+    }
+}
+
+// Local-space bounds, computed once when a mesh is created
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshBounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+
+impl MeshBounds {
+    pub fn from_points<I>(points: I) -> Self
+    where
+        I: IntoIterator<Item = Vec3>,
+        I::IntoIter: Clone,
+    {
+        let points = points.into_iter();
+        let aabb = Aabb::from_points(points.clone());
+        let sphere = BoundingSphere::from_points(&aabb, points);
+        Self { aabb, sphere }
+    }
+
+    pub fn transformed(&self, matrix: &Mat4) -> Self {
+        Self { aabb: self.aabb.transformed(matrix), sphere: self.sphere.transformed(matrix) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Quat;
+    use crate::primitives::Primitive;
+
+    fn primitive_bounds(primitive: Primitive) -> MeshBounds {
+        let (vertices, _) = primitive.generate();
+        MeshBounds::from_points(vertices.iter().map(|v| Vec3::from(v.position)))
+    }
+
+    #[test]
+    fn unit_cube_bounds() {
+        let bounds = primitive_bounds(Primitive::unit_box());
+        assert_eq!(bounds.aabb, Aabb { min: Vec3::splat(-0.5), max: Vec3::splat(0.5) });
+        assert_eq!(bounds.sphere.center, Vec3::ZERO);
+        assert!((bounds.sphere.radius - 0.75f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sphere_bounds_match_the_radius() {
+        let bounds = primitive_bounds(Primitive::Icosphere { radius: 2.0, subdivisions: 2 });
+        assert!(bounds.aabb.min.abs_diff_eq(Vec3::splat(-2.0), 1e-5) && bounds.aabb.max.abs_diff_eq(Vec3::splat(2.0), 1e-5));
+        assert!(bounds.sphere.center.abs_diff_eq(Vec3::ZERO, 1e-5));
+        assert!((bounds.sphere.radius - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn empty_point_sets_give_zero_bounds() {
+        let bounds = MeshBounds::from_points(std::iter::empty());
+        assert_eq!(bounds.aabb, Aabb { min: Vec3::ZERO, max: Vec3::ZERO });
+        assert_eq!(bounds.sphere, BoundingSphere { center: Vec3::ZERO, radius: 0.0 });
+    }
+
+    #[test]
+    fn transformed_bounds_contain_the_transformed_points() {
+        let (vertices, _) = Primitive::unit_box().generate();
+        let points: Vec<Vec3> = vertices.iter().map(|v| Vec3::from(v.position)).collect();
+        let bounds = MeshBounds::from_points(points.iter().copied());
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::new(1.0, 2.0, 3.0),
+            Quat::from_rotation_y(0.7),
+            Vec3::new(5.0, -1.0, 2.0),
+        );
+        let world = bounds.transformed(&matrix);
+        let exact = Aabb::from_points(points.iter().map(|&p| matrix.transform_point3(p)));
+
+        // A box transforms exactly, the sphere has to hold every corner
+        assert!(world.aabb.min.abs_diff_eq(exact.min, 1e-5) && world.aabb.max.abs_diff_eq(exact.max, 1e-5));
+        assert_eq!(world.sphere.center, Vec3::new(5.0, -1.0, 2.0));
+        assert!((world.sphere.radius - 0.75f32.sqrt() * 3.0).abs() < 1e-5);
+        for p in &points {
+            assert!(matrix.transform_point3(*p).distance(world.sphere.center) <= world.sphere.radius + 1e-5);
+        }
+    }
+}
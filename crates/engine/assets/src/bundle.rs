@@ -0,0 +1,491 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use engine_gpu_types::{VertexJW, VertexPTNT};
+use engine_vfs::{FileData, Vfs};
+use crate::asset_error::AssetError;
+use crate::asset_manager::{AssetManifest, ColorSpace, LodThreshold, MaterialConfig, TextureKind, lod_chains, manifest_jobs};
+use crate::compressed::level_size;
+use crate::loader::{DecodedAsset, DecodedImage, ModelParts, ResolvedImport, spawn_workers};
+use crate::packs::read_manifest_tree;
+use crate::samplers::SamplerConfig;
+use crate::skin::Skin;
+
+// Cooked bundle layout:
+//   header   magic "ABDL", format version, CRC32 of everything after the header, TOC length
+//   TOC      JSON table of contents, the only part that is parsed
+//   data     texture levels and vertex/index/joint weight arrays, each aligned to 16 bytes
+//            within the file. The file itself may sit at any address in memory.
+// All integers are little endian. Bumping BUNDLE_VERSION makes old bundles fail to load
+// instead of being misread, they have to be cooked again.
+pub const BUNDLE_VERSION: u32 = 5;
+pub const BUNDLE_EXTENSION: &str = "bundle";
+const MAGIC: [u8; 4] = *b"ABDL";
+const HEADER_LEN: usize = 16;
+const ALIGN: usize = 16;
+
+pub fn is_bundle_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION)
+}
+
+// Byte range inside the data section
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub(crate) struct BlobRange {
+    offset: u64,
+    len: u64,
+}
+
+// How texture levels are stored. Together with the color space this gives the wgpu format.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum TextureEncoding {
+    Rgba8,
+    Rgba16f,
+    Bc1,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc7,
+}
+
+impl TextureEncoding {
+    // None for formats bundles can't store
+    fn of(format: wgpu::TextureFormat) -> Option<Self> {
+        use wgpu::TextureFormat as F;
+        Some(match format.remove_srgb_suffix() {
+            F::Rgba8Unorm => TextureEncoding::Rgba8,
+            F::Rgba16Float => TextureEncoding::Rgba16f,
+            F::Bc1RgbaUnorm => TextureEncoding::Bc1,
+            F::Bc3RgbaUnorm => TextureEncoding::Bc3,
+            F::Bc4RUnorm => TextureEncoding::Bc4,
+            F::Bc5RgUnorm => TextureEncoding::Bc5,
+            F::Bc7RgbaUnorm => TextureEncoding::Bc7,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct BundleTexture {
+    pub name: String,
+    pub kind: TextureKind,
+    pub color_space: ColorSpace,
+    pub encoding: TextureEncoding,
+    pub width: u32,
+    pub height: u32,
+    // 6 for cubemaps, every level holds all layers
+    pub layers: u32,
+    pub sampler: Option<String>,
+    // Base level followed by the mip chain
+    levels: Vec<BlobRange>,
+}
+
+impl BundleTexture {
+    pub fn format(&self) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat as F;
+        let format = match self.encoding {
+            TextureEncoding::Rgba8 => F::Rgba8Unorm,
+            TextureEncoding::Rgba16f => F::Rgba16Float,
+            TextureEncoding::Bc1 => F::Bc1RgbaUnorm,
+            TextureEncoding::Bc3 => F::Bc3RgbaUnorm,
+            TextureEncoding::Bc4 => F::Bc4RUnorm,
+            TextureEncoding::Bc5 => F::Bc5RgUnorm,
+            TextureEncoding::Bc7 => F::Bc7RgbaUnorm,
+        };
+        match self.color_space {
+            ColorSpace::Srgb => format.add_srgb_suffix(),
+            ColorSpace::Linear => format,
+        }
+    }
+
+    pub fn size(&self) -> wgpu::Extent3d {
+        wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: self.layers }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct BundleMesh {
+    pub name: String,
+    vertices: BlobRange,
+    indices: BlobRange,
+    // Skin name and joint weights of skinned meshes
+    skin: Option<(String, BlobRange)>,
+}
+
+// Everything the asset manager registers, with OBJ/glTF imports already resolved into
+// plain textures, meshes and materials
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct BundleToc {
+    pub textures: Vec<BundleTexture>,
+    pub meshes: Vec<BundleMesh>,
+    pub materials: Vec<(String, MaterialConfig)>,
+    pub samplers: HashMap<String, SamplerConfig>,
+    pub models: Vec<(String, ModelParts)>,
+    pub aliases: Vec<(String, String)>,
+    pub skins: Vec<(String, Skin)>,
+    // LOD thresholds of the meshes with a chain, the levels are models like any other
+    pub lods: Vec<(String, Vec<LodThreshold>)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BundleEntry {
+    Texture(usize),
+    Mesh(usize),
+}
+
+// A verified bundle, memory-mapped when it is a file on disk. Texture and mesh data is
+// handed to wgpu straight from it without being copied or parsed.
+pub(crate) struct AssetBundle {
+    pub path: PathBuf,
+    pub toc: BundleToc,
+    map: FileData,
+    data_start: usize,
+}
+
+impl AssetBundle {
+    pub fn open(vfs: &Vfs, path: &Path) -> Result<Self, AssetError> {
+        // The engine never writes bundles it has open, the checksum below catches files that
+        // were replaced while being mapped
+        let map = vfs.open(path).map_err(|source| AssetError::BundleRead { path: path.to_path_buf(), source })?;
+
+        let invalid = |reason: String| AssetError::InvalidBundle { path: path.to_path_buf(), reason };
+        if map.len() < HEADER_LEN || map[0..4] != MAGIC {
+            return Err(invalid("not an asset bundle".to_string()));
+        }
+        let version = read_u32(&map, 4);
+        if version != BUNDLE_VERSION {
+            return Err(invalid(format!(
+                "format version {} is not supported, cook it again for version {}",
+                version, BUNDLE_VERSION
+            )));
+        }
+        if crc32fast::hash(&map[HEADER_LEN..]) != read_u32(&map, 8) {
+            return Err(invalid("checksum mismatch, the file is corrupt or truncated".to_string()));
+        }
+
+        let toc_end = HEADER_LEN + read_u32(&map, 12) as usize;
+        let toc_bytes = map.get(HEADER_LEN..toc_end)
+            .ok_or_else(|| invalid("table of contents is out of bounds".to_string()))?;
+        let toc = serde_json::from_slice(toc_bytes)
+            .map_err(|e| invalid(format!("table of contents could not be parsed: {}", e)))?;
+
+        Ok(Self { path: path.to_path_buf(), toc, map, data_start: align(toc_end) })
+    }
+
+    // Textures first, then meshes. Materials and models are in the TOC itself.
+    pub fn entries(&self) -> Vec<(String, BundleEntry)> {
+        let textures = self.toc.textures.iter().enumerate()
+            .map(|(i, texture)| (texture.name.clone(), BundleEntry::Texture(i)));
+        let meshes = self.toc.meshes.iter().enumerate()
+            .map(|(i, mesh)| (mesh.name.clone(), BundleEntry::Mesh(i)));
+        textures.chain(meshes).collect()
+    }
+
+    pub fn texture_levels(&self, texture: &BundleTexture) -> Result<Vec<&[u8]>, AssetError> {
+        let format = texture.format();
+        texture.levels.iter().enumerate()
+            .map(|(level, &range)| {
+                let bytes = self.bytes(range)?;
+                let expected = level_size(format, texture.width, texture.height, level as u32) * texture.layers as usize;
+                if bytes.len() != expected {
+                    return Err(self.invalid(format!("mip {} of texture '{}' has the wrong size", level, texture.name)));
+                }
+                Ok(bytes)
+            })
+            .collect()
+    }
+
+    pub fn mesh_data(&self, mesh: &BundleMesh) -> Result<(PodData<'_, VertexPTNT>, PodData<'_, u32>), AssetError> {
+        let wrong_size = || self.invalid(format!("data of mesh '{}' has the wrong size", mesh.name));
+        let vertices = pod_slice(self.bytes(mesh.vertices)?).ok_or_else(wrong_size)?;
+        let indices = pod_slice(self.bytes(mesh.indices)?).ok_or_else(wrong_size)?;
+        Ok((vertices, indices))
+    }
+
+    pub fn mesh_skin<'a>(&'a self, mesh: &'a BundleMesh) -> Result<Option<(&'a str, PodData<'a, VertexJW>)>, AssetError> {
+        let Some((skin, weights)) = &mesh.skin else { return Ok(None) };
+        let weights = pod_slice(self.bytes(*weights)?)
+            .ok_or_else(|| self.invalid(format!("joint weights of mesh '{}' have the wrong size", mesh.name)))?;
+        Ok(Some((skin, weights)))
+    }
+
+    fn bytes(&self, range: BlobRange) -> Result<&[u8], AssetError> {
+        let start = self.data_start + range.offset as usize;
+        self.map.get(start..start + range.len as usize)
+            .ok_or_else(|| self.invalid("data range is out of bounds".to_string()))
+    }
+
+    fn invalid(&self, reason: String) -> AssetError {
+        AssetError::InvalidBundle { path: self.path.clone(), reason }
+    }
+}
+
+// Array read from a bundle, borrowed from the mapping or copied
+pub type PodData<'a, T> = Cow<'a, [T]>;
+
+// Borrows `bytes` as a slice of `T` when it is aligned and copies it otherwise. Embedded
+// files and zip entries only guarantee 1-byte alignment. None if the length doesn't fit.
+fn pod_slice<T: bytemuck::Pod>(bytes: &[u8]) -> Option<PodData<'_, T>> {
+    if !bytes.len().is_multiple_of(size_of::<T>()) {
+        return None;
+    }
+    Some(match bytemuck::try_cast_slice(bytes) {
+        Ok(slice) => Cow::Borrowed(slice),
+        Err(_) => Cow::Owned(bytemuck::pod_collect_to_vec(bytes)),
+    })
+}
+
+// Totals of a cooked bundle
+#[derive(Debug, Clone, Default)]
+pub struct CookSummary {
+    pub textures: usize,
+    pub meshes: usize,
+    pub materials: usize,
+    pub models: usize,
+    pub bytes: u64,
+}
+
+// Decodes everything `manifest_path` references and writes it into one bundle at `output`.
+// Entries are sorted by name, so the same sources always give the same file.
+pub fn cook_manifest(vfs: &Vfs, manifest_path: &Path, output: &Path) -> Result<CookSummary, AssetError> {
+    let tree = read_manifest_tree(vfs, manifest_path)?;
+    // A bundle has no placeholders to fall back to
+    if let Some((_, error)) = tree.broken_materials.into_iter().next() {
+        return Err(error);
+    }
+    let manifest = tree.manifest;
+    let jobs = manifest_jobs(&manifest, &AssetManifest::default());
+    let job_count = jobs.len();
+    let results = spawn_workers(jobs, vfs);
+
+    let mut assets = Vec::with_capacity(job_count);
+    for _ in 0..job_count {
+        assets.push(results.recv().map_err(|_| AssetError::LoaderPanicked)??);
+    }
+    assets.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let mut writer = BundleWriter::new(output);
+    for asset in assets {
+        match asset {
+            DecodedAsset::Texture { name, source, image } => {
+                let sampler = manifest.textures.get(&name).and_then(|entry| entry.sampler()).map(str::to_string);
+                writer.texture(name, source.kind, &image, sampler)?;
+            }
+            DecodedAsset::Obj { name, obj, .. } => {
+                obj.warnings.iter().for_each(|warning| log::warn!("{}", warning));
+                writer.import(obj.resolve(&name))?;
+            }
+            DecodedAsset::Gltf { name, gltf, .. } => writer.import(gltf.resolve(&name))?,
+            DecodedAsset::Mesh { name, vertices, indices } => writer.mesh(name, &vertices, &indices, None),
+            DecodedAsset::Cubemap { name, image } => writer.texture(name, TextureKind::Color, &image, None)?,
+            DecodedAsset::Bundled { .. } => unreachable!("manifest jobs never produce bundled assets"),
+        }
+    }
+
+    writer.toc.lods = lod_chains(&manifest).into_iter().filter(|(_, thresholds)| !thresholds.is_empty()).collect();
+    let mut materials: Vec<_> = manifest.materials.into_iter().collect();
+    materials.sort_by(|a, b| a.0.cmp(&b.0));
+    writer.toc.materials.extend(materials);
+    writer.toc.samplers = manifest.samplers;
+
+    writer.write()
+}
+
+struct BundleWriter {
+    path: PathBuf,
+    toc: BundleToc,
+    data: Vec<u8>,
+}
+
+impl BundleWriter {
+    fn new(path: &Path) -> Self {
+        Self { path: path.to_path_buf(), toc: BundleToc::default(), data: Vec::new() }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> BlobRange {
+        self.data.resize(align(self.data.len()), 0);
+        let offset = self.data.len() as u64;
+        self.data.extend_from_slice(bytes);
+        BlobRange { offset, len: bytes.len() as u64 }
+    }
+
+    fn texture(&mut self, name: String, kind: TextureKind, image: &DecodedImage, sampler: Option<String>) -> Result<(), AssetError> {
+        let encoding = TextureEncoding::of(image.format).ok_or_else(|| AssetError::InvalidBundle {
+            path: self.path.clone(),
+            reason: format!("texture '{}' has the format {:?}, which bundles can't store", name, image.format),
+        })?;
+        let levels = image.levels.iter().map(|level| self.push(level)).collect();
+        let color_space = if image.format.is_srgb() { ColorSpace::Srgb } else { ColorSpace::Linear };
+        self.toc.textures.push(BundleTexture {
+            name,
+            kind,
+            color_space,
+            encoding,
+            width: image.width,
+            height: image.height,
+            layers: image.layers,
+            sampler,
+            levels,
+        });
+        Ok(())
+    }
+
+    fn mesh(&mut self, name: String, vertices: &[VertexPTNT], indices: &[u32], skin: Option<(String, &[VertexJW])>) {
+        let vertices = self.push(bytemuck::cast_slice(vertices));
+        let indices = self.push(bytemuck::cast_slice(indices));
+        let skin = skin.map(|(skin, weights)| (skin, self.push(bytemuck::cast_slice(weights))));
+        self.toc.meshes.push(BundleMesh { name, vertices, indices, skin });
+    }
+
+    fn import(&mut self, import: ResolvedImport) -> Result<(), AssetError> {
+        for texture in import.textures {
+            self.texture(texture.name, texture.kind, &texture.image, None)?;
+        }
+        for mesh in import.meshes {
+            let skin = mesh.skin.as_ref().map(|(skin, weights)| (skin.clone(), weights.as_slice()));
+            self.mesh(mesh.name, &mesh.vertices, &mesh.indices, skin);
+        }
+        self.toc.materials.extend(import.materials);
+        self.toc.models.extend(import.models);
+        self.toc.aliases.extend(import.aliases);
+        self.toc.skins.extend(import.skins);
+        Ok(())
+    }
+
+    fn write(self) -> Result<CookSummary, AssetError> {
+        let path = self.path.as_path();
+        let mut body = serde_json::to_vec(&self.toc).map_err(|e| AssetError::InvalidBundle {
+            path: path.to_path_buf(),
+            reason: format!("table of contents could not be written: {}", e),
+        })?;
+        let toc_len = body.len();
+        body.resize(align(HEADER_LEN + toc_len) - HEADER_LEN, 0);
+        body.extend_from_slice(&self.data);
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&BUNDLE_VERSION.to_le_bytes());
+        header.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        header.extend_from_slice(&(toc_len as u32).to_le_bytes());
+
+        let write_error = |source| AssetError::BundleWrite { path: path.to_path_buf(), source };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(write_error)?;
+        }
+        let mut file = File::create(path).map_err(write_error)?;
+        file.write_all(&header).map_err(write_error)?;
+        file.write_all(&body).map_err(write_error)?;
+
+        Ok(CookSummary {
+            textures: self.toc.textures.len(),
+            meshes: self.toc.meshes.len(),
+            materials: self.toc.materials.len(),
+            models: self.toc.models.len(),
+            bytes: (HEADER_LEN + body.len()) as u64,
+        })
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn align(offset: usize) -> usize {
+    offset.next_multiple_of(ALIGN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pod_slice_copies_unaligned_data() {
+        let values = [1u32, 2, 3, 4];
+        let mut bytes = vec![0u8];
+        bytes.extend_from_slice(bytemuck::cast_slice(&values));
+        let aligned: PodData<u32> = pod_slice(bytemuck::cast_slice(&values)).unwrap();
+        let unaligned: PodData<u32> = pod_slice(&bytes[1..]).unwrap();
+        assert!(matches!(aligned, Cow::Borrowed(_)));
+        assert!(matches!(unaligned, Cow::Owned(_)));
+        assert_eq!(&*unaligned, &values);
+        assert!(pod_slice::<u32>(&bytes[1..6]).is_none());
+    }
+
+    fn temp_bundle(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("engine_assets_{}_{}.bundle", std::process::id(), name))
+    }
+
+    fn image(format: wgpu::TextureFormat) -> DecodedImage {
+        DecodedImage { width: 2, height: 2, layers: 1, format, levels: vec![vec![7; 16]] }
+    }
+
+    fn open_error(path: &Path) -> String {
+        match AssetBundle::open(&Vfs::new(), path) {
+            Err(AssetError::InvalidBundle { reason, .. }) => reason,
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("bundle was accepted"),
+        }
+    }
+
+    #[test]
+    fn written_bundles_open_again() {
+        let path = temp_bundle("round_trip");
+        let vertex = VertexPTNT { position: [1.0, 2.0, 3.0], tex_coords: [0.5, 0.5], normal: [0.0, 1.0, 0.0], tangent: [1.0, 0.0, 0.0, 1.0] };
+        let mut writer = BundleWriter::new(&path);
+        writer.texture("albedo".to_string(), TextureKind::Color, &image(wgpu::TextureFormat::Rgba8UnormSrgb), None).unwrap();
+        writer.mesh("triangle".to_string(), &[vertex; 3], &[0, 1, 2], None);
+        let summary = writer.write().unwrap();
+        assert_eq!((summary.textures, summary.meshes), (1, 1));
+
+        // Absolute paths bypass the mounts
+        let bundle = AssetBundle::open(&Vfs::new(), &path).unwrap();
+        let texture = &bundle.toc.textures[0];
+        assert_eq!(texture.format(), wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(bundle.texture_levels(texture).unwrap(), vec![&[7u8; 16][..]]);
+        let (vertices, indices) = bundle.mesh_data(&bundle.toc.meshes[0]).unwrap();
+        assert_eq!(vertices[2].position, [1.0, 2.0, 3.0]);
+        assert_eq!(&*indices, &[0, 1, 2]);
+        assert!(bundle.mesh_skin(&bundle.toc.meshes[0]).unwrap().is_none());
+        drop(bundle);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn damaged_bundles_are_rejected() {
+        let path = temp_bundle("damaged");
+        let mut writer = BundleWriter::new(&path);
+        writer.mesh("point".to_string(), &[bytemuck::Zeroable::zeroed()], &[0], None);
+        writer.write().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        std::fs::write(&path, &bad_magic).unwrap();
+        assert!(open_error(&path).contains("not an asset bundle"));
+
+        let mut old_version = bytes.clone();
+        old_version[4..8].copy_from_slice(&(BUNDLE_VERSION - 1).to_le_bytes());
+        std::fs::write(&path, &old_version).unwrap();
+        assert!(open_error(&path).contains("format version"));
+
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() ^= 0xFF;
+        std::fs::write(&path, &corrupt).unwrap();
+        assert!(open_error(&path).contains("checksum mismatch"));
+
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(open_error(&path).contains("checksum mismatch"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unsupported_texture_formats_are_errors() {
+        let mut writer = BundleWriter::new(&temp_bundle("unsupported"));
+        let result = writer.texture("depth".to_string(), TextureKind::Color, &image(wgpu::TextureFormat::R32Float), None);
+        assert!(matches!(result, Err(AssetError::InvalidBundle { .. })));
+        assert!(writer.toc.textures.is_empty());
+    }
+}
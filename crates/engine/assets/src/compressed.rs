@@ -0,0 +1,166 @@
+use std::path::Path;
+use ddsfile::{Caps2, Dds, DxgiFormat, FourCC, MiscFlag};
+use engine_textures::mip_level_count;
+use crate::asset_error::AssetError;
+use crate::loader::DecodedImage;
+
+// KTX2 and DDS containers with BC1/BC3/BC4/BC5/BC7 payloads. Blocks and the stored mip chain
+// are kept as they are, adapters without BC support get them decompressed at upload time.
+pub(crate) fn is_compressed_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ktx2") || ext.eq_ignore_ascii_case("dds"))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BlockFormat {
+    Bc1,
+    Bc3,
+    Bc4,
+    Bc5,
+    Bc7,
+}
+
+impl BlockFormat {
+    // BC4 and BC5 have no sRGB variant
+    fn texture_format(self, srgb: bool) -> wgpu::TextureFormat {
+        use wgpu::TextureFormat as F;
+        match (self, srgb) {
+            (BlockFormat::Bc1, false) => F::Bc1RgbaUnorm,
+            (BlockFormat::Bc1, true) => F::Bc1RgbaUnormSrgb,
+            (BlockFormat::Bc3, false) => F::Bc3RgbaUnorm,
+            (BlockFormat::Bc3, true) => F::Bc3RgbaUnormSrgb,
+            (BlockFormat::Bc4, _) => F::Bc4RUnorm,
+            (BlockFormat::Bc5, _) => F::Bc5RgUnorm,
+            (BlockFormat::Bc7, false) => F::Bc7RgbaUnorm,
+            (BlockFormat::Bc7, true) => F::Bc7RgbaUnormSrgb,
+        }
+    }
+}
+
+struct Container {
+    format: BlockFormat,
+    width: u32,
+    height: u32,
+    levels: Vec<Vec<u8>>,
+}
+
+// The color space comes from the manifest like for every other texture, the sRGB flag of the
+// file's format is ignored. Without `mipmaps` only the base level is kept.
+pub(crate) fn read_compressed(asset: &str, path: &Path, bytes: &[u8], srgb: bool, mipmaps: bool) -> Result<DecodedImage, AssetError> {
+    let invalid = |reason: String| AssetError::InvalidTexture {
+        asset: asset.to_string(),
+        path: path.to_path_buf(),
+        reason,
+    };
+    let is_ktx2 = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("ktx2"));
+    let container = if is_ktx2 { read_ktx2(bytes) } else { read_dds(bytes) }.map_err(invalid)?;
+    let Container { format, width, height, mut levels } = container;
+
+    // wgpu only creates BC textures whose base level is made of whole blocks
+    if width % 4 != 0 || height % 4 != 0 {
+        return Err(invalid(format!("{}x{} is not a multiple of the 4x4 block size", width, height)));
+    }
+    if levels.len() > mip_level_count(width, height) as usize {
+        return Err(invalid(format!("{} mip levels are more than a {}x{} texture can have", levels.len(), width, height)));
+    }
+    if srgb && matches!(format, BlockFormat::Bc4 | BlockFormat::Bc5) {
+        log::warn!("Texture '{}' is {:?}, which has no sRGB variant. It is sampled as linear data.", asset, format);
+    }
+
+    let format = format.texture_format(srgb);
+    for (level, data) in levels.iter().enumerate() {
+        let expected = level_size(format, width, height, level as u32);
+        if data.len() != expected {
+            return Err(invalid(format!("mip {} has {} bytes instead of {}", level, data.len(), expected)));
+        }
+    }
+    if !mipmaps {
+        levels.truncate(1);
+    }
+
+    Ok(DecodedImage { width, height, layers: 1, format, levels })
+}
+
+// Bytes of one mip level, rounded up to whole blocks
+pub(crate) fn level_size(format: wgpu::TextureFormat, width: u32, height: u32, level: u32) -> usize {
+    let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 }
+        .mip_level_size(level, wgpu::TextureDimension::D2)
+        .physical_size(format);
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = format.block_copy_size(None).expect("texture formats have a block size");
+    ((size.width / block_width) * (size.height / block_height) * block_size) as usize
+}
+
+fn read_ktx2(bytes: &[u8]) -> Result<Container, String> {
+    use ktx2::Format as K;
+    let reader = ktx2::Reader::new(bytes).map_err(|e| e.to_string())?;
+    let header = reader.header();
+    if let Some(scheme) = header.supercompression_scheme {
+        return Err(format!("supercompression {:?} is not supported", scheme));
+    }
+    if header.pixel_depth > 1 || header.layer_count > 1 || header.face_count != 1 {
+        return Err("only single 2D images are supported".to_string());
+    }
+
+    let format = match header.format {
+        Some(K::BC1_RGB_UNORM_BLOCK | K::BC1_RGB_SRGB_BLOCK | K::BC1_RGBA_UNORM_BLOCK | K::BC1_RGBA_SRGB_BLOCK) => BlockFormat::Bc1,
+        Some(K::BC3_UNORM_BLOCK | K::BC3_SRGB_BLOCK) => BlockFormat::Bc3,
+        Some(K::BC4_UNORM_BLOCK) => BlockFormat::Bc4,
+        Some(K::BC5_UNORM_BLOCK) => BlockFormat::Bc5,
+        Some(K::BC7_UNORM_BLOCK | K::BC7_SRGB_BLOCK) => BlockFormat::Bc7,
+        Some(other) => return Err(format!("format {:?} is not supported, only BC1, BC3, BC4, BC5 and BC7 are", other)),
+        None => return Err("Basis Universal textures are not supported".to_string()),
+    };
+
+    if header.pixel_width == 0 {
+        return Err("the image has a width of 0".to_string());
+    }
+    let levels: Vec<Vec<u8>> = reader.levels().map(|level| level.data.to_vec()).collect();
+    if levels.is_empty() {
+        return Err("the file has no mip levels".to_string());
+    }
+
+    Ok(Container {
+        format,
+        width: header.pixel_width,
+        height: header.pixel_height.max(1),
+        levels,
+    })
+}
+
+fn read_dds(bytes: &[u8]) -> Result<Container, String> {
+    let dds = Dds::read(bytes).map_err(|e| e.to_string())?;
+    let is_cube = dds.header.caps2.contains(Caps2::CUBEMAP)
+        || dds.header10.as_ref().is_some_and(|h10| h10.misc_flag.contains(MiscFlag::TEXTURECUBE));
+    if is_cube || dds.get_depth() > 1 || dds.get_num_array_layers() > 1 {
+        return Err("only single 2D images are supported".to_string());
+    }
+
+    // Legacy headers without a DXGI format only name BC4 through its FourCC
+    let fourcc = dds.header.spf.fourcc.as_ref().map(|fourcc| fourcc.0);
+    let format = match dds.get_dxgi_format() {
+        Some(DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm | DxgiFormat::BC1_UNorm_sRGB) => BlockFormat::Bc1,
+        Some(DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB) => BlockFormat::Bc3,
+        Some(DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm) => BlockFormat::Bc4,
+        Some(DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm) => BlockFormat::Bc5,
+        Some(DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm | DxgiFormat::BC7_UNorm_sRGB) => BlockFormat::Bc7,
+        None if fourcc == Some(FourCC::BC4_UNORM) => BlockFormat::Bc4,
+        other => return Err(format!("format {:?} is not supported, only BC1, BC3, BC4, BC5 and BC7 are", other)),
+    };
+
+    // Levels are stored back to back, each one rounded up to whole blocks
+    let (width, height) = (dds.get_width(), dds.get_height());
+    let texture_format = format.texture_format(false);
+    let mut levels = Vec::new();
+    let mut offset = 0;
+    for level in 0..dds.get_num_mipmap_levels().max(1) {
+        let size = level_size(texture_format, width, height, level);
+        let data = dds.data.get(offset..offset + size)
+            .ok_or_else(|| format!("mip {} is truncated", level))?;
+        levels.push(data.to_vec());
+        offset += size;
+    }
+
+    Ok(Container { format, width, height, levels })
+}
@@ -0,0 +1,115 @@
+use std::f32::consts::{PI, TAU};
+use std::path::Path;
+use glam::Vec3;
+use engine_vfs::Vfs;
+use image::GenericImageView;
+use crate::asset_error::AssetError;
+use crate::asset_manager::{ColorSpace, CubemapConfig};
+use crate::loader::{DecodedImage, is_hdr, open_image};
+
+// Decodes the faces of a cubemap, or resamples its panorama into them. Faces are kept as
+// RGBA32F until the end, LDR sources are converted back to RGBA8 then.
+pub(crate) fn decode_cubemap(asset: &str, config: &CubemapConfig, vfs: &Vfs) -> Result<DecodedImage, AssetError> {
+    let invalid = |reason: String| AssetError::InvalidCubemap { asset: asset.to_string(), reason };
+
+    let (faces, size, hdr) = match (&config.faces, &config.equirect) {
+        (Some(paths), None) => {
+            let images = paths.iter()
+                .map(|path| open_image(asset, Path::new(path), vfs))
+                .collect::<Result<Vec<_>, _>>()?;
+            let size = images[0].width();
+            if let Some((path, image)) = paths.iter().zip(&images)
+                .find(|(_, image)| image.width() != size || image.height() != size)
+            {
+                let (width, height) = image.dimensions();
+                return Err(invalid(format!(
+                    "face '{}' is {}x{}, faces have to be square and {}x{} like the first one",
+                    path, width, height, size, size
+                )));
+            }
+            let hdr = images.iter().any(is_hdr);
+            let faces = images.into_iter().map(|image| image.into_rgba32f().into_raw()).collect();
+            (faces, size, hdr)
+        }
+        (None, Some(path)) => {
+            let image = open_image(asset, Path::new(path), vfs)?;
+            let (width, height) = image.dimensions();
+            let size = config.size.unwrap_or(width / 4).max(1);
+            let hdr = is_hdr(&image);
+            let faces = equirect_to_faces(&image.into_rgba32f().into_raw(), width, height, size);
+            (faces, size, hdr)
+        }
+        _ => return Err(invalid("it needs either six 'faces' or one 'equirect' image".to_string())),
+    };
+
+    let srgb = config.color_space.unwrap_or(ColorSpace::Srgb) == ColorSpace::Srgb;
+    let faces: Vec<DecodedImage> = faces.into_iter()
+        .map(|face| if hdr {
+            DecodedImage::from_hdr(face, size, size, config.mipmaps)
+        } else {
+            let rgba = face.into_iter().map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8).collect();
+            let format = if srgb { wgpu::TextureFormat::Rgba8UnormSrgb } else { wgpu::TextureFormat::Rgba8Unorm };
+            DecodedImage::new(rgba, size, size, format, config.mipmaps)
+        })
+        .collect();
+
+    // Each face has its own chain, a cubemap level holds the six faces back to back
+    let levels = (0..faces[0].levels.len())
+        .map(|level| faces.iter().flat_map(|face| face.levels[level].iter().copied()).collect())
+        .collect();
+    Ok(DecodedImage { width: size, height: size, layers: 6, format: faces[0].format, levels })
+}
+
+// Direction through a face texel, `s` and `t` run from -1 to 1 across the face. Follows the
+// wgpu layer order +X, -X, +Y, -Y, +Z, -Z.
+fn face_direction(face: usize, s: f32, t: f32) -> Vec3 {
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+}
+
+// The panorama's center column looks down -Z, its top row straight up
+fn equirect_to_faces(pixels: &[f32], width: u32, height: u32, size: u32) -> Vec<Vec<f32>> {
+    (0..6)
+        .map(|face| {
+            let mut out = Vec::with_capacity((size * size * 4) as usize);
+            for y in 0..size {
+                for x in 0..size {
+                    let s = 2.0 * (x as f32 + 0.5) / size as f32 - 1.0;
+                    let t = 2.0 * (y as f32 + 0.5) / size as f32 - 1.0;
+                    let dir = face_direction(face, s, t).normalize();
+                    let u = 0.5 + dir.x.atan2(-dir.z) / TAU;
+                    let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+                    out.extend(sample_bilinear(pixels, width, height, u, v));
+                }
+            }
+            out
+        })
+        .collect()
+}
+
+// Wraps around horizontally and clamps at the poles
+fn sample_bilinear(pixels: &[f32], width: u32, height: u32, u: f32, v: f32) -> [f32; 4] {
+    let (x, y) = (u * width as f32 - 0.5, v * height as f32 - 0.5);
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+
+    let texel = |px: i64, py: i64| {
+        let px = px.rem_euclid(width as i64) as usize;
+        let py = py.clamp(0, height as i64 - 1) as usize;
+        let offset = (py * width as usize + px) * 4;
+        [pixels[offset], pixels[offset + 1], pixels[offset + 2], pixels[offset + 3]]
+    };
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let (a, b, c, d) = (texel(x0, y0), texel(x0 + 1, y0), texel(x0, y0 + 1), texel(x0 + 1, y0 + 1));
+    std::array::from_fn(|i| {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        top + (bottom - top) * fy
+    })
+}
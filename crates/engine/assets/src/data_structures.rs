@@ -1,26 +1,159 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use crate::asset_manager::LodThreshold;
+use crate::bounds::MeshBounds;
+use crate::skin::Skin;
+use engine_gpu_types::MaterialUniform;
 
+// Structs for managing loaded assets
 
+// Weak, copyable reference to a storage slot. The generation changes whenever the slot is
+// freed, so an id that outlived its asset is detected instead of reading the new occupant.
+pub struct AssetId<T> {
+    pub(crate) index: u32,
+    pub(crate) generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
 
-// Structs for managing loaded assets
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct MeshId(pub usize);
+impl<T> AssetId<T> {
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation, _marker: PhantomData }
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct MaterialId(pub usize);
+impl<T> Clone for AssetId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct TextureId(pub usize);
+impl<T> Copy for AssetId<T> {}
 
+impl<T> PartialEq for AssetId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for AssetId<T> {}
+
+impl<T> Hash for AssetId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for AssetId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AssetId({}v{})", self.index, self.generation)
+    }
+}
+
+pub type MeshId = AssetId<MeshBuffers>;
+pub type MaterialId = AssetId<MaterialData>;
+pub type TextureId = AssetId<wgpu::TextureView>;
+
+// Strong reference that keeps an asset loaded. Clones share the count, dropping the last
+// one lets `AssetManager::unload_unused` free the asset.
+pub struct Handle<T> {
+    id: AssetId<T>,
+    _ref: Arc<()>,
+}
+
+impl<T> Handle<T> {
+    pub(crate) fn new(id: AssetId<T>, count: Arc<()>) -> Self {
+        Self { id, _ref: count }
+    }
+
+    pub fn id(&self) -> AssetId<T> {
+        self.id
+    }
+}
 
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self { id: self.id, _ref: Arc::clone(&self._ref) }
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.id).finish()
+    }
+}
+
+// One drawable piece of a multi-part model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelPart {
+    pub mesh: MeshId,
+    pub material: MaterialId,
+}
+
+// A coarser level of a mesh and from where on it is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshLod {
+    pub mesh: MeshId,
+    pub threshold: LodThreshold,
+}
+
+#[derive(Clone)]
 pub struct MeshBuffers {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
     pub num_indices: u32,
+    // Bounds of the rest pose
+    pub bounds: MeshBounds,
+    pub skin: Option<MeshSkin>,
 }
 
+// Second vertex stream of a skinned mesh and the skin its joint indices refer to
+#[derive(Clone)]
+pub struct MeshSkin {
+    pub weights_buffer: wgpu::Buffer,
+    pub skin: Arc<Skin>,
+}
+
+// The parts of a material that can be changed at runtime without rebuilding it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaterialParams {
+    pub roughness: f32,
+    pub metallic: f32,
+}
+
+// Resolved textures and sampler of a material, instances bind the same ones
+#[derive(Debug, Clone)]
+pub(crate) struct MaterialBindings {
+    pub diffuse: wgpu::TextureView,
+    pub normal: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    // Comes from the sampler, kept when the params change
+    pub lod_bias: f32,
+}
 
 #[derive(Debug, Clone)]
 pub struct MaterialData {
+    // Name it is registered under, its config is kept by that name
+    pub name: String,
     pub pipeline_name: String,
     pub bind_group: wgpu::BindGroup,
+    pub uniform_buffer: wgpu::Buffer,
+    pub params: MaterialParams,
+    pub(crate) bindings: MaterialBindings,
+    // Keeps the bound textures loaded as long as the material is
+    pub(crate) _textures: Vec<Handle<wgpu::TextureView>>,
+}
+
+impl MaterialData {
+    pub(crate) fn uniforms(&self) -> MaterialUniform {
+        MaterialUniform {
+            roughness: self.params.roughness,
+            metallic: self.params.metallic,
+            lod_bias: self.bindings.lod_bias,
+            _padding: 0.0,
+        }
+    }
 }
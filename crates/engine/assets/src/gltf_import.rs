@@ -0,0 +1,403 @@
+use std::borrow::Cow;
+use std::path::Path;
+use engine_gpu_types::{VertexJW, VertexPTN, VertexPTNT};
+use engine_vfs::Vfs;
+use glam::{Mat4, Quat};
+use crate::asset_error::AssetError;
+use crate::loader::read_file;
+use crate::mesh_optimizer::optimize_mesh;
+use crate::skin::{Joint, JointTransform, Skin};
+use crate::tangents::with_tangents;
+
+// CPU-side result of reading a glTF/GLB file. The AssetManager uploads these to the GPU
+// and registers them under "<asset>/<name>".
+pub struct GltfImport {
+    pub meshes: Vec<ImportedMesh>,
+    pub images: Vec<ImportedImage>,
+    pub materials: Vec<ImportedMaterial>,
+    pub skins: Vec<ImportedSkin>,
+}
+
+pub struct ImportedMesh {
+    pub mesh_name: String,
+    pub primitive: usize,
+    pub material: Option<usize>,
+    pub vertices: Vec<VertexPTNT>,
+    pub indices: Vec<u32>,
+    // Index into `skins` and the joints and weights of every vertex, for meshes that a
+    // node binds to a skin
+    pub skin: Option<(usize, Vec<VertexJW>)>,
+}
+
+pub struct ImportedSkin {
+    pub name: String,
+    pub skin: Skin,
+}
+
+// Welded and reordered as one vertex, so the two streams stay in step
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkinnedVertex {
+    vertex: VertexPTNT,
+    weights: VertexJW,
+}
+
+pub struct ImportedImage {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+    pub linear: bool, // Normal maps are stored without sRGB encoding
+}
+
+pub struct ImportedMaterial {
+    pub name: String,
+    pub base_color_image: Option<usize>,
+    pub base_color_factor: [f32; 4],
+    pub normal_image: Option<usize>,
+    pub roughness: f32,
+    pub metallic: f32,
+}
+
+pub fn import_gltf(asset_name: &str, path: &Path, vfs: &Vfs) -> Result<GltfImport, AssetError> {
+    let import_error = |source| AssetError::GltfImport { asset: asset_name.to_string(), path: path.to_path_buf(), source };
+    let bytes = read_file(vfs, asset_name, path)?;
+    let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(&bytes).map_err(import_error)?;
+
+    // External buffers and images are read through the VFS next to the file, embedded ones
+    // (GLB / data URI) are decoded by gltf itself
+    let buffers = import_buffers(asset_name, path, &document, blob, vfs)?;
+    let images = import_images(asset_name, path, &document, &buffers, vfs)?;
+    let (skins, skin_indices) = import_skins(asset_name, &document, &buffers);
+
+    // glTF binds skins to nodes, a mesh is skinned with the first one a node uses it with
+    let mut mesh_skins = vec![None; document.meshes().len()];
+    for node in document.nodes() {
+        if let (Some(mesh), Some(skin)) = (node.mesh(), node.skin())
+            && let Some(index) = skin_indices[skin.index()]
+        {
+            mesh_skins[mesh.index()].get_or_insert(index);
+        }
+    }
+
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let mesh_name = mesh.name().map(str::to_string).unwrap_or_else(|| format!("mesh{}", mesh.index()));
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                log::warn!(
+                    "Skipping primitive {} of mesh '{}' in '{}': only triangle lists are supported.",
+                    primitive.index(), mesh_name, asset_name
+                );
+                continue;
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let Some(positions) = reader.read_positions() else { continue };
+            let positions: Vec<[f32; 3]> = positions.collect();
+            let normals: Vec<[f32; 3]> = reader.read_normals().map(|n| n.collect()).unwrap_or_default();
+            let tex_coords: Vec<[f32; 2]> = reader.read_tex_coords(0).map(|t| t.into_f32().collect()).unwrap_or_default();
+            let tangents: Vec<[f32; 4]> = reader.read_tangents().map(|t| t.collect()).unwrap_or_default();
+            let joints: Vec<[u16; 4]> = reader.read_joints(0).map(|j| j.into_u16().collect()).unwrap_or_default();
+            let weights: Vec<[f32; 4]> = reader.read_weights(0).map(|w| w.into_f32().collect()).unwrap_or_default();
+
+            let vertices = positions.iter().enumerate()
+                .map(|(i, position)| VertexPTN {
+                    position: *position,
+                    tex_coords: tex_coords.get(i).copied().unwrap_or([0.0; 2]),
+                    normal: normals.get(i).copied().unwrap_or([0.0; 3]),
+                })
+                .collect::<Vec<_>>();
+
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+
+            // Exported tangents are used as-is, they are MikkTSpace by spec
+            let vertices = if tangents.len() == vertices.len() {
+                vertices.iter().zip(&tangents)
+                    .map(|(v, tangent)| VertexPTNT {
+                        position: v.position,
+                        tex_coords: v.tex_coords,
+                        normal: v.normal,
+                        tangent: *tangent,
+                    })
+                    .collect()
+            } else {
+                with_tangents(&vertices, &indices)
+            };
+
+            let optimize_name = format!("{}/{}", mesh_name, primitive.index());
+            let skin = mesh_skins[mesh.index()]
+                .filter(|_| joints.len() == vertices.len() && weights.len() == vertices.len());
+            let (vertices, indices, skin) = match skin {
+                Some(skin) => {
+                    let joint_count = skins[skin].skin.joints.len();
+                    let skinned: Vec<SkinnedVertex> = vertices.iter().zip(joints.iter().zip(&weights))
+                        .map(|(vertex, (joints, weights))| SkinnedVertex {
+                            vertex: *vertex,
+                            weights: joint_weights(*joints, *weights, joint_count),
+                        })
+                        .collect();
+                    let (skinned, indices) = optimize_mesh(&optimize_name, &skinned, &indices);
+                    let vertices = skinned.iter().map(|v| v.vertex).collect();
+                    let weights = skinned.iter().map(|v| v.weights).collect();
+                    (vertices, indices, Some((skin, weights)))
+                }
+                None => {
+                    let (vertices, indices) = optimize_mesh(&optimize_name, &vertices, &indices);
+                    (vertices, indices, None)
+                }
+            };
+
+            meshes.push(ImportedMesh {
+                mesh_name: mesh_name.clone(),
+                primitive: primitive.index(),
+                material: primitive.material().index(),
+                vertices,
+                indices,
+                skin,
+            });
+        }
+    }
+
+    let materials: Vec<ImportedMaterial> = document.materials()
+        .filter_map(|material| {
+            // The implicit default material has no index and is never registered
+            let index = material.index()?;
+            let pbr = material.pbr_metallic_roughness();
+            Some(ImportedMaterial {
+                name: material.name().map(str::to_string).unwrap_or_else(|| format!("material{}", index)),
+                base_color_image: pbr.base_color_texture().map(|info| info.texture().source().index()),
+                base_color_factor: pbr.base_color_factor(),
+                normal_image: material.normal_texture().map(|normal| normal.texture().source().index()),
+                roughness: pbr.roughness_factor(),
+                metallic: pbr.metallic_factor(),
+            })
+        })
+        .collect();
+
+    let images = document.images()
+        .zip(images)
+        .map(|(image, (width, height, rgba))| {
+            let linear = materials.iter().any(|m| m.normal_image == Some(image.index()));
+            ImportedImage {
+                name: image.name().map(str::to_string).unwrap_or_else(|| format!("image{}", image.index())),
+                width,
+                height,
+                rgba,
+                linear,
+            }
+        })
+        .collect();
+
+    Ok(GltfImport { meshes, images, materials, skins })
+}
+
+// The skins that could be imported, and for every glTF skin its index among them
+fn import_skins(
+    asset_name: &str,
+    document: &gltf::Document,
+    buffers: &[gltf::buffer::Data],
+) -> (Vec<ImportedSkin>, Vec<Option<usize>>) {
+    let mut node_parents = vec![None; document.nodes().len()];
+    for node in document.nodes() {
+        for child in node.children() {
+            node_parents[child.index()] = Some(node.index());
+        }
+    }
+    let node_transforms: Vec<Mat4> = document.nodes()
+        .map(|node| Mat4::from_cols_array_2d(&node.transform().matrix()))
+        .collect();
+
+    let mut skins = Vec::new();
+    let mut indices = Vec::new();
+    for skin in document.skins() {
+        let name = skin.name().map(str::to_string).unwrap_or_else(|| format!("skin{}", skin.index()));
+        let nodes: Vec<gltf::Node> = skin.joints().collect();
+        // Without inverse bind matrices the joints are bound at their identity
+        let inverse_binds: Vec<Mat4> = skin.reader(|buffer| Some(&buffers[buffer.index()]))
+            .read_inverse_bind_matrices()
+            .map(|matrices| matrices.map(|m| Mat4::from_cols_array_2d(&m)).collect())
+            .unwrap_or_default();
+
+        let joint_of_node = |node: usize| nodes.iter().position(|joint| joint.index() == node);
+        let joints = nodes.iter().enumerate()
+            .map(|(i, node)| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                Joint {
+                    name: node.name().map(str::to_string).unwrap_or_else(|| format!("joint{}", node.index())),
+                    parent: node_parents[node.index()].and_then(joint_of_node),
+                    rest: JointTransform {
+                        translation: translation.into(),
+                        rotation: Quat::from_array(rotation),
+                        scale: scale.into(),
+                    },
+                    inverse_bind: inverse_binds.get(i).copied().unwrap_or(Mat4::IDENTITY),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Nodes above the hierarchy, usually a single armature node
+        let mut root = Mat4::IDENTITY;
+        let first_root = nodes.iter().zip(&joints).find(|(_, joint)| joint.parent.is_none());
+        let mut ancestor = first_root.and_then(|(node, _)| node_parents[node.index()]);
+        while let Some(node) = ancestor {
+            root = node_transforms[node] * root;
+            ancestor = node_parents[node];
+        }
+
+        match Skin::new(joints, root) {
+            Some(imported) => {
+                indices.push(Some(skins.len()));
+                skins.push(ImportedSkin { name, skin: imported });
+            }
+            None => {
+                log::warn!("Skipping skin '{}' in '{}': its joints don't form a hierarchy.", name, asset_name);
+                indices.push(None);
+            }
+        }
+    }
+    (skins, indices)
+}
+
+// Weights of joints the skin doesn't have are dropped, the rest normalized to add up to 1
+fn joint_weights(joints: [u16; 4], mut weights: [f32; 4], joint_count: usize) -> VertexJW {
+    for (joint, weight) in joints.iter().zip(&mut weights) {
+        if *joint as usize >= joint_count || !weight.is_finite() {
+            *weight = 0.0;
+        }
+    }
+    let sum: f32 = weights.iter().sum();
+    if sum <= 0.0 {
+        return VertexJW { joints: [0; 4], weights: [1.0, 0.0, 0.0, 0.0] };
+    }
+    VertexJW {
+        joints: joints.map(|joint| if joint as usize >= joint_count { 0 } else { joint }),
+        weights: weights.map(|weight| weight / sum),
+    }
+}
+
+fn import_buffers(
+    asset_name: &str,
+    path: &Path,
+    document: &gltf::Document,
+    mut blob: Option<Vec<u8>>,
+    vfs: &Vfs,
+) -> Result<Vec<gltf::buffer::Data>, AssetError> {
+    let import_error = |source| AssetError::GltfImport { asset: asset_name.to_string(), path: path.to_path_buf(), source };
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut buffers = Vec::new();
+    for buffer in document.buffers() {
+        let external = match buffer.source() {
+            gltf::buffer::Source::Uri(uri) => external_file(uri),
+            gltf::buffer::Source::Bin => None,
+        };
+        let data = match external {
+            Some(file) => gltf::buffer::Data(read_file(vfs, asset_name, &base.join(&*file))?),
+            None => gltf::buffer::Data::from_source_and_blob(buffer.source(), Some(base), &mut blob).map_err(import_error)?,
+        };
+        if data.len() < buffer.length() {
+            return Err(import_error(gltf::Error::BufferLength {
+                buffer: buffer.index(),
+                expected: buffer.length(),
+                actual: data.len(),
+            }));
+        }
+        buffers.push(data);
+    }
+    Ok(buffers)
+}
+
+// RGBA8 pixels with their width and height
+fn import_images(
+    asset_name: &str,
+    path: &Path,
+    document: &gltf::Document,
+    buffers: &[gltf::buffer::Data],
+    vfs: &Vfs,
+) -> Result<Vec<(u32, u32, Vec<u8>)>, AssetError> {
+    let import_error = |source| AssetError::GltfImport { asset: asset_name.to_string(), path: path.to_path_buf(), source };
+    let base = path.parent().unwrap_or(Path::new(""));
+    let mut images = Vec::new();
+    for image in document.images() {
+        let external = match image.source() {
+            gltf::image::Source::Uri { uri, .. } => external_file(uri),
+            gltf::image::Source::View { .. } => None,
+        };
+        let decoded = match external {
+            Some(file) => {
+                let file = base.join(&*file);
+                let bytes = read_file(vfs, asset_name, &file)?;
+                let rgba = image::load_from_memory(&bytes)
+                    .map_err(|source| AssetError::ImageDecode { asset: asset_name.to_string(), path: file, source })?
+                    .to_rgba8();
+                (rgba.width(), rgba.height(), rgba.into_raw())
+            }
+            None => {
+                let data = gltf::image::Data::from_source(image.source(), Some(base), buffers).map_err(import_error)?;
+                (data.width, data.height, to_rgba8(&data))
+            }
+        };
+        images.push(decoded);
+    }
+    Ok(images)
+}
+
+// The file a URI references relative to the glTF file, None for embedded data URIs
+fn external_file(uri: &str) -> Option<Cow<'_, str>> {
+    if uri.starts_with("data:") {
+        return None;
+    }
+    let uri = uri.strip_prefix("file://").or_else(|| uri.strip_prefix("file:")).unwrap_or(uri);
+    Some(urlencoding::decode(uri).unwrap_or(Cow::Borrowed(uri)))
+}
+
+fn to_rgba8(data: &gltf::image::Data) -> Vec<u8> {
+    use gltf::image::Format;
+
+    let pixel_count = (data.width * data.height) as usize;
+    let mut rgba = Vec::with_capacity(pixel_count * 4);
+
+    // (channels, bytes per channel)
+    let (channels, channel_size) = match data.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+
+    let read_channel = |bytes: &[u8]| -> u8 {
+        match channel_size {
+            1 => bytes[0],
+            2 => (u16::from_le_bytes([bytes[0], bytes[1]]) >> 8) as u8,
+            _ => {
+                let value = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            }
+        }
+    };
+
+    for pixel in data.pixels.chunks_exact(channels * channel_size).take(pixel_count) {
+        let mut out = [0, 0, 0, 255];
+        for (c, channel) in pixel.chunks_exact(channel_size).enumerate() {
+            out[c] = read_channel(channel);
+        }
+        // Grayscale images are expanded to all three color channels
+        if channels == 1 {
+            out[1] = out[0];
+            out[2] = out[0];
+        }
+        rgba.extend_from_slice(&out);
+    }
+
+    rgba
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use engine_vfs::Vfs;
+use notify::{EventKind, RecursiveMode, Watcher};
+use crate::asset_error::AssetError;
+use crate::asset_manager::TextureKind;
+
+// Files every loaded asset was read from, so a changed file can be mapped back to the
+// assets that have to be rebuilt. All paths are normalized with `normalize_path`.
+#[derive(Default)]
+pub(crate) struct AssetSources {
+    pub textures: HashMap<String, TextureSource>,
+    pub meshes: HashMap<String, PathBuf>,
+    // (level, ratio) of the LOD levels simplified from an OBJ mesh
+    pub simplified: HashMap<String, Vec<(usize, f32)>>,
+    pub gltf: HashMap<String, PathBuf>,
+}
+
+impl AssetSources {
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.meshes.clear();
+        self.simplified.clear();
+        self.gltf.clear();
+    }
+
+    // Files from archives or the binary can't change, only the ones on disk are watched
+    pub fn directories(&self) -> HashSet<PathBuf> {
+        self.textures.values().map(|source| &source.path)
+            .chain(self.meshes.values())
+            .chain(self.gltf.values())
+            .filter(|path| path.is_absolute())
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TextureSource {
+    pub path: PathBuf,
+    pub format: wgpu::TextureFormat,
+    pub kind: TextureKind,
+    pub mipmaps: bool,
+}
+
+pub(crate) struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl FileWatcher {
+    // Watches `root` recursively and every other directory non-recursively
+    pub fn new(root: &Path, directories: &HashSet<PathBuf>) -> Result<Self, AssetError> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|source| AssetError::Watch { path: root.to_path_buf(), source })?;
+
+        watcher.watch(root, RecursiveMode::Recursive)
+            .map_err(|source| AssetError::Watch { path: root.to_path_buf(), source })?;
+
+        for dir in directories.iter().filter(|dir| !dir.starts_with(root)) {
+            watcher.watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|source| AssetError::Watch { path: dir.clone(), source })?;
+        }
+
+        Ok(Self { _watcher: watcher, events })
+    }
+
+    // Drains all pending events. Editors often emit several events per save, so the
+    // paths are deduplicated.
+    pub fn changed_paths(&self) -> HashSet<PathBuf> {
+        let mut changed = HashSet::new();
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    changed.extend(event.paths.iter().map(|path| std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())));
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("File watcher error: {}", e),
+            }
+        }
+        changed
+    }
+}
+
+// The canonical file on disk, like the watcher reports it. Files that only exist in an
+// archive or the binary keep their VFS path.
+pub(crate) fn normalize_path(vfs: &Vfs, path: &Path) -> PathBuf {
+    vfs.real_path(path).unwrap_or_else(|| engine_vfs::normalize(path))
+}
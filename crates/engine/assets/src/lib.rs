@@ -1,4 +1,24 @@
 pub mod asset_manager;
+pub mod asset_error;
+pub mod bounds;
+pub mod bundle;
 pub mod data_structures;
+pub mod gltf_import;
+pub mod memory;
+pub mod mesh_optimizer;
+pub mod primitives;
+pub mod samplers;
+pub mod simplify;
+pub mod skin;
+mod compressed;
+mod cubemap;
+mod hot_reload;
+mod loader;
+mod packs;
+mod storage;
+pub mod tangents;
+pub mod validation;
 
 pub use asset_manager::AssetManager;
+pub use asset_error::AssetError;
+pub use loader::LoadProgress;
@@ -0,0 +1,646 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::{Arc, Mutex};
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use engine_vfs::Vfs;
+use engine_gpu_types::{VertexJW, VertexPTN, VertexPTNT};
+use engine_textures::{generate_mip_chain, generate_mip_chain_f32};
+use crate::asset_error::AssetError;
+use crate::asset_manager::{AssetManifest, CubemapConfig, MaterialConfig, TextureKind, ERROR_TEXTURE, lod_name};
+use crate::gltf_import::{self, ImportedMaterial, ImportedMesh, ImportedSkin};
+use crate::bundle::{AssetBundle, BundleEntry};
+use crate::compressed::{is_compressed_path, read_compressed};
+use crate::cubemap::decode_cubemap;
+use crate::hot_reload::TextureSource;
+use crate::mesh_optimizer::optimize_mesh;
+use crate::primitives::Primitive;
+use crate::simplify::simplify_mesh;
+use crate::skin::Skin;
+use crate::tangents::with_tangents;
+
+// Snapshot of a running (or the last finished) manifest load
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadProgress {
+    pub loaded: usize,
+    pub total: usize,
+    pub current: Option<String>, // Asset that was uploaded last
+}
+
+impl LoadProgress {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 { 1.0 } else { self.loaded as f32 / self.total as f32 }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.loaded >= self.total
+    }
+}
+
+// A manifest load in flight. Decoded assets are uploaded as they arrive, the manifest's
+// materials are created last since they can reference any texture.
+pub(crate) struct PendingLoad {
+    pub manifest_path: PathBuf,
+    pub manifest: AssetManifest,
+    pub files: Vec<PathBuf>,
+    pub materials: Vec<(String, MaterialConfig)>,
+    pub rebuild_materials: bool,
+    pub results: Receiver<Result<DecodedAsset, LoadFailure>>,
+    pub remaining: usize,
+    // Models and mesh aliases of a bundle, registered once its materials exist
+    pub bundle: Option<Arc<AssetBundle>>,
+    // Materials of the manifest that are drawn like the error material, see `ManifestTree`
+    pub broken_materials: Vec<(String, AssetError)>,
+}
+
+// Everything that is read from disk. Decoding runs on worker threads, GPU upload does not.
+#[derive(Clone)]
+pub(crate) enum LoadJob {
+    Texture { name: String, source: TextureSource },
+    // (level, ratio) of the LOD levels that are simplified from the file
+    Obj { name: String, path: PathBuf, simplify: Vec<(usize, f32)> },
+    Gltf { name: String, path: PathBuf },
+    Primitive { name: String, primitive: Primitive },
+    Cubemap { name: String, config: CubemapConfig },
+}
+
+impl LoadJob {
+    fn decode(self, vfs: &Vfs) -> Result<DecodedAsset, AssetError> {
+        Ok(match self {
+            LoadJob::Texture { name, source } => {
+                let image = DecodedImage::open(&name, &source, vfs)?;
+                DecodedAsset::Texture { name, source, image }
+            }
+            LoadJob::Obj { name, path, simplify } => {
+                let mut obj = DecodedObj::load(&name, &path, vfs)?;
+                obj.simplify(&name, &simplify);
+                DecodedAsset::Obj { name, path, obj }
+            }
+            LoadJob::Gltf { name, path } => {
+                let gltf = DecodedGltf::load(&name, &path, vfs)?;
+                DecodedAsset::Gltf { name, path, gltf }
+            }
+            LoadJob::Primitive { name, primitive } => {
+                let (vertices, indices) = primitive.generate();
+                DecodedAsset::Mesh { name, vertices, indices }
+            }
+            LoadJob::Cubemap { name, config } => {
+                let image = decode_cubemap(&name, &config, vfs)?;
+                DecodedAsset::Cubemap { name, image }
+            }
+        })
+    }
+}
+
+// A job that could not be decoded, kept so a placeholder can be registered in its place
+pub(crate) struct LoadFailure {
+    pub job: LoadJob,
+    pub error: AssetError,
+}
+
+impl From<LoadFailure> for AssetError {
+    fn from(failure: LoadFailure) -> Self {
+        failure.error
+    }
+}
+
+pub(crate) enum DecodedAsset {
+    Texture { name: String, source: TextureSource, image: DecodedImage },
+    Obj { name: String, path: PathBuf, obj: DecodedObj },
+    Gltf { name: String, path: PathBuf, gltf: DecodedGltf },
+    Mesh { name: String, vertices: Vec<VertexPTNT>, indices: Vec<u32> },
+    Cubemap { name: String, image: DecodedImage },
+    // Cooked data is uploaded straight from the mapped bundle file
+    Bundled { name: String, bundle: Arc<AssetBundle>, entry: BundleEntry },
+}
+
+impl DecodedAsset {
+    pub fn name(&self) -> &str {
+        match self {
+            DecodedAsset::Texture { name, .. }
+            | DecodedAsset::Obj { name, .. }
+            | DecodedAsset::Gltf { name, .. }
+            | DecodedAsset::Mesh { name, .. }
+            | DecodedAsset::Cubemap { name, .. }
+            | DecodedAsset::Bundled { name, .. } => name,
+        }
+    }
+}
+
+// The base level followed by its mip chain, all in `format`. Images are RGBA8 with a
+// CPU-generated chain, HDR images Rgba16Float, KTX2/DDS files keep their BCn blocks and
+// stored chain. Cubemaps have six layers, each level holds all of them back to back.
+pub(crate) struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub layers: u32,
+    pub format: wgpu::TextureFormat,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl DecodedImage {
+    pub fn new(rgba: Vec<u8>, width: u32, height: u32, format: wgpu::TextureFormat, mipmaps: bool) -> Self {
+        let mip_chain = if mipmaps {
+            generate_mip_chain(&rgba, width, height, format.is_srgb())
+        } else {
+            Vec::new()
+        };
+        let levels = std::iter::once(rgba)
+            .chain(mip_chain.into_iter().map(RgbaImage::into_raw))
+            .collect();
+        Self { width, height, layers: 1, format, levels }
+    }
+
+    // Linear RGBA32F pixels, stored as half floats since Rgba32Float can't be filtered
+    pub fn from_hdr(rgba: Vec<f32>, width: u32, height: u32, mipmaps: bool) -> Self {
+        let mip_chain = if mipmaps {
+            generate_mip_chain_f32(&rgba, width, height)
+        } else {
+            Vec::new()
+        };
+        let levels = std::iter::once(rgba)
+            .chain(mip_chain)
+            .map(|level| level.into_iter().flat_map(|c| half::f16::from_f32(c).to_le_bytes()).collect())
+            .collect();
+        Self { width, height, layers: 1, format: wgpu::TextureFormat::Rgba16Float, levels }
+    }
+
+    pub fn open(asset: &str, source: &TextureSource, vfs: &Vfs) -> Result<Self, AssetError> {
+        if is_compressed_path(&source.path) {
+            let bytes = read_file(vfs, asset, &source.path)?;
+            return read_compressed(asset, &source.path, &bytes, source.format.is_srgb(), source.mipmaps);
+        }
+
+        let img = open_image(asset, &source.path, vfs)?;
+        let (width, height) = img.dimensions();
+        if is_hdr(&img) {
+            return Ok(Self::from_hdr(img.into_rgba32f().into_raw(), width, height, source.mipmaps));
+        }
+
+        Ok(Self::new(img.to_rgba8().into_raw(), width, height, source.format, source.mipmaps))
+    }
+}
+
+pub(crate) struct DecodedObject {
+    pub name: String,
+    pub vertices: Vec<VertexPTNT>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+// An OBJ file with its MTL materials. Texture maps are decoded once per file name.
+pub(crate) struct DecodedObj {
+    pub objects: Vec<DecodedObject>,
+    // Level, ratio and objects of every simplified LOD level
+    pub lods: Vec<(usize, f32, Vec<DecodedObject>)>,
+    pub materials: Vec<tobj::Material>,
+    pub textures: Vec<(String, TextureSource, DecodedImage)>,
+    // Problems that didn't stop the import, logged by loads and reported by the validator
+    pub warnings: Vec<String>,
+}
+
+impl DecodedObj {
+    pub fn load(name: &str, path: &Path, vfs: &Vfs) -> Result<Self, AssetError> {
+        let bytes = read_file(vfs, name, path)?;
+        // MTL files and texture maps are relative to the OBJ file
+        let base_path = path.parent().unwrap_or(Path::new(""));
+        let options = tobj::LoadOptions { triangulate: true, single_index: true, ..Default::default() };
+        let (models, materials) = tobj::load_obj_buf(&mut bytes.as_slice(), &options, |mtl_path| {
+            let mtl = vfs.read(base_path.join(mtl_path)).map_err(|_| tobj::LoadError::OpenFileFailed)?;
+            tobj::load_mtl_buf(&mut mtl.as_slice())
+        }).map_err(|source| AssetError::ObjParse {
+            asset: name.to_string(),
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        if models.is_empty() {
+            return Err(AssetError::EmptyMesh { asset: name.to_string(), path: path.to_path_buf() });
+        }
+
+        let mut warnings = Vec::new();
+        let materials = materials.unwrap_or_else(|e| {
+            warnings.push(format!("Materials for mesh '{}' could not be loaded: {}", name, e));
+            Vec::new()
+        });
+
+        // map_Kd is color data, map_Bump a normal map
+        let mut textures: Vec<(String, TextureSource, DecodedImage)> = Vec::new();
+        for material in &materials {
+            let maps = [
+                (&material.diffuse_texture, TextureKind::Color),
+                (&material.normal_texture, TextureKind::Normal),
+            ];
+            for (file, kind) in maps {
+                if file.is_empty() || textures.iter().any(|(existing, ..)| existing == file) {
+                    continue;
+                }
+                let source = TextureSource {
+                    path: base_path.join(file),
+                    format: kind.default_color_space().format(),
+                    kind,
+                    mipmaps: true,
+                };
+                // A broken map only replaces its own slot, see `resolve`
+                match DecodedImage::open(&format!("{}/{}", name, file), &source, vfs) {
+                    Ok(image) => textures.push((file.clone(), source, image)),
+                    Err(e) => warnings.push(format!(
+                        "Texture '{}' of mesh '{}' could not be loaded, using a placeholder: {}",
+                        file, name, e
+                    )),
+                }
+            }
+        }
+
+        let objects = models.into_iter().enumerate()
+            .map(|(i, model)| {
+                let object = if model.name.is_empty() { format!("object{}", i) } else { model.name };
+                let vertices = with_tangents(&obj_vertices(&model.mesh), &model.mesh.indices);
+                let (vertices, indices) = optimize_mesh(&format!("{}/{}", name, object), &vertices, &model.mesh.indices);
+                DecodedObject { name: object, vertices, indices, material: model.mesh.material_id }
+            })
+            .collect();
+
+        Ok(Self { objects, lods: Vec::new(), materials, textures, warnings })
+    }
+
+    // Adds a simplified copy of every object for each (level, ratio). The levels keep the
+    // objects' materials.
+    pub fn simplify(&mut self, name: &str, levels: &[(usize, f32)]) {
+        for &(level, ratio) in levels {
+            let lod = lod_name(name, level);
+            let objects = self.objects.iter()
+                .map(|object| {
+                    let indices = simplify_mesh(&object.vertices, &object.indices, ratio);
+                    let (vertices, indices) = optimize_mesh(&format!("{}/{}", lod, object.name), &object.vertices, &indices);
+                    log::debug!(
+                        "Simplified mesh '{}/{}': {} -> {} triangles",
+                        lod, object.name, object.indices.len() / 3, indices.len() / 3,
+                    );
+                    DecodedObject { name: object.name.clone(), vertices, indices, material: object.material }
+                })
+                .collect();
+            self.lods.push((level, ratio, objects));
+        }
+    }
+
+    pub fn simplified_levels(&self) -> Vec<(usize, f32)> {
+        self.lods.iter().map(|(level, ratio, _)| (*level, *ratio)).collect()
+    }
+
+    // Objects become meshes "<name>/<object>", MTL materials "<name>/<material>" and the
+    // textures "<name>/<file>". The model "<name>" holds every object, the mesh "<name>"
+    // is an alias of the first one. Simplified levels follow the same scheme under
+    // "<name>/lod<level>".
+    pub fn resolve(self, name: &str) -> ResolvedImport {
+        let mut import = ResolvedImport::default();
+        let loaded: Vec<String> = self.textures.iter().map(|(file, ..)| file.clone()).collect();
+        for (file, source, image) in self.textures {
+            import.textures.push(ResolvedTexture {
+                name: format!("{}/{}", name, file),
+                kind: source.kind,
+                image,
+                source: Some(source),
+            });
+        }
+
+        // map_Kd and map_Bump become the diffuse and normal textures. Ns is converted from a
+        // Blinn-Phong exponent to roughness, the average of Ks is used as metallic. Maps that
+        // failed to load use the error texture or the default normal.
+        for material in &self.materials {
+            let mat_name = format!("{}/{}", name, material.name);
+            let diffuse = if material.diffuse_texture.is_empty() {
+                let [r, g, b] = material.diffuse;
+                import.color_texture(format!("{}_diffuse", mat_name), [r, g, b, material.dissolve])
+            } else if loaded.contains(&material.diffuse_texture) {
+                format!("{}/{}", name, material.diffuse_texture)
+            } else {
+                ERROR_TEXTURE.to_string()
+            };
+            let normal = (!material.normal_texture.is_empty() && loaded.contains(&material.normal_texture))
+                .then(|| format!("{}/{}", name, material.normal_texture));
+
+            import.materials.push((mat_name, MaterialConfig {
+                pipeline: "standard".to_string(),
+                diffuse,
+                normal,
+                roughness: (2.0 / (material.shininess + 2.0)).sqrt().clamp(0.0, 1.0),
+                metallic: (material.specular.iter().sum::<f32>() / 3.0).clamp(0.0, 1.0),
+                sampler: None,
+            }));
+        }
+
+        let levels = std::iter::once((name.to_string(), self.objects))
+            .chain(self.lods.into_iter().map(|(level, _, objects)| (lod_name(name, level), objects)));
+        for (model_name, objects) in levels {
+            let mut parts = Vec::with_capacity(objects.len());
+            for object in objects {
+                let mesh_name = format!("{}/{}", model_name, object.name);
+                let material = object.material
+                    .and_then(|index| import.materials.get(index))
+                    .map(|(mat_name, _)| mat_name.clone());
+                parts.push((mesh_name.clone(), material));
+                import.meshes.push(ResolvedMesh { name: mesh_name, vertices: object.vertices, indices: object.indices, skin: None });
+            }
+            if let Some((first, _)) = parts.first() {
+                import.aliases.push((model_name.clone(), first.clone()));
+            }
+            import.models.push((model_name, parts));
+        }
+        import
+    }
+}
+
+// A glTF/GLB import whose images already carry their mip chains
+pub(crate) struct DecodedGltf {
+    pub meshes: Vec<ImportedMesh>,
+    pub materials: Vec<ImportedMaterial>,
+    pub images: Vec<(String, DecodedImage)>,
+    pub skins: Vec<ImportedSkin>,
+}
+
+impl DecodedGltf {
+    pub fn load(name: &str, path: &Path, vfs: &Vfs) -> Result<Self, AssetError> {
+        let import = gltf_import::import_gltf(name, path, vfs)?;
+
+        let images = import.images.into_iter()
+            .map(|image| {
+                let format = if image.linear {
+                    wgpu::TextureFormat::Rgba8Unorm
+                } else {
+                    wgpu::TextureFormat::Rgba8UnormSrgb
+                };
+                (image.name, DecodedImage::new(image.rgba, image.width, image.height, format, true))
+            })
+            .collect();
+
+        Ok(Self { meshes: import.meshes, materials: import.materials, images, skins: import.skins })
+    }
+
+    // Primitives become meshes "<name>/<mesh>/<primitive>", images textures "<name>/<image>",
+    // materials "<name>/<material>" and skins "<name>/<skin>". Each glTF mesh is the model
+    // "<name>/<mesh>", the whole file the model "<name>". Skinned meshes get a copy of their
+    // material for the skinned pipeline, "<name>/<material>_skinned".
+    pub fn resolve(self, name: &str) -> ResolvedImport {
+        let mut import = ResolvedImport::default();
+        let mut image_names = Vec::with_capacity(self.images.len());
+        for (image_name, image) in self.images {
+            let tex_name = format!("{}/{}", name, image_name);
+            // The importer only stores normal maps without sRGB encoding
+            let kind = if image.format.is_srgb() { TextureKind::Color } else { TextureKind::Normal };
+            import.textures.push(ResolvedTexture { name: tex_name.clone(), kind, image, source: None });
+            image_names.push(tex_name);
+        }
+
+        for material in &self.materials {
+            let mat_name = format!("{}/{}", name, material.name);
+
+            // Without a base color texture the factor is baked into a single pixel texture.
+            // With one, the factor is ignored since MaterialUniform has no tint.
+            let diffuse = match material.base_color_image {
+                Some(index) => image_names[index].clone(),
+                None => import.color_texture(format!("{}_diffuse", mat_name), material.base_color_factor),
+            };
+
+            import.materials.push((mat_name, MaterialConfig {
+                pipeline: "standard".to_string(),
+                diffuse,
+                normal: material.normal_image.map(|index| image_names[index].clone()),
+                roughness: material.roughness,
+                metallic: material.metallic,
+                sampler: None,
+            }));
+        }
+
+        let skin_names: Vec<String> = self.skins.into_iter()
+            .map(|skin| {
+                let skin_name = format!("{}/{}", name, skin.name);
+                import.skins.push((skin_name.clone(), skin.skin));
+                skin_name
+            })
+            .collect();
+
+        let mut all_parts = Vec::with_capacity(self.meshes.len());
+        for mesh in self.meshes {
+            let mesh_name = format!("{}/{}/{}", name, mesh.mesh_name, mesh.primitive);
+            let material = match (&mesh.skin, mesh.material) {
+                (None, material) => material.map(|index| import.materials[index].0.clone()),
+                (Some(_), Some(index)) => Some(import.skinned_material(index)),
+                (Some(_), None) => Some(import.default_skinned_material(name)),
+            };
+            let part = (mesh_name.clone(), material);
+
+            let model_name = format!("{}/{}", name, mesh.mesh_name);
+            match import.models.iter_mut().find(|(existing, _)| *existing == model_name) {
+                Some((_, parts)) => parts.push(part.clone()),
+                None => import.models.push((model_name, vec![part.clone()])),
+            }
+            all_parts.push(part);
+            import.meshes.push(ResolvedMesh {
+                name: mesh_name,
+                vertices: mesh.vertices,
+                indices: mesh.indices,
+                skin: mesh.skin.map(|(index, weights)| (skin_names[index].clone(), weights)),
+            });
+        }
+        import.models.push((name.to_string(), all_parts));
+        import
+    }
+}
+
+// (mesh, material) names of a model's parts. Parts without a material use "internal:white".
+pub(crate) type ModelParts = Vec<(String, Option<String>)>;
+
+pub(crate) struct ResolvedTexture {
+    pub name: String,
+    pub kind: TextureKind,
+    pub image: DecodedImage,
+    // Set for textures that are files of their own and can be hot reloaded
+    pub source: Option<TextureSource>,
+}
+
+pub(crate) struct ResolvedMesh {
+    pub name: String,
+    pub vertices: Vec<VertexPTNT>,
+    pub indices: Vec<u32>,
+    // Name of the skin and the joint weights of every vertex
+    pub skin: Option<(String, Vec<VertexJW>)>,
+}
+
+// An OBJ or glTF import flattened into named assets. Uploads and the bundle cooker both go
+// through this, so they agree on the names.
+#[derive(Default)]
+pub(crate) struct ResolvedImport {
+    pub textures: Vec<ResolvedTexture>,
+    pub materials: Vec<(String, MaterialConfig)>,
+    pub meshes: Vec<ResolvedMesh>,
+    pub models: Vec<(String, ModelParts)>,
+    // (alias, mesh) pairs that register an existing mesh under a second name
+    pub aliases: Vec<(String, String)>,
+    pub skins: Vec<(String, Skin)>,
+}
+
+impl ResolvedImport {
+    // Adds a single pixel texture for a linear color and returns its name
+    fn color_texture(&mut self, name: String, color: [f32; 4]) -> String {
+        let image = DecodedImage::new(
+            linear_to_srgb8(color).to_vec(),
+            1,
+            1,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            false,
+        );
+        self.textures.push(ResolvedTexture { name: name.clone(), kind: TextureKind::Color, image, source: None });
+        name
+    }
+
+    // Adds the skinned copy of a material once and returns its name
+    fn skinned_material(&mut self, index: usize) -> String {
+        let (name, config) = &self.materials[index];
+        let skinned = format!("{}_skinned", name);
+        if !self.materials.iter().any(|(existing, _)| *existing == skinned) {
+            let config = MaterialConfig { pipeline: "skinned".to_string(), ..config.clone() };
+            self.materials.push((skinned.clone(), config));
+        }
+        skinned
+    }
+
+    // Skinned counterpart of "internal:white" for skinned meshes without a material
+    fn default_skinned_material(&mut self, name: &str) -> String {
+        let mat_name = format!("{}/default_skinned", name);
+        if !self.materials.iter().any(|(existing, _)| *existing == mat_name) {
+            let diffuse = self.color_texture(format!("{}_diffuse", mat_name), [1.0; 4]);
+            self.materials.push((mat_name.clone(), MaterialConfig {
+                pipeline: "skinned".to_string(),
+                diffuse,
+                normal: None,
+                roughness: 0.5,
+                metallic: 0.0,
+                sampler: None,
+            }));
+        }
+        mat_name
+    }
+}
+
+// image::open tone maps Radiance .hdr files down to RGB8, so those are read through the
+// decoder directly to keep their range
+pub(crate) fn open_image(asset: &str, path: &Path, vfs: &Vfs) -> Result<DynamicImage, AssetError> {
+    let bytes = read_file(vfs, asset, path)?;
+    let decode_error = |source| AssetError::ImageDecode { asset: asset.to_string(), path: path.to_path_buf(), source };
+    let is_radiance = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("hdr"));
+    if !is_radiance {
+        // Like image::open, the format comes from the extension
+        return image::ImageFormat::from_path(path)
+            .and_then(|format| image::load_from_memory_with_format(&bytes, format))
+            .map_err(decode_error);
+    }
+
+    let decoder = image::codecs::hdr::HdrDecoder::new(bytes.as_slice()).map_err(decode_error)?;
+    let meta = decoder.metadata();
+    let pixels = decoder.read_image_hdr().map_err(decode_error)?;
+    let raw = pixels.into_iter().flat_map(|pixel| pixel.0).collect();
+    image::Rgb32FImage::from_raw(meta.width, meta.height, raw)
+        .map(DynamicImage::ImageRgb32F)
+        .ok_or_else(|| decode_error(image::ImageError::Limits(image::error::LimitError::from_kind(
+            image::error::LimitErrorKind::DimensionError,
+        ))))
+}
+
+// Missing files are reported as such, so they can be told apart from unreadable ones
+pub(crate) fn read_file(vfs: &Vfs, asset: &str, path: &Path) -> Result<Vec<u8>, AssetError> {
+    vfs.read(path).map_err(|source| match source.kind() {
+        std::io::ErrorKind::NotFound => AssetError::FileNotFound { asset: asset.to_string(), path: path.to_path_buf() },
+        _ => AssetError::FileRead { asset: asset.to_string(), path: path.to_path_buf(), source },
+    })
+}
+
+// Floating point images keep their range instead of being clamped to RGBA8
+pub(crate) fn is_hdr(image: &DynamicImage) -> bool {
+    matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_))
+}
+
+// Material factors are linear, the single pixel diffuse texture is sRGB encoded
+fn linear_to_srgb8(color: [f32; 4]) -> [u8; 4] {
+    let encode = |c: f32| {
+        let c = c.clamp(0.0, 1.0);
+        let srgb = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+        (srgb * 255.0).round() as u8
+    };
+    [encode(color[0]), encode(color[1]), encode(color[2]), (color[3].clamp(0.0, 1.0) * 255.0).round() as u8]
+}
+
+// Decodes `jobs` on a pool of worker threads. Every job sends exactly one result. A worker
+// that panics drops its sender, so the receiver disconnects before all results arrived.
+pub(crate) fn spawn_workers(jobs: Vec<LoadJob>, vfs: &Vfs) -> Receiver<Result<DecodedAsset, LoadFailure>> {
+    let (sender, results) = channel();
+    let worker_count = std::thread::available_parallelism()
+        .map_or(4, NonZeroUsize::get)
+        .min(jobs.len());
+    let queue = Arc::new(Mutex::new(VecDeque::from(jobs)));
+
+    for _ in 0..worker_count {
+        let queue = Arc::clone(&queue);
+        let sender = sender.clone();
+        let vfs = vfs.clone();
+        std::thread::spawn(move || {
+            while let Some(job) = queue.lock().ok().and_then(|mut jobs| jobs.pop_front()) {
+                // The receiver is gone when the load was cancelled or failed
+                let result = job.clone().decode(&vfs).map_err(|error| LoadFailure { job, error });
+                if sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    results
+}
+
+fn obj_vertices(mesh: &tobj::Mesh) -> Vec<VertexPTN> {
+    (0..mesh.positions.len() / 3)
+        .map(|i| VertexPTN {
+            position: [mesh.positions[i*3], mesh.positions[i*3+1], mesh.positions[i*3+2]],
+            tex_coords: if mesh.texcoords.is_empty() { [0.0; 2] } else {
+                [mesh.texcoords[i*2], 1.0 - mesh.texcoords[i*2+1]]
+            },
+            normal: if mesh.normals.is_empty() { [0.0; 3] } else {
+                [mesh.normals[i*3], mesh.normals[i*3+1], mesh.normals[i*3+2]]
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_obj_maps_only_replace_their_slot() {
+        let dir = std::env::temp_dir().join(format!("engine_assets_{}_obj_maps", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("crate.obj"), "mtllib crate.mtl\n\
+            v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 1\n\
+            o lid\nusemtl wood\nf 1/1/1 2/2/1 3/3/1\n\
+            o body\nusemtl metal\nf 1/1/1 2/2/1 3/3/1\n").unwrap();
+        std::fs::write(dir.join("crate.mtl"), "newmtl wood\nmap_Kd wood.png\nmap_Bump missing_normal.png\n\
+            newmtl metal\nmap_Kd missing.png\n").unwrap();
+        RgbaImage::new(2, 2).save(dir.join("wood.png")).unwrap();
+
+        // Absolute paths bypass the mounts
+        let obj = DecodedObj::load("crate", &dir.join("crate.obj"), &Vfs::new()).unwrap();
+        assert_eq!(obj.warnings.len(), 2);
+        assert!(obj.warnings.iter().any(|warning| warning.contains("missing_normal.png")));
+        assert!(obj.warnings.iter().any(|warning| warning.contains("missing.png")));
+
+        let import = obj.resolve("crate");
+        assert_eq!(import.textures.len(), 1);
+        let material = |name: &str| &import.materials.iter().find(|(mat_name, _)| mat_name == name).unwrap().1;
+        assert_eq!(material("crate/wood").diffuse, "crate/wood.png");
+        assert_eq!(material("crate/wood").normal, None);
+        assert_eq!(material("crate/metal").diffuse, ERROR_TEXTURE);
+        assert_eq!(import.models[0].1.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::AddAssign;
+use std::path::PathBuf;
+use crate::data_structures::{MaterialData, MeshBuffers};
+
+// GPU memory of loaded assets, computed from the sizes of their textures and buffers.
+// Drivers add alignment and padding on top, so the real usage is somewhat higher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub textures: u64,
+    pub vertex_buffers: u64,
+    pub index_buffers: u64,
+    pub uniform_buffers: u64,
+}
+
+impl MemoryUsage {
+    pub fn of_texture(view: &wgpu::TextureView) -> Self {
+        Self { textures: texture_bytes(view.texture()), ..Self::default() }
+    }
+
+    pub fn of_mesh(mesh: &MeshBuffers) -> Self {
+        let weights = mesh.skin.as_ref().map_or(0, |skin| skin.weights_buffer.size());
+        Self { vertex_buffers: mesh.vertex_buffer.size() + weights, index_buffers: mesh.index_buffer.size(), ..Self::default() }
+    }
+
+    // Only the uniform buffer, the textures are assets of their own
+    pub fn of_material(material: &MaterialData) -> Self {
+        Self { uniform_buffers: material.uniform_buffer.size(), ..Self::default() }
+    }
+
+    fn mesh_buffer(&mut self, is_index: bool) -> &mut u64 {
+        if is_index { &mut self.index_buffers } else { &mut self.vertex_buffers }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.textures + self.vertex_buffers + self.index_buffers + self.uniform_buffers
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.textures += other.textures;
+        self.vertex_buffers += other.vertex_buffers;
+        self.index_buffers += other.index_buffers;
+        self.uniform_buffers += other.uniform_buffers;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStats {
+    pub total: MemoryUsage,
+    // By manifest or bundle path. Assets shared by several packs count for each of them.
+    pub packs: Vec<(PathBuf, MemoryUsage)>,
+    pub budget: Option<u64>,
+}
+
+impl MemoryStats {
+    pub fn over_budget(&self) -> bool {
+        self.budget.is_some_and(|budget| self.total.total() > budget)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetMemory {
+    pub kind: &'static str,
+    pub name: String,
+    pub usage: MemoryUsage,
+}
+
+// Running GPU memory of a set of assets, updated as assets are added and removed. Every
+// texture and buffer is counted once, placeholders and aliases share theirs with other
+// assets.
+#[derive(Default)]
+pub(crate) struct MemoryCounter {
+    // Number of counted assets that use each texture and buffer
+    textures: HashMap<wgpu::Texture, usize>,
+    buffers: HashMap<wgpu::Buffer, usize>,
+    pub usage: MemoryUsage,
+}
+
+impl MemoryCounter {
+    pub fn add_texture(&mut self, view: &wgpu::TextureView) {
+        if add_ref(&mut self.textures, view.texture()) {
+            self.usage.textures += texture_bytes(view.texture());
+        }
+    }
+
+    pub fn remove_texture(&mut self, view: &wgpu::TextureView) {
+        if remove_ref(&mut self.textures, view.texture()) {
+            self.usage.textures -= texture_bytes(view.texture());
+        }
+    }
+
+    pub fn add_mesh(&mut self, mesh: &MeshBuffers) {
+        for (buffer, is_index) in mesh_buffers(mesh) {
+            if add_ref(&mut self.buffers, buffer) {
+                *self.usage.mesh_buffer(is_index) += buffer.size();
+            }
+        }
+    }
+
+    pub fn remove_mesh(&mut self, mesh: &MeshBuffers) {
+        for (buffer, is_index) in mesh_buffers(mesh) {
+            if remove_ref(&mut self.buffers, buffer) {
+                *self.usage.mesh_buffer(is_index) -= buffer.size();
+            }
+        }
+    }
+
+    pub fn add_material(&mut self, material: &MaterialData) {
+        if add_ref(&mut self.buffers, &material.uniform_buffer) {
+            self.usage.uniform_buffers += material.uniform_buffer.size();
+        }
+    }
+
+    pub fn remove_material(&mut self, material: &MaterialData) {
+        if remove_ref(&mut self.buffers, &material.uniform_buffer) {
+            self.usage.uniform_buffers -= material.uniform_buffer.size();
+        }
+    }
+}
+
+// The vertex, index and joint weight buffers of a mesh, flagged true for the index buffer
+fn mesh_buffers(mesh: &MeshBuffers) -> impl Iterator<Item = (&wgpu::Buffer, bool)> {
+    [(&mesh.vertex_buffer, false), (&mesh.index_buffer, true)].into_iter()
+        .chain(mesh.skin.as_ref().map(|skin| (&skin.weights_buffer, false)))
+}
+
+// True for the first reference
+fn add_ref<K: Clone + Eq + Hash>(refs: &mut HashMap<K, usize>, key: &K) -> bool {
+    let count = refs.entry(key.clone()).or_default();
+    *count += 1;
+    *count == 1
+}
+
+// True once the last reference is gone
+fn remove_ref<K: Eq + Hash>(refs: &mut HashMap<K, usize>, key: &K) -> bool {
+    let Some(count) = refs.get_mut(key) else { return false };
+    *count -= 1;
+    if *count > 0 {
+        return false;
+    }
+    refs.remove(key);
+    true
+}
+
+// Every mip level and layer, block compressed formats by their block size
+pub fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let size = texture.size();
+    (0..texture.mip_level_count())
+        .map(|level| texture.format().theoretical_memory_footprint(size.mip_level_size(level, texture.dimension())))
+        .sum()
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    } else {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    }
+}
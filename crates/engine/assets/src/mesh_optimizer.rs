@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use bytemuck::Pod;
+
+// Import-time mesh optimization: duplicate vertices are welded, triangles reordered for
+// the post-transform vertex cache and vertices for fetch locality. Runs on the loader
+// threads, so uploads stay cheap.
+
+// Simulated cache size of the triangle ordering. Larger than real caches on purpose, the
+// ordering degrades gracefully on smaller ones.
+const CACHE_SIZE: usize = 32;
+// Scoring constants from Tom Forsyth's "Linear-Speed Vertex Cache Optimisation"
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+pub fn optimize_mesh<V: Pod>(name: &str, vertices: &[V], indices: &[u32]) -> (Vec<V>, Vec<u32>) {
+    let (welded, indices) = weld_vertices(vertices, indices);
+    let acmr_before = average_cache_miss_ratio(&indices, welded.len(), 16);
+    let mut indices = optimize_vertex_cache(&indices, welded.len());
+    let optimized = optimize_vertex_fetch(&welded, &mut indices);
+    log::debug!(
+        "Optimized mesh '{}': {} -> {} vertices, ACMR {:.2} -> {:.2}",
+        name,
+        vertices.len(),
+        optimized.len(),
+        acmr_before,
+        average_cache_miss_ratio(&indices, optimized.len(), 16),
+    );
+    (optimized, indices)
+}
+
+// Merges vertices that are bit-for-bit identical, so seams with differing normals, UVs or
+// tangents stay split
+pub fn weld_vertices<V: Pod>(vertices: &[V], indices: &[u32]) -> (Vec<V>, Vec<u32>) {
+    let mut unique: HashMap<&[u8], u32> = HashMap::with_capacity(vertices.len());
+    let mut welded = Vec::with_capacity(vertices.len());
+    let remap: Vec<u32> = vertices.iter()
+        .map(|vertex| {
+            *unique.entry(bytemuck::bytes_of(vertex)).or_insert_with(|| {
+                welded.push(*vertex);
+                welded.len() as u32 - 1
+            })
+        })
+        .collect();
+    let indices = indices.iter().map(|&index| remap[index as usize]).collect();
+    (welded, indices)
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: u32) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        None => 0.0,
+        // The triangle that was just drawn, its vertices are used no matter the order
+        Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+        Some(position) => {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+        }
+    };
+    // Vertices with few triangles left are finished first, so they can leave the cache
+    cache_score + VALENCE_BOOST_SCALE * (remaining_triangles as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+// Greedily emits the triangle whose vertices score highest. Only triangles next to cached
+// vertices are candidates, when there are none the next triangle in input order is taken.
+pub fn optimize_vertex_cache(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+
+    // Triangles of every vertex, packed. The first `remaining[v]` entries of a vertex are
+    // the ones that still have to be drawn.
+    let mut remaining = vec![0u32; vertex_count];
+    for &index in indices {
+        remaining[index as usize] += 1;
+    }
+    let mut offsets = vec![0usize; vertex_count + 1];
+    for vertex in 0..vertex_count {
+        offsets[vertex + 1] = offsets[vertex] + remaining[vertex] as usize;
+    }
+    let mut adjacency = vec![0u32; triangle_count * 3];
+    let mut filled = offsets.clone();
+    for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+        for &vertex in corners {
+            adjacency[filled[vertex as usize]] = triangle as u32;
+            filled[vertex as usize] += 1;
+        }
+    }
+
+    let mut cache_position: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = remaining.iter().map(|&count| vertex_score(None, count)).collect();
+    let mut triangle_scores: Vec<f32> = indices.chunks_exact(3)
+        .map(|corners| corners.iter().map(|&vertex| vertex_scores[vertex as usize]).sum())
+        .collect();
+    let mut emitted = vec![false; triangle_count];
+
+    let mut output = Vec::with_capacity(triangle_count * 3);
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut best: Option<usize> = None;
+    let mut cursor = 0;
+    for _ in 0..triangle_count {
+        let triangle = best.take().unwrap_or_else(|| {
+            while emitted[cursor] {
+                cursor += 1;
+            }
+            cursor
+        });
+        emitted[triangle] = true;
+        let corners = &indices[triangle * 3..triangle * 3 + 3];
+        output.extend_from_slice(corners);
+
+        for &vertex in corners {
+            let vertex = vertex as usize;
+            let live = &mut adjacency[offsets[vertex]..offsets[vertex] + remaining[vertex] as usize];
+            if let Some(position) = live.iter().position(|&t| t as usize == triangle) {
+                live.swap(position, live.len() - 1);
+                remaining[vertex] -= 1;
+            }
+        }
+
+        // The drawn triangle moves to the front, everything else shifts back. Vertices that
+        // fall out of the cache are rescored too.
+        let mut new_cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+        for &vertex in corners.iter().chain(&cache) {
+            if !new_cache.contains(&vertex) {
+                new_cache.push(vertex);
+            }
+        }
+        for (position, &vertex) in new_cache.iter().enumerate() {
+            let vertex = vertex as usize;
+            cache_position[vertex] = (position < CACHE_SIZE).then_some(position);
+            let score = vertex_score(cache_position[vertex], remaining[vertex]);
+            let delta = score - vertex_scores[vertex];
+            vertex_scores[vertex] = score;
+            for &t in &adjacency[offsets[vertex]..offsets[vertex] + remaining[vertex] as usize] {
+                triangle_scores[t as usize] += delta;
+            }
+        }
+        new_cache.truncate(CACHE_SIZE);
+        cache = new_cache;
+
+        let mut best_score = f32::NEG_INFINITY;
+        for &vertex in &cache {
+            let vertex = vertex as usize;
+            for &t in &adjacency[offsets[vertex]..offsets[vertex] + remaining[vertex] as usize] {
+                if triangle_scores[t as usize] > best_score {
+                    best_score = triangle_scores[t as usize];
+                    best = Some(t as usize);
+                }
+            }
+        }
+    }
+    output
+}
+
+// Renumbers vertices in the order the index buffer first uses them, unused ones are dropped
+pub fn optimize_vertex_fetch<V: Copy>(vertices: &[V], indices: &mut [u32]) -> Vec<V> {
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut reordered = Vec::with_capacity(vertices.len());
+    for index in indices.iter_mut() {
+        let new_index = &mut remap[*index as usize];
+        if *new_index == u32::MAX {
+            *new_index = reordered.len() as u32;
+            reordered.push(vertices[*index as usize]);
+        }
+        *index = *new_index;
+    }
+    reordered
+}
+
+// Vertex shader invocations per triangle with a FIFO cache of `cache_size`. 3 is the worst
+// case, 0.5 about the best a regular grid can reach.
+pub fn average_cache_miss_ratio(indices: &[u32], vertex_count: usize, cache_size: usize) -> f32 {
+    if indices.len() < 3 {
+        return 0.0;
+    }
+    // Time stamp of the vertex's last load, a FIFO evicts by load order only
+    let mut loaded_at = vec![None::<usize>; vertex_count];
+    let mut misses = 0;
+    for &index in indices {
+        let cached = loaded_at[index as usize].is_some_and(|time| misses - time < cache_size);
+        if !cached {
+            loaded_at[index as usize] = Some(misses);
+            misses += 1;
+        }
+    }
+    misses as f32 / (indices.len() / 3) as f32
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Normal direction of every triangle, +1 for counter-clockwise seen from +Z
+    fn windings(vertices: &[[f32; 3]], indices: &[u32]) -> Vec<f32> {
+        indices.chunks_exact(3)
+            .map(|t| {
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| vertices[i as usize]);
+                ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])).signum()
+            })
+            .collect()
+    }
+
+    // Triangles as sets of positions, rotated to start at their smallest corner, so the
+    // same triangle compares equal no matter how its vertices were renumbered
+    fn triangles(vertices: &[[f32; 3]], indices: &[u32]) -> Vec<[[i32; 3]; 3]> {
+        let mut triangles: Vec<[[i32; 3]; 3]> = indices.chunks_exact(3)
+            .map(|t| {
+                let mut corners = [t[0], t[1], t[2]].map(|i| vertices[i as usize].map(|x| (x * 100.0) as i32));
+                let first = (0..3).min_by_key(|&i| corners[i]).unwrap();
+                corners.rotate_left(first);
+                corners
+            })
+            .collect();
+        triangles.sort();
+        triangles
+    }
+
+    // An n x n grid of quads in the XY plane, every triangle counter-clockwise
+    fn grid(n: u32) -> (Vec<[f32; 3]>, Vec<u32>) {
+        let vertices = (0..=n).flat_map(|y| (0..=n).map(move |x| [x as f32, y as f32, 0.0])).collect();
+        let mut indices = Vec::new();
+        for y in 0..n {
+            for x in 0..n {
+                let i = y * (n + 1) + x;
+                indices.extend_from_slice(&[i, i + 1, i + n + 2, i, i + n + 2, i + n + 1]);
+            }
+        }
+        (vertices, indices)
+    }
+
+    #[test]
+    fn duplicated_quad_is_welded() {
+        // Two triangles that each bring their own copies of the shared diagonal
+        let vertices = [
+            [0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0],
+        ];
+        let indices = [0, 1, 2, 3, 4, 5];
+        let (optimized, new_indices) = optimize_mesh("quad", &vertices, &indices);
+
+        assert_eq!(optimized.len(), 4);
+        assert_eq!(new_indices.len(), 6);
+        assert!(new_indices.iter().all(|&index| (index as usize) < optimized.len()));
+        assert_eq!(windings(&optimized, &new_indices), [1.0, 1.0]);
+        assert_eq!(triangles(&optimized, &new_indices), triangles(&vertices, &indices));
+    }
+
+    #[test]
+    fn differing_attributes_stay_split() {
+        // Same position, different normal, like a hard edge
+        let vertices = [[0.0f32, 0.0, 1.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]];
+        let (welded, indices) = weld_vertices(&vertices, &[0, 1, 2, 2, 1, 0]);
+        assert_eq!(welded.len(), 2);
+        assert_eq!(indices, [0, 0, 1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn cache_order_keeps_triangles_and_winding() {
+        let (vertices, indices) = grid(16);
+        let (optimized, new_indices) = optimize_mesh("grid", &vertices, &indices);
+
+        assert_eq!(optimized.len(), vertices.len());
+        assert!(new_indices.iter().all(|&index| (index as usize) < optimized.len()));
+        assert!(windings(&optimized, &new_indices).iter().all(|&winding| winding == 1.0));
+        assert_eq!(triangles(&optimized, &new_indices), triangles(&vertices, &indices));
+        let before = average_cache_miss_ratio(&indices, vertices.len(), 16);
+        let after = average_cache_miss_ratio(&new_indices, optimized.len(), 16);
+        assert!(after < before, "ACMR went from {} to {}", before, after);
+    }
+
+    #[test]
+    fn vertex_fetch_follows_first_use() {
+        let vertices = ['a', 'b', 'c', 'd'];
+        let mut indices = [3, 1, 3, 0];
+        let reordered = optimize_vertex_fetch(&vertices, &mut indices);
+        assert_eq!(reordered, ['d', 'b', 'a']);
+        assert_eq!(indices, [0, 1, 0, 2]);
+    }
+}
@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use engine_vfs::Vfs;
+use crate::asset_error::AssetError;
+use crate::asset_manager::{AssetManager, AssetManifest, MaterialConfig, MaterialEntry, MeshEntry, TextureEntry, error_material_config};
+use crate::data_structures::{Handle, MaterialData, MeshBuffers};
+use crate::hot_reload::normalize_path;
+use crate::memory::MemoryCounter;
+
+// A manifest with all of its includes merged in. File paths are resolved against the
+// directory of the manifest that names them, names carry their manifest's namespace.
+pub(crate) struct ManifestTree {
+    pub manifest: AssetManifest,
+    // Every manifest file that was read, normalized, so hot reload can watch them
+    pub files: Vec<PathBuf>,
+    // Materials whose parents could not be applied, sorted by name. The manifest holds the
+    // error material's config in their place.
+    pub broken_materials: Vec<(String, AssetError)>,
+}
+
+// A manifest or bundle loaded through `begin_loading` or `load_pack`. Its handles keep the
+// assets it defines loaded until the pack is unloaded.
+pub(crate) struct LoadedPack {
+    pub path: PathBuf,
+    pub manifest: AssetManifest,
+    // Empty for bundles, which can't be hot reloaded
+    pub files: Vec<PathBuf>,
+    pub handles: PackHandles,
+    pub memory: MemoryCounter,
+}
+
+#[derive(Default)]
+pub(crate) struct PackHandles {
+    pub meshes: Vec<Handle<MeshBuffers>>,
+    pub materials: Vec<Handle<MaterialData>>,
+    pub textures: Vec<Handle<wgpu::TextureView>>,
+}
+
+pub(crate) fn read_manifest_tree(vfs: &Vfs, path: &Path) -> Result<ManifestTree, AssetError> {
+    let mut tree = TreeBuilder::default();
+    tree.include(vfs, path, &mut Vec::new())?;
+    let broken_materials = resolve_materials(&mut tree.manifest);
+    Ok(ManifestTree { manifest: tree.manifest, files: tree.files, broken_materials })
+}
+
+// Parents can be defined by any manifest of the tree, including ones that are merged later.
+// Every material is resolved on its own, so one bad parent only breaks the materials that
+// inherit from it.
+fn resolve_materials(manifest: &mut AssetManifest) -> Vec<(String, AssetError)> {
+    let entries = std::mem::take(&mut manifest.material_entries);
+    let mut broken = Vec::new();
+    for name in entries.keys() {
+        let config = resolve_material(name, &entries, &mut Vec::new()).unwrap_or_else(|error| {
+            broken.push((name.clone(), error));
+            error_material_config()
+        });
+        manifest.materials.insert(name.clone(), config);
+    }
+    broken.sort_by(|a, b| a.0.cmp(&b.0));
+    broken
+}
+
+fn resolve_material(
+    name: &str,
+    entries: &HashMap<String, MaterialEntry>,
+    chain: &mut Vec<String>,
+) -> Result<MaterialConfig, AssetError> {
+    let entry = &entries[name];
+    let Some(parent) = &entry.parent else {
+        return entry.resolve(name, None);
+    };
+    chain.push(name.to_string());
+    if let Some(start) = chain.iter().position(|material| material == parent) {
+        let mut cycle = chain.split_off(start);
+        cycle.push(parent.clone());
+        return Err(AssetError::MaterialParentCycle { chain: cycle });
+    }
+    if !entries.contains_key(parent) {
+        return Err(AssetError::MissingParentMaterial { material: name.to_string(), parent: parent.clone() });
+    }
+    let base = resolve_material(parent, entries, chain)?;
+    entry.resolve(name, Some(base))
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    manifest: AssetManifest,
+    files: Vec<PathBuf>,
+    // Manifest that defined each (kind, name), for conflict errors
+    owners: HashMap<(&'static str, String), PathBuf>,
+}
+
+impl TreeBuilder {
+    // Includes are merged before the manifest's own entries. One that is included more than
+    // once, like a core pack shared by several levels, is merged the first time only.
+    fn include(&mut self, vfs: &Vfs, path: &Path, stack: &mut Vec<PathBuf>) -> Result<(), AssetError> {
+        let file = normalize_path(vfs, path);
+        if stack.contains(&file) {
+            return Err(AssetError::ManifestIncludeCycle { path: path.to_path_buf() });
+        }
+        if self.files.contains(&file) {
+            return Ok(());
+        }
+
+        let manifest = AssetManager::read_manifest(vfs, path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        stack.push(file.clone());
+        for include in &manifest.include {
+            self.include(vfs, &join_path(dir, include), stack)?;
+        }
+        stack.pop();
+        self.files.push(file);
+
+        let manifest = resolve_manifest(manifest, path)?;
+        let owners = &mut self.owners;
+        merge_entries("Texture", path, owners, &mut self.manifest.textures, manifest.textures)?;
+        merge_entries("Mesh", path, owners, &mut self.manifest.meshes, manifest.meshes)?;
+        merge_entries("Material", path, owners, &mut self.manifest.material_entries, manifest.material_entries)?;
+        merge_entries("glTF", path, owners, &mut self.manifest.gltf, manifest.gltf)?;
+        merge_entries("Sampler", path, owners, &mut self.manifest.samplers, manifest.samplers)?;
+        merge_entries("Primitive", path, owners, &mut self.manifest.primitives, manifest.primitives)?;
+        merge_entries("Cubemap", path, owners, &mut self.manifest.cubemaps, manifest.cubemaps)
+    }
+}
+
+fn merge_entries<T>(
+    kind: &'static str,
+    path: &Path,
+    owners: &mut HashMap<(&'static str, String), PathBuf>,
+    target: &mut HashMap<String, T>,
+    entries: HashMap<String, T>,
+) -> Result<(), AssetError> {
+    for (name, entry) in entries {
+        if let Some(other) = owners.insert((kind, name.clone()), path.to_path_buf()) {
+            return Err(AssetError::ManifestConflict { kind, name, path: path.to_path_buf(), other });
+        }
+        target.insert(name, entry);
+    }
+    Ok(())
+}
+
+// Makes file paths relative to the VFS root and applies the namespace. Every name
+// a namespaced manifest defines becomes "<namespace>:<name>". References without a ':'
+// point into the same namespace, qualified ones like "core:stone" or "internal:white" are
+// kept as they are.
+fn resolve_manifest(mut manifest: AssetManifest, path: &Path) -> Result<AssetManifest, AssetError> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let resolve = |file: &mut String| *file = join_path(dir, file).to_string_lossy().into_owned();
+    for entry in manifest.textures.values_mut() {
+        match entry {
+            TextureEntry::Path(file) => resolve(file),
+            TextureEntry::Config(config) => resolve(&mut config.path),
+        }
+    }
+    for entry in manifest.meshes.values_mut() {
+        match entry {
+            MeshEntry::Path(file) => resolve(file),
+            MeshEntry::Config(config) => {
+                resolve(&mut config.path);
+                config.lods.iter_mut().flat_map(|lod| &mut lod.path).for_each(resolve);
+            }
+        }
+    }
+    manifest.gltf.values_mut().for_each(resolve);
+    for config in manifest.cubemaps.values_mut() {
+        config.faces.iter_mut().flatten().for_each(resolve);
+        config.equirect.iter_mut().for_each(resolve);
+    }
+    manifest.include.clear();
+
+    let Some(namespace) = manifest.namespace.take() else {
+        return Ok(manifest);
+    };
+    if namespace.is_empty() || namespace.contains(':') {
+        return Err(AssetError::InvalidNamespace { path: path.to_path_buf(), namespace });
+    }
+    let qualify = |name: &mut String| {
+        if !name.contains(':') {
+            *name = format!("{}:{}", namespace, name);
+        }
+    };
+    for entry in manifest.textures.values_mut() {
+        if let TextureEntry::Config(config) = entry {
+            config.sampler.iter_mut().for_each(qualify);
+        }
+    }
+    for entry in manifest.material_entries.values_mut() {
+        entry.parent.iter_mut().for_each(qualify);
+        entry.diffuse.iter_mut().for_each(qualify);
+        entry.normal.iter_mut().flatten().for_each(qualify);
+        entry.sampler.iter_mut().flatten().for_each(qualify);
+    }
+
+    Ok(AssetManifest {
+        textures: prefix_names(&namespace, manifest.textures),
+        meshes: prefix_names(&namespace, manifest.meshes),
+        material_entries: prefix_names(&namespace, manifest.material_entries),
+        gltf: prefix_names(&namespace, manifest.gltf),
+        samplers: prefix_names(&namespace, manifest.samplers),
+        primitives: prefix_names(&namespace, manifest.primitives),
+        cubemaps: prefix_names(&namespace, manifest.cubemaps),
+        ..manifest
+    })
+}
+
+fn prefix_names<T>(namespace: &str, entries: HashMap<String, T>) -> HashMap<String, T> {
+    entries.into_iter()
+        .map(|(name, entry)| (format!("{}:{}", namespace, name), entry))
+        .collect()
+}
+
+// Joins without touching the file system, so the same file reached through different
+// includes gives the same path. Leading ".." components are kept.
+fn join_path(dir: &Path, path: &str) -> PathBuf {
+    engine_vfs::normalize(&dir.join(path))
+}
+
+// First entry of `manifest` that `other` defines differently
+pub(crate) fn find_conflict(manifest: &AssetManifest, other: &AssetManifest) -> Option<(&'static str, String)> {
+    fn differs<T: PartialEq>(kind: &'static str, a: &HashMap<String, T>, b: &HashMap<String, T>) -> Option<(&'static str, String)> {
+        a.iter()
+            .find(|(name, entry)| b.get(*name).is_some_and(|other| other != *entry))
+            .map(|(name, _)| (kind, name.clone()))
+    }
+    differs("Texture", &manifest.textures, &other.textures)
+        .or_else(|| differs("Mesh", &manifest.meshes, &other.meshes))
+        .or_else(|| differs("Material", &manifest.materials, &other.materials))
+        .or_else(|| differs("glTF", &manifest.gltf, &other.gltf))
+        .or_else(|| differs("Sampler", &manifest.samplers, &other.samplers))
+        .or_else(|| differs("Primitive", &manifest.primitives, &other.primitives))
+        .or_else(|| differs("Cubemap", &manifest.cubemaps, &other.cubemaps))
+}
+
+pub(crate) fn extend_manifest(manifest: &mut AssetManifest, other: &AssetManifest) {
+    manifest.textures.extend(other.textures.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.meshes.extend(other.meshes.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.materials.extend(other.materials.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.gltf.extend(other.gltf.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.samplers.extend(other.samplers.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.primitives.extend(other.primitives.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+    manifest.cubemaps.extend(other.cubemaps.iter().map(|(name, entry)| (name.clone(), entry.clone())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Manifests only, the files they name are never read
+    fn manifest_dir(name: &str, manifests: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("engine_assets_{}_{}", std::process::id(), name));
+        for (file, json) in manifests {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, json).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn include_cycles_are_rejected() {
+        let dir = manifest_dir("include_cycle", &[
+            ("a.json", r#"{ "include": ["b.json"], "textures": {}, "meshes": {}, "materials": {} }"#),
+            ("b.json", r#"{ "include": ["a.json"], "textures": {}, "meshes": {}, "materials": {} }"#),
+        ]);
+        // Absolute paths bypass the mounts
+        let result = read_manifest_tree(&Vfs::new(), &dir.join("a.json"));
+        assert!(matches!(result, Err(AssetError::ManifestIncludeCycle { ref path }) if path.ends_with("a.json")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shared_includes_are_merged_once() {
+        let dir = manifest_dir("shared_include", &[
+            ("level.json", r#"{ "include": ["props.json", "core.json"], "textures": {}, "meshes": {}, "materials": {} }"#),
+            ("props.json", r#"{ "include": ["core.json"], "textures": { "crate": "crate.png" }, "meshes": {}, "materials": {} }"#),
+            ("core.json", r#"{ "textures": { "stone": "stone.png" }, "meshes": {}, "materials": {} }"#),
+        ]);
+        let tree = read_manifest_tree(&Vfs::new(), &dir.join("level.json")).unwrap();
+        assert_eq!(tree.files.len(), 3);
+        assert_eq!(tree.manifest.textures.len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_assets_across_packs_conflict() {
+        let dir = manifest_dir("duplicate_asset", &[
+            ("level.json", r#"{ "include": ["a.json", "b.json"], "textures": {}, "meshes": {}, "materials": {} }"#),
+            ("a.json", r#"{ "textures": { "stone": "stone.png" }, "meshes": {}, "materials": {} }"#),
+            ("b.json", r#"{ "textures": { "stone": "other/stone.png" }, "meshes": {}, "materials": {} }"#),
+        ]);
+        let vfs = Vfs::new();
+        // Within one tree the second definition is an error
+        match read_manifest_tree(&vfs, &dir.join("level.json")) {
+            Err(AssetError::ManifestConflict { kind, name, path, other }) => {
+                assert_eq!((kind, name.as_str()), ("Texture", "stone"));
+                assert!(path.ends_with("b.json") && other.ends_with("a.json"));
+            }
+            Err(error) => panic!("unexpected error: {}", error),
+            Ok(_) => panic!("conflicting manifests were merged"),
+        }
+
+        // Packs loaded on their own only conflict when they define a name differently
+        let a = read_manifest_tree(&vfs, &dir.join("a.json")).unwrap().manifest;
+        let b = read_manifest_tree(&vfs, &dir.join("b.json")).unwrap().manifest;
+        assert_eq!(find_conflict(&a, &b), Some(("Texture", "stone".to_string())));
+        assert_eq!(find_conflict(&a, &a.clone()), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn namespaced_names_and_references() {
+        let dir = manifest_dir("namespace", &[
+            ("level.json", r#"{
+                "include": ["core/core.json"],
+                "textures": { "stone": "stone.png" },
+                "meshes": {},
+                "materials": {
+                    "wall": { "parent": "core:stone", "diffuse": "stone" }
+                }
+            }"#),
+            ("core/core.json", r#"{
+                "namespace": "core",
+                "textures": { "stone": "textures/stone.png" },
+                "meshes": {},
+                "materials": {
+                    "stone": { "pipeline": "standard", "diffuse": "stone", "normal": "internal:flat", "roughness": 0.8, "metallic": 0.0 }
+                }
+            }"#),
+        ]);
+        let tree = read_manifest_tree(&Vfs::new(), &dir.join("level.json")).unwrap();
+        assert!(tree.broken_materials.is_empty());
+        let manifest = tree.manifest;
+
+        // Both "stone" textures exist side by side, each with the path of its own manifest
+        assert!(manifest.textures["core:stone"].path().ends_with("core/textures/stone.png"));
+        assert!(manifest.textures["stone"].path().ends_with("stone.png"));
+        assert!(!manifest.textures.contains_key("core:textures/stone.png"));
+
+        // Unqualified references stay in their namespace, qualified ones are kept
+        let core = &manifest.materials["core:stone"];
+        assert_eq!(core.diffuse, "core:stone");
+        assert_eq!(core.normal.as_deref(), Some("internal:flat"));
+        assert_eq!(manifest.materials["wall"].diffuse, "stone");
+        assert_eq!(manifest.materials["wall"].roughness, 0.8);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_namespaces_are_rejected() {
+        let dir = manifest_dir("bad_namespace", &[
+            ("a.json", r#"{ "namespace": "core:props", "textures": {}, "meshes": {}, "materials": {} }"#),
+        ]);
+        let result = read_manifest_tree(&Vfs::new(), &dir.join("a.json"));
+        assert!(matches!(result, Err(AssetError::InvalidNamespace { ref namespace, .. }) if namespace == "core:props"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn resolved(materials: &str) -> (AssetManifest, Vec<(String, AssetError)>) {
+        let json = format!(r#"{{ "textures": {{}}, "meshes": {{}}, "materials": {} }}"#, materials);
+        let mut manifest: AssetManifest = serde_json::from_str(&json).unwrap();
+        let broken = resolve_materials(&mut manifest);
+        (manifest, broken)
+    }
+
+    #[test]
+    fn materials_inherit_unset_params() {
+        let (manifest, broken) = resolved(r#"{
+            "base": { "pipeline": "standard", "diffuse": "stone", "normal": "stone_n", "sampler": "repeat", "roughness": 0.8, "metallic": 0.1 },
+            "wet": { "parent": "base", "roughness": 0.2 },
+            "puddle": { "parent": "wet", "diffuse": "water", "normal": null }
+        }"#);
+        assert!(broken.is_empty());
+        let wet = &manifest.materials["wet"];
+        assert_eq!((wet.roughness, wet.metallic), (0.2, 0.1));
+        assert_eq!((wet.diffuse.as_str(), wet.normal.as_deref()), ("stone", Some("stone_n")));
+
+        // Grandparent values come through the parent, null clears them
+        let puddle = &manifest.materials["puddle"];
+        assert_eq!(puddle.pipeline, "standard");
+        assert_eq!(puddle.diffuse, "water");
+        assert_eq!(puddle.normal, None);
+        assert_eq!(puddle.sampler.as_deref(), Some("repeat"));
+        assert_eq!(puddle.roughness, 0.2);
+        assert!(manifest.material_entries.is_empty());
+    }
+
+    #[test]
+    fn missing_parents_only_break_their_children() {
+        let (manifest, broken) = resolved(r#"{
+            "stone": { "pipeline": "standard", "diffuse": "stone", "roughness": 0.8, "metallic": 0.0 },
+            "moss": { "parent": "grass", "roughness": 0.9 },
+            "wet_moss": { "parent": "moss", "roughness": 0.1 }
+        }"#);
+        let names: Vec<&str> = broken.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["moss", "wet_moss"]);
+        // The missing parent is reported for every material whose chain reaches it
+        for (_, error) in &broken {
+            assert!(matches!(error, AssetError::MissingParentMaterial { material, parent } if material == "moss" && parent == "grass"));
+        }
+        assert_eq!(manifest.materials["moss"], error_material_config());
+        assert_eq!(manifest.materials["stone"].diffuse, "stone");
+    }
+
+    #[test]
+    fn parent_cycles_are_broken() {
+        let (manifest, broken) = resolved(r#"{
+            "a": { "parent": "b", "roughness": 0.5 },
+            "b": { "parent": "a", "metallic": 0.5 },
+            "c": { "parent": "a" },
+            "self": { "parent": "self" }
+        }"#);
+        let chains: Vec<Vec<String>> = broken.iter()
+            .map(|(_, error)| match error {
+                AssetError::MaterialParentCycle { chain } => chain.clone(),
+                error => panic!("unexpected error: {}", error),
+            })
+            .collect();
+        assert_eq!(chains, [
+            vec!["a", "b", "a"],
+            vec!["b", "a", "b"],
+            vec!["a", "b", "a"],
+            vec!["self", "self"],
+        ]);
+        assert_eq!(manifest.materials.len(), 4);
+        assert!(manifest.materials.values().all(|config| *config == error_material_config()));
+    }
+
+    #[test]
+    fn materials_without_parent_need_every_field() {
+        let (_, broken) = resolved(r#"{ "bare": { "diffuse": "stone", "roughness": 0.5, "metallic": 0.0 } }"#);
+        assert!(matches!(&broken[..], [(_, AssetError::IncompleteMaterial { field: "pipeline", .. })]));
+    }
+}
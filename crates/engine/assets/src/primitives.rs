@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+use glam::{Vec2, Vec3};
+use serde::Deserialize;
+use engine_gpu_types::{VertexPTN, VertexPTNT};
+use crate::tangents::with_tangents;
+
+// Parametric meshes for blockouts, centered on the origin with Y up. Box faces are
+// ordered +X, -X, +Y, -Y, +Z, -Z. In the manifest's `primitives` section they are written
+// as objects with a "type" field, e.g. { "type": "plane", "size": [20, 20], ... }.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Primitive {
+    Plane { size: [f32; 2], subdivisions: [u32; 2], uv_scale: [f32; 2] },
+    Box { size: [f32; 3], face_uv_scale: [[f32; 2]; 6] },
+    Sphere { radius: f32, rings: u32, segments: u32 },
+    Icosphere { radius: f32, subdivisions: u32 },
+    Cylinder { radius: f32, height: f32, segments: u32 },
+    Cone { radius: f32, height: f32, segments: u32 },
+    Capsule { radius: f32, height: f32, rings: u32, segments: u32 },
+    Torus { major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32 },
+}
+
+impl Primitive {
+    // Unit cube with one texture repeat per face
+    pub fn unit_box() -> Self {
+        Primitive::Box { size: [1.0; 3], face_uv_scale: [[1.0; 2]; 6] }
+    }
+
+    // Segment counts are clamped to the smallest value that still gives a closed shape
+    pub fn generate(&self) -> (Vec<VertexPTNT>, Vec<u32>) {
+        let mut mesh = MeshBuilder::default();
+        match *self {
+            Primitive::Plane { size, subdivisions, uv_scale } => {
+                let [width, depth] = size;
+                mesh.surface(subdivisions[0].max(1), subdivisions[1].max(1), |s, t| (
+                    Vec3::new((s - 0.5) * width, 0.0, (t - 0.5) * depth),
+                    Vec3::Y,
+                    Vec2::new(s * uv_scale[0], t * uv_scale[1]),
+                ));
+            }
+            Primitive::Box { size, face_uv_scale } => {
+                let extent = Vec3::from(size) * 0.5;
+                // (normal, u axis, v axis), v runs down the texture
+                let faces = [
+                    (Vec3::X, Vec3::NEG_Z, Vec3::NEG_Y),
+                    (Vec3::NEG_X, Vec3::Z, Vec3::NEG_Y),
+                    (Vec3::Y, Vec3::X, Vec3::Z),
+                    (Vec3::NEG_Y, Vec3::X, Vec3::NEG_Z),
+                    (Vec3::Z, Vec3::X, Vec3::NEG_Y),
+                    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::NEG_Y),
+                ];
+                for ((normal, u_axis, v_axis), uv_scale) in faces.into_iter().zip(face_uv_scale) {
+                    mesh.surface(1, 1, |s, t| (
+                        (normal + u_axis * (2.0 * s - 1.0) + v_axis * (2.0 * t - 1.0)) * extent,
+                        normal,
+                        Vec2::new(s * uv_scale[0], t * uv_scale[1]),
+                    ));
+                }
+            }
+            Primitive::Sphere { radius, rings, segments } => {
+                mesh.surface(segments.max(3), rings.max(2), |s, t| {
+                    let normal = spherical(s * TAU, t * PI);
+                    (normal * radius, normal, Vec2::new(s, t))
+                });
+            }
+            Primitive::Icosphere { radius, subdivisions } => icosphere(&mut mesh, radius, subdivisions),
+            Primitive::Cylinder { radius, height, segments } => {
+                let segments = segments.max(3);
+                let half = height * 0.5;
+                mesh.surface(segments, 1, |s, t| {
+                    let normal = spherical(s * TAU, PI * 0.5);
+                    (normal * radius + Vec3::Y * (half - t * height), normal, Vec2::new(s, t))
+                });
+                mesh.disc(radius, half, Vec3::Y, segments);
+                mesh.disc(radius, -half, Vec3::NEG_Y, segments);
+            }
+            Primitive::Cone { radius, height, segments } => {
+                let segments = segments.max(3);
+                let half = height * 0.5;
+                // The side normal leans up by the slope of the cone
+                let slope = radius.atan2(height);
+                mesh.surface(segments, 1, |s, t| {
+                    let around = spherical(s * TAU, PI * 0.5);
+                    let normal = (around * slope.cos() + Vec3::Y * slope.sin()).normalize();
+                    (around * radius * t + Vec3::Y * (half - t * height), normal, Vec2::new(s, t))
+                });
+                mesh.disc(radius, -half, Vec3::NEG_Y, segments);
+            }
+            Primitive::Capsule { radius, height, rings, segments } => {
+                let rings = rings.max(1);
+                let half = height * 0.5;
+                // Rows 0..=rings are the top hemisphere, the rest the bottom one. The band
+                // between the two equator rows is the cylinder.
+                let profile_length = PI * radius + height;
+                let rows = 2 * rings + 1;
+                mesh.surface(segments.max(3), rows, |s, t| {
+                    let row = (t * rows as f32).round() as u32;
+                    let (theta, offset) = if row <= rings {
+                        (row as f32 / rings as f32 * PI * 0.5, half)
+                    } else {
+                        ((row - 1) as f32 / rings as f32 * PI * 0.5, -half)
+                    };
+                    let normal = spherical(s * TAU, theta);
+                    let position = normal * radius + Vec3::Y * offset;
+                    let arc = if row <= rings { theta * radius } else { theta * radius + height };
+                    (position, normal, Vec2::new(s, arc / profile_length))
+                });
+            }
+            Primitive::Torus { major_radius, minor_radius, major_segments, minor_segments } => {
+                mesh.surface(major_segments.max(3), minor_segments.max(3), |s, t| {
+                    let around = spherical(s * TAU, PI * 0.5);
+                    let (sin, cos) = (t * TAU).sin_cos();
+                    let normal = around * cos + Vec3::Y * sin;
+                    (around * major_radius + normal * minor_radius, normal, Vec2::new(s, t))
+                });
+            }
+        }
+
+        let vertices = with_tangents(&mesh.vertices, &mesh.indices);
+        (vertices, mesh.indices)
+    }
+}
+
+// Direction for azimuth `phi` around Y and polar angle `theta` from +Y
+fn spherical(phi: f32, theta: f32) -> Vec3 {
+    let (sin_theta, cos_theta) = theta.sin_cos();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+    Vec3::new(cos_phi * sin_theta, cos_theta, sin_phi * sin_theta)
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    vertices: Vec<VertexPTN>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.vertices.push(VertexPTN {
+            position: position.to_array(),
+            tex_coords: uv.to_array(),
+            normal: normal.to_array(),
+        });
+        (self.vertices.len() - 1) as u32
+    }
+
+    // Winds the triangle counter-clockwise around its vertex normals. Degenerate triangles,
+    // e.g. at sphere poles and the cone tip, are dropped.
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        let [pa, pb, pc] = [a, b, c].map(|i| Vec3::from(self.vertices[i as usize].position));
+        let face = (pb - pa).cross(pc - pa);
+        if face.length_squared() <= f32::EPSILON * f32::EPSILON {
+            return;
+        }
+        let normal: Vec3 = [a, b, c].iter().map(|&i| Vec3::from(self.vertices[i as usize].normal)).sum();
+        if face.dot(normal) >= 0.0 {
+            self.indices.extend([a, b, c]);
+        } else {
+            self.indices.extend([a, c, b]);
+        }
+    }
+
+    // Grid of (cols + 1) x (rows + 1) vertices. `f` maps the grid fractions to position,
+    // normal and UV. Seam columns are duplicated so UVs don't wrap.
+    fn surface(&mut self, cols: u32, rows: u32, f: impl Fn(f32, f32) -> (Vec3, Vec3, Vec2)) {
+        let first = self.vertices.len() as u32;
+        for row in 0..=rows {
+            for col in 0..=cols {
+                let (position, normal, uv) = f(col as f32 / cols as f32, row as f32 / rows as f32);
+                self.vertex(position, normal, uv);
+            }
+        }
+
+        let stride = cols + 1;
+        for row in 0..rows {
+            for col in 0..cols {
+                let i0 = first + row * stride + col;
+                let (i1, i2, i3) = (i0 + 1, i0 + stride + 1, i0 + stride);
+                self.triangle(i0, i1, i2);
+                self.triangle(i0, i2, i3);
+            }
+        }
+    }
+
+    // Flat cap at height `y`, UVs project the disc onto the unit square
+    fn disc(&mut self, radius: f32, y: f32, normal: Vec3, segments: u32) {
+        let center = self.vertex(Vec3::Y * y, normal, Vec2::splat(0.5));
+        let first = self.vertices.len() as u32;
+        for i in 0..=segments {
+            let around = spherical(i as f32 / segments as f32 * TAU, PI * 0.5);
+            let uv = Vec2::new(around.x, around.z) * 0.5 + 0.5;
+            self.vertex(around * radius + Vec3::Y * y, normal, uv);
+        }
+        for i in 0..segments {
+            self.triangle(center, first + i, first + i + 1);
+        }
+    }
+}
+
+// Subdivided icosahedron with spherical UVs. Triangles that straddle the u seam get their
+// own vertices with u shifted past 1.
+fn icosphere(mesh: &mut MeshBuilder, radius: f32, subdivisions: u32) {
+    let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+    let mut points: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ].iter().map(|&(x, y, z)| Vec3::new(x, y, z).normalize()).collect();
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    // Each level splits every triangle into four, shared edge midpoints are reused
+    for _ in 0..subdivisions.min(6) {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, points: &mut Vec<Vec3>| -> u32 {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push(((points[a as usize] + points[b as usize]) * 0.5).normalize());
+                (points.len() - 1) as u32
+            })
+        };
+        faces = faces.iter()
+            .flat_map(|&[a, b, c]| {
+                let ab = midpoint(a, b, &mut points);
+                let bc = midpoint(b, c, &mut points);
+                let ca = midpoint(c, a, &mut points);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let uv_of = |p: Vec3| Vec2::new(0.5 - p.z.atan2(p.x) / TAU, p.y.clamp(-1.0, 1.0).acos() / PI);
+    let mut vertex_ids: HashMap<(u32, u32), u32> = HashMap::new();
+    for face in faces {
+        let mut uvs = face.map(|i| uv_of(points[i as usize]));
+        let max_u = uvs.iter().map(|uv| uv.x).fold(f32::MIN, f32::max);
+        for (uv, &i) in uvs.iter_mut().zip(&face) {
+            if max_u - uv.x > 0.5 {
+                uv.x += 1.0;
+            }
+            // Poles take the u of the rest of their triangle
+            if points[i as usize].y.abs() > 0.9999 {
+                uv.x = f32::NAN;
+            }
+        }
+        let pole_u = uvs.iter().filter(|uv| !uv.x.is_nan()).map(|uv| uv.x).sum::<f32>() / 2.0;
+        let ids = std::array::from_fn::<u32, 3, _>(|k| {
+            let uv = if uvs[k].x.is_nan() { Vec2::new(pole_u, uvs[k].y) } else { uvs[k] };
+            let point = points[face[k] as usize];
+            *vertex_ids.entry((face[k], uv.x.to_bits())).or_insert_with(|| mesh.vertex(point * radius, point, uv))
+        });
+        mesh.triangle(ids[0], ids[1], ids[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_primitives() -> Vec<Primitive> {
+        vec![
+            Primitive::Plane { size: [4.0, 2.0], subdivisions: [4, 2], uv_scale: [2.0, 1.0] },
+            Primitive::unit_box(),
+            Primitive::Sphere { radius: 1.5, rings: 8, segments: 12 },
+            Primitive::Icosphere { radius: 1.5, subdivisions: 2 },
+            Primitive::Cylinder { radius: 0.5, height: 2.0, segments: 12 },
+            Primitive::Cone { radius: 0.5, height: 2.0, segments: 12 },
+            Primitive::Capsule { radius: 0.5, height: 1.0, rings: 4, segments: 12 },
+            Primitive::Torus { major_radius: 1.0, minor_radius: 0.25, major_segments: 16, minor_segments: 8 },
+        ]
+    }
+
+    fn positions(vertices: &[VertexPTNT]) -> impl Iterator<Item = Vec3> + '_ {
+        vertices.iter().map(|v| Vec3::from(v.position))
+    }
+
+    #[test]
+    fn normals_and_tangents_are_unit_length() {
+        for primitive in all_primitives() {
+            // Pole rows keep a vertex that no triangle uses, it gets no tangent
+            let (vertices, indices) = primitive.generate();
+            for v in indices.iter().map(|&i| &vertices[i as usize]) {
+                let tangent = Vec3::from_slice(&v.tangent[..3]);
+                assert!((Vec3::from(v.normal).length() - 1.0).abs() < 1e-4, "{:?}: normal {:?}", primitive, v.normal);
+                assert!((tangent.length() - 1.0).abs() < 1e-3, "{:?}: tangent {:?}", primitive, v.tangent);
+                assert!(tangent.dot(Vec3::from(v.normal)).abs() < 1e-3, "{:?}: tangent not orthogonal", primitive);
+            }
+        }
+    }
+
+    #[test]
+    fn triangles_wind_counter_clockwise_around_their_normals() {
+        for primitive in all_primitives() {
+            let (vertices, indices) = primitive.generate();
+            assert!(!indices.is_empty() && indices.len() % 3 == 0);
+            for t in indices.chunks_exact(3) {
+                assert!(t.iter().all(|&i| (i as usize) < vertices.len()));
+                let [a, b, c] = [t[0], t[1], t[2]].map(|i| &vertices[i as usize]);
+                let face = (Vec3::from(b.position) - Vec3::from(a.position)).cross(Vec3::from(c.position) - Vec3::from(a.position));
+                let normal = Vec3::from(a.normal) + Vec3::from(b.normal) + Vec3::from(c.normal);
+                assert!(face.dot(normal) > 0.0, "{:?}: triangle {:?} faces inwards", primitive, t);
+            }
+        }
+    }
+
+    #[test]
+    fn unit_box_spans_half_a_unit() {
+        let (vertices, indices) = Primitive::unit_box().generate();
+        assert_eq!((vertices.len(), indices.len()), (24, 36));
+        for v in &vertices {
+            let position = Vec3::from(v.position);
+            let normal = Vec3::from(v.normal);
+            assert_eq!(position.abs(), Vec3::splat(0.5));
+            // Every vertex lies on the face its normal points out of
+            assert_eq!(position.dot(normal), 0.5);
+            assert!(v.tex_coords.iter().all(|&uv| uv == 0.0 || uv == 1.0));
+        }
+    }
+
+    #[test]
+    fn spheres_keep_their_radius() {
+        for primitive in [Primitive::Sphere { radius: 1.5, rings: 8, segments: 12 }, Primitive::Icosphere { radius: 1.5, subdivisions: 2 }] {
+            let (vertices, _) = primitive.generate();
+            for v in &vertices {
+                let position = Vec3::from(v.position);
+                assert!((position.length() - 1.5).abs() < 1e-5, "{:?}: {:?}", primitive, position);
+                assert!(position.normalize().abs_diff_eq(Vec3::from(v.normal), 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn uvs_cover_the_texture() {
+        let (vertices, _) = Primitive::Plane { size: [4.0, 2.0], subdivisions: [4, 2], uv_scale: [2.0, 1.0] }.generate();
+        let uvs: Vec<Vec2> = vertices.iter().map(|v| Vec2::from(v.tex_coords)).collect();
+        assert_eq!(uvs.iter().copied().fold(Vec2::MAX, Vec2::min), Vec2::ZERO);
+        assert_eq!(uvs.iter().copied().fold(Vec2::MIN, Vec2::max), Vec2::new(2.0, 1.0));
+
+        // The seam column is duplicated, so u runs from 0 to 1 without wrapping
+        let (vertices, _) = Primitive::Sphere { radius: 1.0, rings: 8, segments: 12 }.generate();
+        let uvs: Vec<Vec2> = vertices.iter().map(|v| Vec2::from(v.tex_coords)).collect();
+        assert_eq!(uvs.iter().copied().fold(Vec2::MAX, Vec2::min), Vec2::ZERO);
+        assert_eq!(uvs.iter().copied().fold(Vec2::MIN, Vec2::max), Vec2::ONE);
+    }
+
+    #[test]
+    fn shapes_are_centered_on_the_origin() {
+        let extents = |primitive: Primitive| {
+            let (vertices, _) = primitive.generate();
+            let (min, max) = positions(&vertices).fold((Vec3::MAX, Vec3::MIN), |(min, max), p| (min.min(p), max.max(p)));
+            assert!((min + max).abs().max_element() < 1e-5, "{:?} is off center", primitive);
+            max
+        };
+        let close = |a: Vec3, b: Vec3| a.abs_diff_eq(b, 1e-5);
+        assert!(close(extents(Primitive::Cylinder { radius: 0.5, height: 2.0, segments: 12 }), Vec3::new(0.5, 1.0, 0.5)));
+        assert!(close(extents(Primitive::Cone { radius: 0.5, height: 2.0, segments: 12 }), Vec3::new(0.5, 1.0, 0.5)));
+        assert!(close(extents(Primitive::Capsule { radius: 0.5, height: 1.0, rings: 4, segments: 12 }), Vec3::new(0.5, 1.0, 0.5)));
+        assert!(close(extents(Primitive::Torus { major_radius: 1.0, minor_radius: 0.25, major_segments: 16, minor_segments: 8 }), Vec3::new(1.25, 0.25, 1.25)));
+    }
+}
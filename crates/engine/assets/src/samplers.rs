@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+// A named sampler from the manifest. Every field is optional, the defaults match
+// `AssetManager::default_sampler`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SamplerConfig {
+    pub address_mode_u: AddressMode,
+    pub address_mode_v: AddressMode,
+    pub address_mode_w: AddressMode,
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub mipmap_filter: FilterMode,
+    pub anisotropy_clamp: u16,
+    pub lod_min_clamp: f32,
+    pub lod_max_clamp: f32,
+    // wgpu samplers have no bias, it is applied in the shader through MaterialUniform
+    pub lod_bias: f32,
+    pub compare: Option<CompareFunction>,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            mipmap_filter: FilterMode::Linear,
+            anisotropy_clamp: 1,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+            lod_bias: 0.0,
+            compare: None,
+        }
+    }
+}
+
+impl SamplerConfig {
+    // Catches what wgpu would otherwise reject with a validation panic
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if !(1..=16).contains(&self.anisotropy_clamp) {
+            return Err("anisotropy_clamp must be between 1 and 16");
+        }
+        let all_linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == FilterMode::Linear);
+        if self.anisotropy_clamp > 1 && !all_linear {
+            return Err("anisotropic filtering requires linear mag, min and mipmap filters");
+        }
+        if self.lod_min_clamp < 0.0 || self.lod_max_clamp < self.lod_min_clamp {
+            return Err("lod clamps must satisfy 0 <= lod_min_clamp <= lod_max_clamp");
+        }
+        Ok(())
+    }
+
+    fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: None,
+            address_mode_u: self.address_mode_u.into(),
+            address_mode_v: self.address_mode_v.into(),
+            address_mode_w: self.address_mode_w.into(),
+            mag_filter: self.mag_filter.into(),
+            min_filter: self.min_filter.into(),
+            mipmap_filter: self.mipmap_filter.into(),
+            lod_min_clamp: self.lod_min_clamp,
+            lod_max_clamp: self.lod_max_clamp,
+            compare: self.compare.map(Into::into),
+            anisotropy_clamp: self.anisotropy_clamp,
+            border_color: None,
+        }
+    }
+
+    // The bias is not part of the wgpu descriptor, so it is ignored for deduplication
+    fn same_descriptor(&self, other: &SamplerConfig) -> bool {
+        SamplerConfig { lod_bias: 0.0, ..self.clone() } == SamplerConfig { lod_bias: 0.0, ..other.clone() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressMode {
+    ClampToEdge,
+    Repeat,
+    MirrorRepeat,
+}
+
+impl From<AddressMode> for wgpu::AddressMode {
+    fn from(mode: AddressMode) -> Self {
+        match mode {
+            AddressMode::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            AddressMode::Repeat => wgpu::AddressMode::Repeat,
+            AddressMode::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    Nearest,
+    Linear,
+}
+
+impl From<FilterMode> for wgpu::FilterMode {
+    fn from(mode: FilterMode) -> Self {
+        match mode {
+            FilterMode::Nearest => wgpu::FilterMode::Nearest,
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareFunction {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+impl From<CompareFunction> for wgpu::CompareFunction {
+    fn from(function: CompareFunction) -> Self {
+        match function {
+            CompareFunction::Never => wgpu::CompareFunction::Never,
+            CompareFunction::Less => wgpu::CompareFunction::Less,
+            CompareFunction::Equal => wgpu::CompareFunction::Equal,
+            CompareFunction::LessEqual => wgpu::CompareFunction::LessEqual,
+            CompareFunction::Greater => wgpu::CompareFunction::Greater,
+            CompareFunction::NotEqual => wgpu::CompareFunction::NotEqual,
+            CompareFunction::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+            CompareFunction::Always => wgpu::CompareFunction::Always,
+        }
+    }
+}
+
+// One wgpu::Sampler per distinct descriptor, shared by every material that uses it. The
+// first entry is the default sampler.
+pub(crate) struct SamplerCache {
+    samplers: Vec<(SamplerConfig, wgpu::Sampler)>,
+}
+
+impl SamplerCache {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let config = SamplerConfig::default();
+        let sampler = device.create_sampler(&config.descriptor());
+        Self { samplers: vec![(config, sampler)] }
+    }
+
+    pub fn default_sampler(&self) -> wgpu::Sampler {
+        self.samplers[0].1.clone()
+    }
+
+    pub fn get_or_create(&mut self, device: &wgpu::Device, config: &SamplerConfig) -> Result<wgpu::Sampler, &'static str> {
+        if let Some((_, sampler)) = self.samplers.iter().find(|(existing, _)| existing.same_descriptor(config)) {
+            return Ok(sampler.clone());
+        }
+
+        config.validate()?;
+        let sampler = device.create_sampler(&config.descriptor());
+        self.samplers.push((config.clone(), sampler.clone()));
+        Ok(sampler)
+    }
+}
@@ -0,0 +1,276 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use engine_gpu_types::VertexPTNT;
+use glam::DVec3;
+
+// Import-time simplification for LOD levels. Edges are collapsed cheapest first by their
+// quadric error (Garland and Heckbert). A collapse moves a vertex onto a neighbour, so no
+// new vertices are made and UVs, normals and tangents stay exact. Vertices on open edges
+// never move: that keeps borders in place, and UV or normal seams too, since welding
+// leaves their vertices split.
+
+// Symmetric 4x4 matrix of a sum of squared plane distances, upper triangle only
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: DVec3, distance: f64, weight: f64) -> Self {
+        let [a, b, c] = normal.to_array();
+        let d = distance;
+        Self([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d].map(|q| q * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+        let (x, y, z) = (p.x, p.y, p.z);
+        let error = a2 * x * x + 2.0 * ab * x * y + 2.0 * ac * x * z + 2.0 * ad * x
+            + b2 * y * y + 2.0 * bc * y * z + 2.0 * bd * y
+            + c2 * z * z + 2.0 * cd * z
+            + d2;
+        error.max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Collapse {
+    cost: f64,
+    from: u32,
+    to: u32,
+}
+
+// Reversed, so the BinaryHeap pops the cheapest collapse first
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+// Indices of a mesh with about `ratio` of its triangles, into the same vertices. Meshes
+// with many locked vertices can stay above the target. Run `optimize_mesh` on the result
+// to drop the vertices that are no longer used.
+pub fn simplify_mesh(vertices: &[VertexPTNT], indices: &[u32], ratio: f32) -> Vec<u32> {
+    let mut triangles: Vec<[u32; 3]> = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+    let target = (triangles.len() as f32 * ratio.clamp(0.0, 1.0)) as usize;
+    let positions: Vec<DVec3> = vertices.iter().map(|v| glam::Vec3::from(v.position).as_dvec3()).collect();
+
+    let mut edge_uses: HashMap<(u32, u32), u32> = HashMap::new();
+    for triangle in &triangles {
+        for (a, b) in edges(triangle) {
+            *edge_uses.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    let mut locked = vec![false; vertices.len()];
+    for (&(a, b), &uses) in &edge_uses {
+        if uses == 1 {
+            locked[a as usize] = true;
+            locked[b as usize] = true;
+        }
+    }
+
+    // Planes weighted by triangle area, so slivers don't dominate
+    let mut quadrics = vec![Quadric::default(); vertices.len()];
+    let mut adjacency: Vec<Vec<u32>> = vec![Vec::new(); vertices.len()];
+    for (index, triangle) in triangles.iter().enumerate() {
+        let [a, b, c] = triangle.map(|v| positions[v as usize]);
+        let cross = (b - a).cross(c - a);
+        let area = cross.length() * 0.5;
+        if area > 0.0 {
+            let normal = cross.normalize();
+            let quadric = Quadric::from_plane(normal, -normal.dot(a), area);
+            for &vertex in triangle {
+                quadrics[vertex as usize].add(&quadric);
+            }
+        }
+        for &vertex in triangle {
+            adjacency[vertex as usize].push(index as u32);
+        }
+    }
+
+    let cost = |quadrics: &[Quadric], from: u32, to: u32| {
+        let mut quadric = quadrics[from as usize];
+        quadric.add(&quadrics[to as usize]);
+        quadric.error(positions[to as usize])
+    };
+    let mut heap = BinaryHeap::new();
+    for &(a, b) in edge_uses.keys() {
+        for (from, to) in [(a, b), (b, a)] {
+            if !locked[from as usize] {
+                heap.push(Collapse { cost: cost(&quadrics, from, to), from, to });
+            }
+        }
+    }
+
+    let mut alive = vec![true; triangles.len()];
+    let mut live = triangles.len();
+    let mut collapsed = vec![false; vertices.len()];
+    while live > target {
+        let Some(collapse) = heap.pop() else { break };
+        let (from, to) = (collapse.from, collapse.to);
+        if collapsed[from as usize] || collapsed[to as usize] {
+            continue;
+        }
+        // Quadrics only grow as vertices merge, stale entries go back with their real cost
+        let current = cost(&quadrics, from, to);
+        if current > collapse.cost {
+            heap.push(Collapse { cost: current, ..collapse });
+            continue;
+        }
+        let shared = |t: &u32| alive[*t as usize] && triangles[*t as usize].contains(&to);
+        if !adjacency[from as usize].iter().any(shared) || flips(&positions, &triangles, &alive, &adjacency[from as usize], from, to) {
+            continue;
+        }
+
+        for t in std::mem::take(&mut adjacency[from as usize]) {
+            if !alive[t as usize] {
+                continue;
+            }
+            let triangle = &mut triangles[t as usize];
+            if triangle.contains(&to) {
+                alive[t as usize] = false;
+                live -= 1;
+            } else {
+                triangle.iter_mut().filter(|v| **v == from).for_each(|v| *v = to);
+                adjacency[to as usize].push(t);
+            }
+        }
+        collapsed[from as usize] = true;
+        let merged = quadrics[from as usize];
+        quadrics[to as usize].add(&merged);
+
+        for &t in &adjacency[to as usize] {
+            if !alive[t as usize] {
+                continue;
+            }
+            for &other in &triangles[t as usize] {
+                if other == to {
+                    continue;
+                }
+                for (from, to) in [(other, to), (to, other)] {
+                    if !locked[from as usize] {
+                        heap.push(Collapse { cost: cost(&quadrics, from, to), from, to });
+                    }
+                }
+            }
+        }
+    }
+
+    triangles.iter().zip(&alive)
+        .filter(|(_, alive)| **alive)
+        .flat_map(|(triangle, _)| *triangle)
+        .collect()
+}
+
+fn edges(triangle: &[u32; 3]) -> [(u32, u32); 3] {
+    let [a, b, c] = *triangle;
+    [(a, b), (b, c), (c, a)]
+}
+
+// Whether moving `from` onto `to` turns one of the triangles that stay over
+fn flips(positions: &[DVec3], triangles: &[[u32; 3]], alive: &[bool], around: &[u32], from: u32, to: u32) -> bool {
+    around.iter()
+        .filter(|t| alive[**t as usize] && !triangles[**t as usize].contains(&to))
+        .any(|&t| {
+            let triangle = triangles[t as usize];
+            let normal = |moved: bool| {
+                let [a, b, c] = triangle.map(|v| positions[if moved && v == from { to } else { v } as usize]);
+                (b - a).cross(c - a)
+            };
+            normal(false).dot(normal(true)) <= 0.0
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounds::Aabb;
+    use crate::mesh_optimizer::weld_vertices;
+    use crate::primitives::Primitive;
+
+    // A closed icosphere with 1280 triangles, welded by position only so no seams are locked
+    fn closed_sphere() -> (Vec<VertexPTNT>, Vec<u32>) {
+        let (vertices, indices) = Primitive::Icosphere { radius: 2.0, subdivisions: 3 }.generate();
+        let positions: Vec<VertexPTNT> = vertices.iter()
+            .map(|v| VertexPTNT { position: v.position, ..bytemuck::Zeroable::zeroed() })
+            .collect();
+        weld_vertices(&positions, &indices)
+    }
+
+    fn bounds(vertices: &[VertexPTNT], indices: &[u32]) -> Aabb {
+        Aabb::from_points(indices.iter().map(|&i| glam::Vec3::from(vertices[i as usize].position)))
+    }
+
+    fn assert_valid(vertices: &[VertexPTNT], indices: &[u32]) {
+        assert_eq!(indices.len() % 3, 0);
+        for t in indices.chunks_exact(3) {
+            assert!(t.iter().all(|&i| (i as usize) < vertices.len()), "index out of range in {:?}", t);
+            assert!(t[0] != t[1] && t[1] != t[2] && t[2] != t[0], "degenerate triangle {:?}", t);
+        }
+    }
+
+    #[test]
+    fn closed_meshes_reach_the_target() {
+        let (vertices, indices) = closed_sphere();
+        let triangles = indices.len() / 3;
+        for ratio in [0.5, 0.25, 0.1] {
+            let simplified = simplify_mesh(&vertices, &indices, ratio);
+            assert_valid(&vertices, &simplified);
+            // A collapse removes two triangles, so the count can land one below the target
+            let target = (triangles as f32 * ratio) as usize;
+            let count = simplified.len() / 3;
+            assert!(count <= target && count + 2 > target, "{} triangles for a target of {}", count, target);
+        }
+    }
+
+    #[test]
+    fn simplified_bounds_stay_inside_and_close() {
+        let (vertices, indices) = closed_sphere();
+        let original = bounds(&vertices, &indices);
+        let simplified = bounds(&vertices, &simplify_mesh(&vertices, &indices, 0.25));
+        // Vertices only move onto neighbours, the box can shrink but never grow
+        assert!(simplified.min.cmpge(original.min).all() && simplified.max.cmple(original.max).all());
+        let extents = simplified.half_extents() / original.half_extents();
+        assert!(extents.min_element() > 0.9, "bounds shrank to {}", extents);
+    }
+
+    #[test]
+    fn open_borders_keep_their_vertices() {
+        let plane = Primitive::Plane { size: [4.0, 4.0], subdivisions: [8, 8], uv_scale: [1.0, 1.0] };
+        let (vertices, indices) = plane.generate();
+        let simplified = simplify_mesh(&vertices, &indices, 0.0);
+        assert_valid(&vertices, &simplified);
+        assert!(simplified.len() < indices.len());
+        assert_eq!(bounds(&vertices, &simplified), bounds(&vertices, &indices));
+
+        // All 32 border vertices stay. The 30 triangles that span them are the minimum, a
+        // collapse that would flip a triangle can leave an inner vertex or two.
+        let border = (0..81u32).filter(|i| i % 9 == 0 || i % 9 == 8 || i / 9 == 0 || i / 9 == 8);
+        assert!(border.clone().count() == 32 && border.into_iter().all(|i| simplified.contains(&i)));
+        assert!((30..=40).contains(&(simplified.len() / 3)), "{} triangles left", simplified.len() / 3);
+    }
+
+    #[test]
+    fn full_ratio_keeps_the_mesh() {
+        let (vertices, indices) = closed_sphere();
+        assert_eq!(simplify_mesh(&vertices, &indices, 1.0), indices);
+    }
+}
@@ -0,0 +1,95 @@
+use glam::{Mat4, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+// Local transform of a joint, relative to its parent joint
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct JointTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl JointTransform {
+    pub const IDENTITY: Self = Self { translation: Vec3::ZERO, rotation: Quat::IDENTITY, scale: Vec3::ONE };
+
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Self { translation, rotation, scale }
+    }
+
+    pub fn to_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Joint {
+    pub name: String,
+    // None for the roots of the hierarchy
+    pub parent: Option<usize>,
+    // Transform in the pose the file was exported in
+    pub rest: JointTransform,
+    // Moves a vertex from model space into the joint's space in the bind pose
+    pub inverse_bind: Mat4,
+}
+
+// The joint hierarchy a skinned mesh is bound to. Joint indices are the ones the mesh's
+// VertexJW stream refers to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Skin {
+    pub joints: Vec<Joint>,
+    // Transform of the nodes above the root joints that aren't joints themselves
+    pub root: Mat4,
+    // Joint indices with every parent before its children
+    order: Vec<usize>,
+}
+
+impl Skin {
+    // None when the parents don't form a hierarchy
+    pub fn new(joints: Vec<Joint>, root: Mat4) -> Option<Self> {
+        let mut order = Vec::with_capacity(joints.len());
+        let mut placed = vec![false; joints.len()];
+        while order.len() < joints.len() {
+            let before = order.len();
+            for (index, joint) in joints.iter().enumerate() {
+                let ready = match joint.parent {
+                    Some(parent) => *placed.get(parent)?,
+                    None => true,
+                };
+                if ready && !placed[index] {
+                    placed[index] = true;
+                    order.push(index);
+                }
+            }
+            // Only joints in a parent cycle are left
+            if order.len() == before {
+                return None;
+            }
+        }
+        Some(Self { joints, root, order })
+    }
+
+    pub fn rest_pose(&self) -> Vec<JointTransform> {
+        self.joints.iter().map(|joint| joint.rest).collect()
+    }
+
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    // Skinning matrices of a pose with one local transform per joint: the joint's model
+    // space transform times its inverse bind matrix. Joints the pose is missing stay at rest.
+    pub fn joint_matrices(&self, pose: &[JointTransform], matrices: &mut Vec<Mat4>) {
+        matrices.clear();
+        matrices.resize(self.joints.len(), Mat4::IDENTITY);
+        for &index in &self.order {
+            let joint = &self.joints[index];
+            let local = pose.get(index).unwrap_or(&joint.rest).to_matrix();
+            let parent = joint.parent.map_or(self.root, |parent| matrices[parent]);
+            matrices[index] = parent * local;
+        }
+        for (matrix, joint) in matrices.iter_mut().zip(&self.joints) {
+            *matrix *= joint.inverse_bind;
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use crate::data_structures::{AssetId, Handle};
+
+struct Slot<T> {
+    value: Option<T>,
+    generation: u32,
+    // The storage holds one reference itself, every Handle adds one
+    refs: Arc<()>,
+    persistent: bool,
+}
+
+// Generational slot map. Freed slots are reused with a bumped generation.
+pub(crate) struct AssetStorage<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for AssetStorage<T> {
+    fn default() -> Self {
+        Self { slots: Vec::new(), free: Vec::new() }
+    }
+}
+
+impl<T> AssetStorage<T> {
+    pub fn insert(&mut self, value: T) -> AssetId<T> {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return AssetId::new(index, slot.generation);
+        }
+
+        let index = self.slots.len() as u32;
+        self.slots.push(Slot { value: Some(value), generation: 0, refs: Arc::new(()), persistent: false });
+        AssetId::new(index, 0)
+    }
+
+    // Swaps the asset behind a live id and returns the old one, handles to it stay valid
+    pub fn replace(&mut self, id: AssetId<T>, value: T) -> Option<T> {
+        self.slot_mut(id).and_then(|slot| slot.value.replace(value))
+    }
+
+    pub fn get(&self, id: AssetId<T>) -> Option<&T> {
+        self.slots.get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_ref())
+    }
+
+    pub fn get_mut(&mut self, id: AssetId<T>) -> Option<&mut T> {
+        self.slot_mut(id).and_then(|slot| slot.value.as_mut())
+    }
+
+    pub fn contains(&self, id: AssetId<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn handle(&self, id: AssetId<T>) -> Option<Handle<T>> {
+        self.slots.get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation && slot.value.is_some())
+            .map(|slot| Handle::new(id, Arc::clone(&slot.refs)))
+    }
+
+    // Persistent assets are never returned by `unused`
+    pub fn set_persistent(&mut self, id: AssetId<T>) {
+        if let Some(slot) = self.slot_mut(id) {
+            slot.persistent = true;
+        }
+    }
+
+    // Live assets without any outstanding Handle
+    pub fn unused(&self) -> Vec<AssetId<T>> {
+        self.slots.iter().enumerate()
+            .filter(|(_, slot)| slot.value.is_some() && !slot.persistent && Arc::strong_count(&slot.refs) == 1)
+            .map(|(index, slot)| AssetId::new(index as u32, slot.generation))
+            .collect()
+    }
+
+    pub fn remove(&mut self, id: AssetId<T>) -> Option<T> {
+        let slot = self.slot_mut(id)?;
+        let value = slot.value.take();
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.persistent = false;
+        // Handles that are still alive keep the old counter and can no longer pin the slot
+        slot.refs = Arc::new(());
+        self.free.push(id.index);
+        value
+    }
+
+    // Frees every slot. Generations are kept so ids from before stay invalid.
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() {
+            let id = AssetId::new(index as u32, self.slots[index].generation);
+            if self.contains(id) {
+                self.remove(id);
+            }
+        }
+    }
+
+    fn slot_mut(&mut self, id: AssetId<T>) -> Option<&mut Slot<T>> {
+        self.slots.get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation && slot.value.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reused_slots_get_a_new_generation() {
+        let mut storage = AssetStorage::default();
+        let first = storage.insert("first");
+        assert_eq!(storage.remove(first), Some("first"));
+
+        let second = storage.insert("second");
+        assert_eq!(second.index, first.index);
+        assert_eq!(second.generation, first.generation + 1);
+        assert_eq!(storage.get(second), Some(&"second"));
+    }
+
+    #[test]
+    fn stale_ids_are_rejected() {
+        let mut storage = AssetStorage::default();
+        let stale = storage.insert(1);
+        storage.remove(stale);
+        let current = storage.insert(2);
+
+        assert!(!storage.contains(stale));
+        assert!(storage.get_mut(stale).is_none());
+        assert!(storage.handle(stale).is_none());
+        assert_eq!(storage.remove(stale), None);
+        storage.replace(stale, 3);
+        assert_eq!(storage.get(current), Some(&2));
+
+        storage.clear();
+        assert!(!storage.contains(current));
+    }
+
+    #[test]
+    fn assets_become_unused_once_handles_drop() {
+        let mut storage = AssetStorage::default();
+        let id = storage.insert("mesh");
+        let handle = storage.handle(id).unwrap();
+        let clone = handle.clone();
+        assert!(storage.unused().is_empty());
+
+        drop(handle);
+        assert!(storage.unused().is_empty());
+        drop(clone);
+        assert_eq!(storage.unused(), vec![id]);
+    }
+
+    #[test]
+    fn handles_to_removed_assets_do_not_pin_the_new_slot() {
+        let mut storage = AssetStorage::default();
+        let old = storage.insert("old");
+        let _handle = storage.handle(old).unwrap();
+        storage.remove(old);
+
+        let new = storage.insert("new");
+        assert_eq!(storage.unused(), vec![new]);
+    }
+
+    #[test]
+    fn persistent_slots_are_never_unused() {
+        let mut storage = AssetStorage::default();
+        let persistent = storage.insert("internal:error");
+        let regular = storage.insert("texture");
+        storage.set_persistent(persistent);
+        drop(storage.handle(persistent));
+
+        assert_eq!(storage.unused(), vec![regular]);
+        // The flag belongs to the asset, not the slot
+        storage.remove(persistent);
+        let reused = storage.insert("next");
+        assert!(storage.unused().contains(&reused));
+    }
+}
@@ -0,0 +1,73 @@
+use engine_gpu_types::{VertexPTN, VertexPTNT};
+
+// Adapter that lets mikktspace walk an indexed triangle list
+struct TangentGeometry<'a> {
+    vertices: &'a mut [VertexPTNT],
+    indices: &'a [u32],
+}
+
+impl mikktspace::Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).position
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertex(face, vert).normal
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        self.vertex(face, vert).tex_coords
+    }
+
+    // Vertices shared between faces keep the last written tangent. Loaders already split
+    // vertices at UV seams, so shared corners agree in all but degenerate cases.
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.indices[face * 3 + vert] as usize;
+        self.vertices[index].tangent = tangent;
+    }
+}
+
+impl TangentGeometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> &VertexPTNT {
+        &self.vertices[self.indices[face * 3 + vert] as usize]
+    }
+}
+
+// Adds MikkTSpace-compatible tangents to an indexed triangle list. Meshes mikktspace
+// rejects (e.g. no UVs) get an arbitrary tangent perpendicular to the normal so the TBN
+// basis stays valid.
+pub fn with_tangents(vertices: &[VertexPTN], indices: &[u32]) -> Vec<VertexPTNT> {
+    let mut vertices: Vec<VertexPTNT> = vertices.iter()
+        .map(|v| VertexPTNT {
+            position: v.position,
+            tex_coords: v.tex_coords,
+            normal: v.normal,
+            tangent: [0.0; 4],
+        })
+        .collect();
+
+    let generated = !indices.is_empty()
+        && mikktspace::generate_tangents(&mut TangentGeometry { vertices: &mut vertices, indices });
+
+    if !generated {
+        for vertex in vertices.iter_mut() {
+            vertex.tangent = fallback_tangent(vertex.normal);
+        }
+    }
+
+    vertices
+}
+
+fn fallback_tangent(normal: [f32; 3]) -> [f32; 4] {
+    let n = glam::Vec3::from(normal).normalize_or(glam::Vec3::Y);
+    let tangent = n.any_orthonormal_vector();
+    [tangent.x, tangent.y, tangent.z, 1.0]
+}
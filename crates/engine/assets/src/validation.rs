@@ -0,0 +1,355 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use engine_vfs::Vfs;
+use crate::asset_error::AssetError;
+use crate::asset_manager::{AssetManifest, LodThreshold, MaterialConfig, MeshEntry, TextureKind, manifest_jobs};
+use crate::loader::{DecodedAsset, spawn_workers};
+use crate::packs::read_manifest_tree;
+
+// Offline checks of a manifest, without a GPU. Every file is decoded like a real load does,
+// then materials are resolved against the textures, samplers and pipelines they name.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Error).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == Severity::Warning).count()
+    }
+
+    fn error(&mut self, error: AssetError) {
+        self.issues.push(Issue { severity: Severity::Error, message: error.to_string() });
+    }
+
+    fn warning(&mut self, message: String) {
+        self.issues.push(Issue { severity: Severity::Warning, message });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            let label = match issue.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            writeln!(f, "{:>7}: {}", label, issue.message)?;
+        }
+        write!(f, "{} errors, {} warnings", self.errors(), self.warnings())
+    }
+}
+
+// Texture names materials can see once the manifest is loaded
+struct KnownTexture {
+    kind: TextureKind,
+    cubemap: bool,
+}
+
+// `pipelines` are the names the game registers in `AssetManager::pipeline_cache`. Only a
+// manifest that can't be read at all is an error of its own, everything else ends up in
+// the report, sorted with errors first.
+pub fn validate_manifest(vfs: &Vfs, manifest_path: &Path, pipelines: &[&str]) -> Result<ValidationReport, AssetError> {
+    let tree = read_manifest_tree(vfs, manifest_path)?;
+    let manifest = tree.manifest;
+    let mut report = ValidationReport::default();
+    // Their place is taken by the error material, which needs no further checks
+    let mut broken = HashSet::new();
+    for (name, error) in tree.broken_materials {
+        report.error(error);
+        broken.insert(name);
+    }
+
+    let mut textures: HashMap<String, KnownTexture> = manifest.textures.iter()
+        .map(|(name, entry)| (name.clone(), KnownTexture { kind: entry.kind(), cubemap: false }))
+        .collect();
+    for name in manifest.cubemaps.keys() {
+        if textures.insert(name.clone(), KnownTexture { kind: TextureKind::Color, cubemap: true }).is_some() {
+            report.warning(format!("Cubemap '{}' replaces the texture of the same name.", name));
+        }
+    }
+
+    // Imports bring their own textures and materials along
+    let mut materials: Vec<(String, MaterialConfig)> = manifest.materials.iter()
+        .filter(|(name, _)| !broken.contains(name.as_str()))
+        .map(|(name, config)| (name.clone(), config.clone()))
+        .collect();
+    let jobs = manifest_jobs(&manifest, &AssetManifest::default());
+    let job_count = jobs.len();
+    let results = spawn_workers(jobs, vfs);
+    for _ in 0..job_count {
+        let import = match results.recv().map_err(|_| AssetError::LoaderPanicked)? {
+            Ok(DecodedAsset::Obj { name, obj, .. }) => {
+                obj.warnings.iter().for_each(|warning| report.warning(warning.clone()));
+                obj.resolve(&name)
+            }
+            Ok(DecodedAsset::Gltf { name, gltf, .. }) => gltf.resolve(&name),
+            Ok(_) => continue,
+            Err(failure) => {
+                report.error(failure.error);
+                continue;
+            }
+        };
+        for texture in import.textures {
+            textures.insert(texture.name, KnownTexture { kind: texture.kind, cubemap: false });
+        }
+        materials.extend(import.materials);
+    }
+
+    for (name, config) in &manifest.samplers {
+        if let Err(reason) = config.validate() {
+            report.error(AssetError::InvalidSampler { sampler: name.clone(), reason });
+        }
+    }
+
+    let mut meshes: Vec<(&String, &MeshEntry)> = manifest.meshes.iter().collect();
+    meshes.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, entry) in meshes {
+        check_lods(name, entry, &mut report);
+    }
+
+    materials.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut used = HashSet::new();
+    for (name, config) in &materials {
+        check_material(name, config, &manifest, &textures, pipelines, &mut report);
+        used.insert(config.diffuse.as_str());
+        used.extend(config.normal.as_deref());
+    }
+
+    // Cubemaps are bound by code, not by materials
+    let mut unused: Vec<&String> = manifest.textures.keys()
+        .filter(|name| !used.contains(name.as_str()))
+        .collect();
+    unused.sort();
+    for name in unused {
+        report.warning(format!("Texture '{}' is not used by any material.", name));
+    }
+
+    report.issues.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.message.cmp(&b.message)));
+    Ok(report)
+}
+
+// Mirrors the checks `AssetManager::create_material` does at runtime
+fn check_material(
+    name: &str,
+    config: &MaterialConfig,
+    manifest: &AssetManifest,
+    textures: &HashMap<String, KnownTexture>,
+    pipelines: &[&str],
+    report: &mut ValidationReport,
+) {
+    if !pipelines.contains(&config.pipeline.as_str()) {
+        report.error(AssetError::MissingPipeline { material: name.to_string(), pipeline: config.pipeline.clone() });
+    }
+
+    let sampler = config.sampler.as_deref()
+        .or_else(|| manifest.textures.get(&config.diffuse).and_then(|entry| entry.sampler()));
+    if let Some(sampler) = sampler {
+        match manifest.samplers.get(sampler) {
+            None => report.error(AssetError::MissingSampler { material: name.to_string(), sampler: sampler.to_string() }),
+            Some(sampler_config) if sampler_config.compare.is_some() => report.error(AssetError::InvalidSampler {
+                sampler: sampler.to_string(),
+                reason: "comparison samplers can't be used by materials",
+            }),
+            Some(_) => {}
+        }
+    }
+
+    let slots = [
+        ("Diffuse", TextureKind::Color, Some(&config.diffuse)),
+        ("Normal", TextureKind::Normal, config.normal.as_ref()),
+    ];
+    for (slot, expected, texture) in slots {
+        let Some(texture) = texture else { continue };
+        // Internal textures are created by the engine itself
+        if texture.starts_with("internal:") {
+            continue;
+        }
+        match textures.get(texture) {
+            None => report.error(AssetError::MissingTexture {
+                material: name.to_string(),
+                slot,
+                texture: texture.clone(),
+            }),
+            Some(known) if known.cubemap => report.error(AssetError::WrongTextureDimension {
+                material: name.to_string(),
+                slot,
+                texture: texture.clone(),
+            }),
+            Some(known) if known.kind != expected => report.warning(format!(
+                "Material '{}' uses {:?} texture '{}' in its {} slot, which expects a {:?} texture.",
+                name, known.kind, texture, slot, expected
+            )),
+            Some(_) => {}
+        }
+    }
+}
+
+// Levels are picked from near to far, so distances have to grow and screen sizes shrink
+fn check_lods(name: &str, entry: &MeshEntry, report: &mut ValidationReport) {
+    let mut previous = None;
+    for (level, lod) in (1..).zip(entry.lods()) {
+        if let Some(ratio) = lod.simplify {
+            if lod.path.is_some() {
+                report.warning(format!("LOD level {} of mesh '{}' has a file, its simplify ratio is ignored.", level, name));
+            } else if !(ratio > 0.0 && ratio <= 1.0) {
+                report.warning(format!("LOD level {} of mesh '{}' has simplify ratio {}, it should be in (0, 1].", level, name, ratio));
+            }
+        }
+        let out_of_order = match (previous, lod.threshold) {
+            (Some(LodThreshold::Distance(before)), LodThreshold::Distance(distance)) => distance <= before,
+            (Some(LodThreshold::ScreenSize(before)), LodThreshold::ScreenSize(size)) => size >= before,
+            _ => false,
+        };
+        if out_of_order {
+            report.warning(format!("LOD level {} of mesh '{}' is never used, its threshold doesn't go past level {}'s.", level, name, level - 1));
+        }
+        previous = Some(lod.threshold);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // A directory with a 2x2 "stone.png" and "stone_n.png" next to the given manifests
+    fn manifest_dir(name: &str, manifests: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("engine_assets_{}_{}", std::process::id(), name));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in ["stone.png", "stone_n.png"] {
+            image::RgbaImage::new(2, 2).save(dir.join(file)).unwrap();
+        }
+        for (file, json) in manifests {
+            std::fs::write(dir.join(file), json).unwrap();
+        }
+        dir
+    }
+
+    // Absolute paths bypass the mounts
+    fn validate(name: &str, json: &str) -> ValidationReport {
+        let dir = manifest_dir(name, &[("manifest.json", json)]);
+        let report = validate_manifest(&Vfs::new(), &dir.join("manifest.json"), &["standard"]).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        report
+    }
+
+    fn messages(report: &ValidationReport, severity: Severity) -> Vec<&str> {
+        report.issues.iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn valid_manifest_has_no_issues() {
+        let report = validate("valid", r#"{
+            "textures": { "stone": "stone.png", "stone_n": "stone_n.png" },
+            "meshes": {},
+            "materials": {
+                "stone": { "pipeline": "standard", "diffuse": "stone", "normal": "stone_n", "roughness": 0.8, "metallic": 0.0 },
+                "white": { "pipeline": "standard", "diffuse": "internal:white", "roughness": 0.5, "metallic": 0.0 }
+            }
+        }"#);
+        assert!(report.issues.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn unknown_references_are_errors() {
+        let report = validate("references", r#"{
+            "textures": { "stone": "stone.png" },
+            "meshes": {},
+            "materials": {
+                "glass": { "pipeline": "transparent", "diffuse": "stone", "roughness": 0.1, "metallic": 0.0 },
+                "moss": { "pipeline": "standard", "diffuse": "moss", "roughness": 0.9, "metallic": 0.0 },
+                "tiles": { "pipeline": "standard", "diffuse": "stone", "sampler": "clamp", "roughness": 0.5, "metallic": 0.0 },
+                "orphan": { "parent": "base", "roughness": 0.5 }
+            }
+        }"#);
+        assert_eq!(report.errors(), 4, "{}", report);
+        assert_eq!(report.warnings(), 0, "{}", report);
+        let errors = messages(&report, Severity::Error);
+        assert!(errors.contains(&"Pipeline 'transparent' for material 'glass' missing."));
+        assert!(errors.contains(&"Diffuse texture 'moss' for material 'moss' missing."));
+        assert!(errors.contains(&"Sampler 'clamp' for material 'tiles' missing."));
+        assert!(errors.contains(&"Parent material 'base' of material 'orphan' missing."));
+    }
+
+    #[test]
+    fn missing_files_are_errors() {
+        let report = validate("missing_file", r#"{
+            "textures": { "stone": "stone.png", "grass": "grass.png" },
+            "meshes": { "rock": "rock.obj" },
+            "materials": {
+                "stone": { "pipeline": "standard", "diffuse": "stone", "roughness": 0.8, "metallic": 0.0 },
+                "grass": { "pipeline": "standard", "diffuse": "grass", "roughness": 0.8, "metallic": 0.0 }
+            }
+        }"#);
+        let errors = messages(&report, Severity::Error);
+        assert_eq!(errors.len(), 2, "{}", report);
+        assert!(errors.iter().any(|error| error.contains("grass.png") && error.contains("'grass'")));
+        assert!(errors.iter().any(|error| error.contains("rock.obj") && error.contains("'rock'")));
+    }
+
+    #[test]
+    fn suspicious_entries_are_warnings() {
+        let report = validate("warnings", r#"{
+            "textures": { "stone": "stone.png", "stone_n": "stone_n.png", "unused": "stone.png" },
+            "meshes": {},
+            "materials": {
+                "swapped": { "pipeline": "standard", "diffuse": "stone_n", "normal": "stone", "roughness": 0.8, "metallic": 0.0 }
+            }
+        }"#);
+        assert_eq!(report.errors(), 0, "{}", report);
+        let warnings = messages(&report, Severity::Warning);
+        assert_eq!(warnings, [
+            "Material 'swapped' uses Color texture 'stone' in its Normal slot, which expects a Normal texture.",
+            "Material 'swapped' uses Normal texture 'stone_n' in its Diffuse slot, which expects a Color texture.",
+            "Texture 'unused' is not used by any material.",
+        ]);
+    }
+
+    #[test]
+    fn errors_are_sorted_before_warnings() {
+        let report = validate("sorted", r#"{
+            "textures": { "stone": "stone.png", "unused": "stone.png" },
+            "meshes": {},
+            "materials": { "moss": { "pipeline": "standard", "diffuse": "moss", "roughness": 0.9, "metallic": 0.0 } }
+        }"#);
+        let severities: Vec<Severity> = report.issues.iter().map(|issue| issue.severity).collect();
+        assert_eq!(severities, [Severity::Error, Severity::Warning, Severity::Warning]);
+    }
+
+    #[test]
+    fn duplicate_names_and_broken_json_fail_the_manifest() {
+        let dir = manifest_dir("duplicates", &[
+            ("manifest.json", r#"{ "include": ["core.json"], "textures": { "stone": "stone.png" }, "meshes": {}, "materials": {} }"#),
+            ("core.json", r#"{ "textures": { "stone": "stone_n.png" }, "meshes": {}, "materials": {} }"#),
+            ("broken.json", r#"{ "textures": { "stone": "stone.png" }, "meshes": {} "#),
+        ]);
+        let vfs = Vfs::new();
+        let conflict = validate_manifest(&vfs, &dir.join("manifest.json"), &["standard"]);
+        assert!(matches!(conflict, Err(AssetError::ManifestConflict { kind: "Texture", ref name, .. }) if name == "stone"));
+        let broken = validate_manifest(&vfs, &dir.join("broken.json"), &["standard"]);
+        assert!(matches!(broken, Err(AssetError::ManifestParse { .. })));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,6 +12,12 @@ pub struct FlyCameraBundle {
     pub marker: PrimaryCamera,
 }
 
+impl Default for FlyCameraBundle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FlyCameraBundle {
     pub fn new() -> Self {
         Self {
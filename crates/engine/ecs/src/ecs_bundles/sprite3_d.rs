@@ -1,13 +1,20 @@
-use engine_assets::AssetManager;
+use engine_assets::{AssetManager, AssetError};
 use crate::Transform;
 use bevy_ecs::prelude::*;
 use crate::ecs_components::assets::*;
+use crate::ecs_components::bounds::*;
+use crate::ecs_components::skeleton::MeshSkeleton;
+use engine_assets::data_structures::{Handle, MaterialData, MeshBuffers};
 
 #[derive(Bundle)]
 pub struct Sprite3DBundle {
     pub transform: Transform,
     pub mesh: MeshHandle,
     pub material: MaterialHandle,
+    pub local_bounds: LocalBounds,
+    pub world_bounds: WorldBounds,
+    // Becomes a Skeleton in the rest pose when the mesh has a skin
+    pub skeleton: MeshSkeleton,
 }
 
 impl Sprite3DBundle {
@@ -16,15 +23,55 @@ impl Sprite3DBundle {
         material_name: &str, 
         position: glam::Vec3, 
         asset_manager: &AssetManager
-    ) -> Self {
-        Self {
-            transform: Transform {
+    ) -> Result<Self, AssetError> {
+        Self::with_handles(
+            position,
+            asset_manager.get_mesh_handle(mesh_name)?,
+            asset_manager.get_material_handle(material_name)?,
+            asset_manager,
+        )
+    }
+
+    // One bundle per part of a multi-part model, all sharing the same position.
+    // Spawn them with `world.spawn_batch`.
+    pub fn from_model(
+        model_name: &str,
+        position: glam::Vec3,
+        asset_manager: &AssetManager
+    ) -> Result<Vec<Self>, AssetError> {
+        let parts = asset_manager.get_model(model_name)?;
+        parts.iter()
+            .map(|part| Self::with_handles(
                 position,
-                rotation: glam::Quat::IDENTITY,
-                scale: glam::Vec3::ONE,
-            },
-            mesh: MeshHandle(asset_manager.get_mesh_id(mesh_name)),
-            material: MaterialHandle(asset_manager.get_material_id(material_name)),
-        }
+                asset_manager.mesh_handle(part.mesh)?,
+                asset_manager.material_handle(part.material)?,
+                asset_manager,
+            ))
+            .collect()
+    }
+
+    fn with_handles(
+        position: glam::Vec3,
+        mesh: Handle<MeshBuffers>,
+        material: Handle<MaterialData>,
+        asset_manager: &AssetManager
+    ) -> Result<Self, AssetError> {
+        let transform = Transform {
+            position,
+            rotation: glam::Quat::IDENTITY,
+            scale: glam::Vec3::ONE,
+        };
+        let bounds = asset_manager.get_mesh_bounds(mesh.id())
+            .ok_or(AssetError::StaleHandle { kind: "Mesh" })?;
+        let mesh = MeshHandle(mesh);
+        let skeleton = MeshSkeleton::for_mesh(&mesh, asset_manager);
+        Ok(Self {
+            transform,
+            mesh,
+            material: MaterialHandle(material),
+            local_bounds: LocalBounds(bounds),
+            world_bounds: WorldBounds(bounds.transformed(&transform.to_matrix())),
+            skeleton,
+        })
     }
 }
@@ -1,11 +1,12 @@
-use engine_assets::data_structures::{MaterialId, MeshId};
+use engine_assets::data_structures::{Handle, MaterialData, MeshBuffers};
 use bevy_ecs::prelude::*;
 
-#[derive(Component, Debug, Clone, Copy)]
-pub struct MeshHandle(pub MeshId);
+// Despawning the last entity that holds a handle lets `AssetManager::unload_unused` free it
+#[derive(Component, Debug, Clone)]
+pub struct MeshHandle(pub Handle<MeshBuffers>);
 
-#[derive(Component, Debug, Clone, Copy)]
-pub struct MaterialHandle(pub MaterialId);
+#[derive(Component, Debug, Clone)]
+pub struct MaterialHandle(pub Handle<MaterialData>);
 
 pub struct Invisible;
 
@@ -0,0 +1,10 @@
+use engine_assets::bounds::MeshBounds;
+use bevy_ecs::prelude::*;
+
+// Mesh bounds in model space, copied from the asset manager when the entity is spawned
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LocalBounds(pub MeshBounds);
+
+// LocalBounds moved by the entity's Transform, kept up to date by `world_bounds_system`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WorldBounds(pub MeshBounds);
@@ -0,0 +1,117 @@
+use bevy_ecs::prelude::*;
+use engine_assets::AssetManager;
+use engine_assets::asset_manager::LodThreshold;
+use engine_assets::bounds::MeshBounds;
+use engine_assets::data_structures::{Handle, MeshBuffers};
+use crate::ecs_components::assets::MeshHandle;
+
+// Share of a threshold the distance has to pass it by before the level changes, so
+// entities right at a threshold don't pop back and forth
+pub const DEFAULT_LOD_HYSTERESIS: f32 = 0.1;
+
+// One mesh of an LOD chain with its model space bounds
+#[derive(Debug, Clone)]
+pub struct LodLevel {
+    pub mesh: Handle<MeshBuffers>,
+    pub bounds: MeshBounds,
+}
+
+// LOD chain of an entity's mesh. `lod_system` puts the level that fits the distance to the
+// PrimaryCamera into the entity's MeshHandle and LocalBounds, level 0 is the full mesh.
+// Holds every level, so none of them is unloaded while the entity exists.
+#[derive(Component, Debug, Clone)]
+pub struct MeshLods {
+    pub levels: Vec<LodLevel>,
+    // Where levels 1.. take over, one fewer than there are levels
+    pub thresholds: Vec<LodThreshold>,
+    pub current: usize,
+    pub hysteresis: f32,
+}
+
+impl MeshLods {
+    // None for meshes without an LOD chain
+    pub fn for_mesh(mesh: &MeshHandle, asset_manager: &AssetManager) -> Option<Self> {
+        let lods = asset_manager.get_mesh_lods(mesh.0.id());
+        if lods.is_empty() {
+            return None;
+        }
+        let level = |mesh: Handle<MeshBuffers>| {
+            let bounds = asset_manager.get_mesh_bounds(mesh.id())?;
+            Some(LodLevel { mesh, bounds })
+        };
+        let levels = std::iter::once(Some(mesh.0.clone()))
+            .chain(lods.iter().map(|lod| asset_manager.mesh_handle(lod.mesh).ok()))
+            .map(|mesh| level(mesh?))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            levels,
+            thresholds: lods.iter().map(|lod| lod.threshold).collect(),
+            current: 0,
+            hysteresis: DEFAULT_LOD_HYSTERESIS,
+        })
+    }
+
+    // Level for a bounding sphere `distance` away from the camera. `screen_distance` is the
+    // distance at which the sphere fills the screen height, screen size thresholds are
+    // converted with it.
+    pub fn select(&self, distance: f32, screen_distance: f32) -> usize {
+        select_level(&self.thresholds, self.current, self.hysteresis, distance, screen_distance)
+    }
+}
+
+fn select_level(thresholds: &[LodThreshold], current: usize, hysteresis: f32, distance: f32, screen_distance: f32) -> usize {
+    // Distance at which `level` takes over from the one before it
+    let switch_distance = |level: usize| match thresholds[level - 1] {
+        LodThreshold::Distance(threshold) => threshold,
+        LodThreshold::ScreenSize(size) => screen_distance / size,
+    };
+    let last = thresholds.len();
+    let mut level = current.min(last);
+    while level < last && distance > switch_distance(level + 1) * (1.0 + hysteresis) {
+        level += 1;
+    }
+    while level > 0 && distance < switch_distance(level) * (1.0 - hysteresis) {
+        level -= 1;
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISTANCES: [LodThreshold; 2] = [LodThreshold::Distance(10.0), LodThreshold::Distance(20.0)];
+
+    #[test]
+    fn picks_the_level_for_the_distance() {
+        assert_eq!(select_level(&DISTANCES, 0, 0.1, 5.0, 1.0), 0);
+        assert_eq!(select_level(&DISTANCES, 0, 0.1, 15.0, 1.0), 1);
+        assert_eq!(select_level(&DISTANCES, 0, 0.1, 100.0, 1.0), 2);
+        assert_eq!(select_level(&DISTANCES, 2, 0.1, 1.0, 1.0), 0);
+        assert_eq!(select_level(&[], 0, 0.1, 100.0, 1.0), 0);
+    }
+
+    #[test]
+    fn hysteresis_keeps_the_level_near_a_threshold() {
+        // Within 10% of the threshold at 10 the current level stays
+        assert_eq!(select_level(&DISTANCES, 0, 0.1, 10.5, 1.0), 0);
+        assert_eq!(select_level(&DISTANCES, 1, 0.1, 9.5, 1.0), 1);
+        // Past the band it switches
+        assert_eq!(select_level(&DISTANCES, 0, 0.1, 11.5, 1.0), 1);
+        assert_eq!(select_level(&DISTANCES, 1, 0.1, 8.5, 1.0), 0);
+        // Without hysteresis the threshold alone decides
+        assert_eq!(select_level(&DISTANCES, 0, 0.0, 10.5, 1.0), 1);
+    }
+
+    #[test]
+    fn screen_size_thresholds_scale_with_the_screen_distance() {
+        // A level for spheres below a quarter of the screen height, the sphere fills the
+        // screen at 8 units, so it covers a quarter at 32
+        let thresholds = [LodThreshold::ScreenSize(0.25)];
+        assert_eq!(select_level(&thresholds, 0, 0.0, 30.0, 8.0), 0);
+        assert_eq!(select_level(&thresholds, 0, 0.0, 34.0, 8.0), 1);
+        // A sphere twice the size switches twice as far away
+        assert_eq!(select_level(&thresholds, 0, 0.0, 34.0, 16.0), 0);
+        assert_eq!(select_level(&thresholds, 0, 0.0, 66.0, 16.0), 1);
+    }
+}
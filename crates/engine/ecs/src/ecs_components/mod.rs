@@ -3,12 +3,18 @@ pub mod collider;
 pub mod info;
 pub mod transform;
 pub mod assets;
+pub mod bounds;
 pub mod lights;
+pub mod skeleton;
+pub mod lod;
 
 pub use camera::*;
 pub use collider::*;
 pub use info::*;
 pub use transform::*;
 pub use assets::*;
+pub use bounds::*;
 pub use lights::*;
+pub use skeleton::*;
+pub use lod::*;
 
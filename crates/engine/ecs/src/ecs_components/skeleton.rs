@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use bevy_ecs::lifecycle::HookContext;
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::DeferredWorld;
+use engine_assets::AssetManager;
+use engine_assets::skin::{JointTransform, Skin};
+use crate::ecs_components::assets::MeshHandle;
+
+// Pose of a skinned entity. Animation and gameplay code writes `pose`, `skeleton_system`
+// turns it into the joint matrices the renderer uploads. Entities drawn with a skinned
+// material need one, Sprite3DBundle adds it for meshes with a skin.
+#[derive(Component, Debug, Clone)]
+pub struct Skeleton {
+    pub skin: Arc<Skin>,
+    // Local transform of every joint, indexed like `skin.joints`
+    pub pose: Vec<JointTransform>,
+    pub joint_matrices: Vec<glam::Mat4>,
+}
+
+impl Skeleton {
+    // Starts in the rest pose
+    pub fn new(skin: Arc<Skin>) -> Self {
+        let pose = skin.rest_pose();
+        let mut joint_matrices = Vec::new();
+        skin.joint_matrices(&pose, &mut joint_matrices);
+        Self { skin, pose, joint_matrices }
+    }
+
+    // None for meshes without a skin
+    pub fn for_mesh(mesh: &MeshHandle, asset_manager: &AssetManager) -> Option<Self> {
+        let skin = asset_manager.get_mesh(mesh.0.id())?.skin.as_ref()?;
+        Some(Self::new(Arc::clone(&skin.skin)))
+    }
+
+    pub fn joint_mut(&mut self, name: &str) -> Option<&mut JointTransform> {
+        let index = self.skin.joint_index(name)?;
+        self.pose.get_mut(index)
+    }
+
+    pub fn reset_pose(&mut self) {
+        self.pose = self.skin.rest_pose();
+    }
+}
+
+// Skeleton for the mesh of a bundle. Bundles can't leave a component out, so they carry this
+// one, which turns into the entity's Skeleton when it is inserted. A Skeleton the entity
+// already has is kept.
+#[derive(Component, Debug, Clone, Default)]
+#[component(on_insert = attach_skeleton)]
+pub struct MeshSkeleton(pub Option<Skeleton>);
+
+impl MeshSkeleton {
+    pub fn for_mesh(mesh: &MeshHandle, asset_manager: &AssetManager) -> Self {
+        Self(Skeleton::for_mesh(mesh, asset_manager))
+    }
+}
+
+fn attach_skeleton(mut world: DeferredWorld, context: HookContext) {
+    let skeleton = world.get_mut::<MeshSkeleton>(context.entity).and_then(|mut pending| pending.0.take());
+    let mut commands = world.commands();
+    let mut entity = commands.entity(context.entity);
+    entity.remove::<MeshSkeleton>();
+    if let Some(skeleton) = skeleton {
+        entity.insert_if_new(skeleton);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use engine_assets::skin::Joint;
+
+    fn skeleton() -> Skeleton {
+        let joint = Joint {
+            name: "root".to_string(),
+            parent: None,
+            rest: JointTransform::from_matrix(glam::Mat4::from_translation(glam::Vec3::Y)),
+            inverse_bind: glam::Mat4::IDENTITY,
+        };
+        Skeleton::new(Arc::new(Skin::new(vec![joint], glam::Mat4::IDENTITY).unwrap()))
+    }
+
+    #[test]
+    fn mesh_skeletons_become_skeletons() {
+        let mut world = World::new();
+        let skinned = world.spawn(MeshSkeleton(Some(skeleton()))).id();
+        let rigid = world.spawn(MeshSkeleton(None)).id();
+
+        assert!(world.get::<MeshSkeleton>(skinned).is_none());
+        assert_eq!(world.get::<Skeleton>(skinned).unwrap().joint_matrices.len(), 1);
+        assert!(world.get::<MeshSkeleton>(rigid).is_none());
+        assert!(world.get::<Skeleton>(rigid).is_none());
+    }
+
+    #[test]
+    fn existing_skeletons_are_kept() {
+        let mut world = World::new();
+        let mut posed = skeleton();
+        posed.joint_mut("root").unwrap().translation = glam::Vec3::X;
+        let entity = world.spawn(posed).id();
+        world.entity_mut(entity).insert(MeshSkeleton(Some(skeleton())));
+        world.flush();
+
+        assert_eq!(world.get::<Skeleton>(entity).unwrap().pose[0].translation, glam::Vec3::X);
+        assert!(world.get::<MeshSkeleton>(entity).is_none());
+    }
+}
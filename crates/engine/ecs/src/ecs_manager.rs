@@ -1,8 +1,9 @@
 use bevy_ecs::prelude::*;
 use crate::ecs_components::{CameraSettings};
-use crate::ecs_resources::{ActionState, RawInputState, InputBindings, GameState, GameStateConfig, FrameContext};
-use crate::ecs_systems::{input_mapping_system, camera_matrix_system, sync_camera_uniform_system, sync_lights_uniform_system, input_clean_up_system};
+use crate::ecs_resources::{ActionState, RawInputState, InputBindings, InputBindingsError, GameState, GameStateConfig, FrameContext};
+use crate::ecs_systems::{input_mapping_system, camera_matrix_system, sync_camera_uniform_system, sync_lights_uniform_system, input_clean_up_system, world_bounds_system, skeleton_system, lod_system};
 use engine_gpu_types::{CameraUniform, GlobalLightDataUniform};
+use engine_vfs::Vfs;
 use winit::event::{WindowEvent, ElementState};
 use winit::keyboard::{PhysicalKey};
 
@@ -24,6 +25,12 @@ pub enum EngineSet {
     Cleanup,
 }
 
+impl Default for ECSManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ECSManager {
     pub fn new() -> Self {
         let mut world = World::new();
@@ -47,6 +54,9 @@ impl ECSManager {
         schedule.add_systems((
             input_mapping_system.in_set(EngineSet::Input),
             camera_matrix_system.in_set(EngineSet::Sync),
+            world_bounds_system.in_set(EngineSet::Sync),
+            skeleton_system.in_set(EngineSet::Sync),
+            lod_system.in_set(EngineSet::Sync).after(world_bounds_system),
             sync_camera_uniform_system.in_set(EngineSet::Sync),
             sync_lights_uniform_system.in_set(EngineSet::Sync),
             input_clean_up_system.in_set(EngineSet::Cleanup),
@@ -55,9 +65,9 @@ impl ECSManager {
         Self { world, schedule }
     }
 
-    pub fn load_input_bindings(&mut self, path: &str){
+    pub fn load_input_bindings(&mut self, vfs: &Vfs, path: &str) -> Result<(), InputBindingsError> {
         let mut input_bindings = self.world.resource_mut::<InputBindings>();
-        input_bindings.load_from_file(path);
+        input_bindings.load_from_file(vfs, path)
     }
 
     pub fn set_game_state_config(&mut self, config: &[GameStateConfig], initial_state: &str) {
@@ -1,24 +1,61 @@
 use bevy_ecs::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::{fmt, io};
 use winit::keyboard::KeyCode;
 use serde::Deserialize;
 use glam::Vec2;
+use engine_vfs::Vfs;
 
 #[derive(Deserialize, Debug)]
 struct JsonConfig(HashMap<String, HashMap<String, String>>);
 
+#[derive(Debug)]
+pub enum InputBindingsError {
+    Read {
+        path: String,
+        source: io::Error,
+    },
+    Parse {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for InputBindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBindingsError::Read { path, source } => {
+                write!(f, "Input bindings '{}' could not be read: {}", path, source)
+            }
+            InputBindingsError::Parse { path, source } => {
+                write!(f, "Input bindings '{}' are invalid: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputBindingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputBindingsError::Read { source, .. } => Some(source),
+            InputBindingsError::Parse { .. } => None,
+        }
+    }
+}
+
 #[derive(Resource, Debug)]
 pub struct InputBindings {
     pub bindings: HashMap<String, HashMap<String, KeyCode>>,
 }
 
 impl InputBindings {
-    pub fn load_from_file(&mut self, path: &str) {
-        let file_content = std::fs::read_to_string(path)
-            .unwrap_or_else(|_| panic!("Could not find path {}", path));
+    // Mods can shadow the file, so a broken one is an error and the old bindings stay
+    pub fn load_from_file(&mut self, vfs: &Vfs, path: &str) -> Result<(), InputBindingsError> {
+        let file_content = vfs.read_to_string(path)
+            .map_err(|source| InputBindingsError::Read { path: path.to_string(), source })?;
 
         let config: JsonConfig = serde_json::from_str(&file_content)
-            .expect("Error parsing JSON");
+            .map_err(|source| InputBindingsError::Parse { path: path.to_string(), source })?;
 
         self.bindings.clear();
 
@@ -33,6 +70,7 @@ impl InputBindings {
             }
             self.bindings.insert(context, context_map);
         }
+        Ok(())
     }
 
     fn string_to_keycode(&self, key_str: &str) -> Option<KeyCode> {
@@ -2,7 +2,7 @@ pub mod input;
 pub mod game_state;
 pub mod frame_context;
 
-pub use input::{ActionState, RawInputState, InputBindings};
+pub use input::{ActionState, RawInputState, InputBindings, InputBindingsError};
 pub use game_state::{GameState, GameStateConfig};
 pub use frame_context::FrameContext;
 
@@ -0,0 +1,27 @@
+use bevy_ecs::prelude::*;
+use crate::ecs_components::{CameraSettings, LocalBounds, LodLevel, MeshHandle, MeshLods, PrimaryCamera, Transform, WorldBounds};
+
+pub fn lod_system(
+    camera: Query<(&Transform, &CameraSettings), With<PrimaryCamera>>,
+    mut query: Query<(&mut MeshLods, &mut MeshHandle, &mut LocalBounds, &mut WorldBounds, &Transform)>,
+) {
+    puffin::profile_function!();
+    let Ok((camera, settings)) = camera.single() else { return };
+    // A sphere of radius 1 fills the screen height at this distance
+    let unit_screen_distance = 1.0 / (settings.fovy.to_radians() * 0.5).tan();
+
+    for (mut lods, mut mesh, mut local_bounds, mut world_bounds, transform) in &mut query {
+        let sphere = world_bounds.0.sphere;
+        let distance = camera.position.distance(sphere.center);
+        let level = lods.select(distance, sphere.radius * unit_screen_distance);
+        // Only on a switch, so the handle isn't flagged as changed every frame. The bounds
+        // follow the mesh, world_bounds_system already ran this frame.
+        if level != lods.current {
+            lods.current = level;
+            let LodLevel { mesh: handle, bounds } = lods.levels[level].clone();
+            mesh.0 = handle;
+            local_bounds.0 = bounds;
+            world_bounds.0 = bounds.transformed(&transform.to_matrix());
+        }
+    }
+}
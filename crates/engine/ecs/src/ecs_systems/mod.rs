@@ -4,6 +4,9 @@ pub mod fly_camera_controller_system;
 pub mod sync_camera_uniform_system;
 pub mod sync_lights_uniform_system;
 pub mod input_clean_up_system;
+pub mod world_bounds_system;
+pub mod skeleton_system;
+pub mod lod_system;
 
 pub use camera_matrix_system::camera_matrix_system;
 pub use input_mapping_system::input_mapping_system;
@@ -11,3 +14,6 @@ pub use fly_camera_controller_system::fly_camera_controller_system;
 pub use sync_camera_uniform_system::sync_camera_uniform_system;
 pub use sync_lights_uniform_system::sync_lights_uniform_system;
 pub use input_clean_up_system::input_clean_up_system;
+pub use world_bounds_system::world_bounds_system;
+pub use skeleton_system::skeleton_system;
+pub use lod_system::lod_system;
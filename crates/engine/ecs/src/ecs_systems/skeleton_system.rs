@@ -0,0 +1,10 @@
+use bevy_ecs::prelude::*;
+use crate::ecs_components::Skeleton;
+
+pub fn skeleton_system(mut query: Query<&mut Skeleton, Changed<Skeleton>>) {
+    puffin::profile_function!();
+    for mut skeleton in &mut query {
+        let skeleton = &mut *skeleton;
+        skeleton.skin.joint_matrices(&skeleton.pose, &mut skeleton.joint_matrices);
+    }
+}
@@ -0,0 +1,13 @@
+use bevy_ecs::prelude::*;
+use crate::ecs_components::{LocalBounds, Transform, WorldBounds};
+
+type BoundsChanged = Or<(Changed<Transform>, Changed<LocalBounds>)>;
+
+pub fn world_bounds_system(
+    mut query: Query<(&Transform, &LocalBounds, &mut WorldBounds), BoundsChanged>
+) {
+    puffin::profile_function!();
+    for (transform, local, mut world) in &mut query {
+        world.0 = local.0.transformed(&transform.to_matrix());
+    }
+}
@@ -11,6 +11,9 @@ pub use ecs_components::collider::*;
 pub use ecs_components::info::*;
 pub use ecs_components::transform::*;
 pub use ecs_components::assets::*;
+pub use ecs_components::bounds::*;
+pub use ecs_components::skeleton::*;
+pub use ecs_components::lod::*;
 
 pub use ecs_bundles::fly_camera::FlyCameraBundle;
 pub use ecs_bundles::sprite3_d::Sprite3DBundle;
@@ -25,5 +28,8 @@ pub use ecs_systems::fly_camera_controller_system::*;
 pub use ecs_systems::sync_camera_uniform_system::*;
 pub use ecs_systems::sync_lights_uniform_system::*;
 pub use ecs_systems::input_clean_up_system::*;
+pub use ecs_systems::world_bounds_system::*;
+pub use ecs_systems::skeleton_system::*;
+pub use ecs_systems::lod_system::*;
 
 
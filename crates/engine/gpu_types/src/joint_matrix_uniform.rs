@@ -0,0 +1,30 @@
+use bytemuck::{Pod, Zeroable};
+use crate::BindGroupLayout;
+
+// Final matrix of one joint, its global transform times its inverse bind matrix
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct JointMatrixUniform {
+    pub joint: glam::Mat4,
+}
+
+// Group 3 of the skinned pipeline: the shared model matrices like in the standard
+// pipeline, plus the joint matrices of the entity that is drawn
+impl BindGroupLayout for JointMatrixUniform {
+    fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("skinned_model_storage_layout"),
+            entries: &[storage_entry(0), storage_entry(1)],
+        })
+    }
+}
@@ -10,9 +10,18 @@ pub use lights_uniform::{GlobalLightDataUniform, LightInstanceUniform};
 pub mod model_matrix_uniform;
 pub use model_matrix_uniform::ModelMatrixUniform;
 
+pub mod joint_matrix_uniform;
+pub use joint_matrix_uniform::JointMatrixUniform;
+
 pub mod vertex_ptn;
 pub use vertex_ptn::VertexPTN;
 
+pub mod vertex_ptnt;
+pub use vertex_ptnt::VertexPTNT;
+
+pub mod vertex_jw;
+pub use vertex_jw::VertexJW;
+
 pub mod traits;
 pub use traits::{BindGroupLayout, BufferLayout};
 
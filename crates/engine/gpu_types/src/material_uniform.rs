@@ -5,7 +5,8 @@ use crate::BindGroupLayout;
 pub struct MaterialUniform {
     pub roughness: f32,
     pub metallic: f32,
-    pub _padding: [f32; 2],
+    pub lod_bias: f32,
+    pub _padding: f32,
 }
 
 impl BindGroupLayout for MaterialUniform {
@@ -29,7 +30,7 @@ impl BindGroupLayout for MaterialUniform {
                     ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                     count: None,
                 },
-                // Binding 2: Material Uniforms (Roughness, Metallic)
+                // Binding 2: Material Uniforms (Roughness, Metallic, LOD Bias)
                 wgpu::BindGroupLayoutEntry {
                     binding: 2,
                     visibility: wgpu::ShaderStages::FRAGMENT,
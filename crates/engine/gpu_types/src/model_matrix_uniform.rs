@@ -1,4 +1,3 @@
-use bevy_ecs::prelude::Resource;
 use bytemuck::{Pod, Zeroable};
 use crate::BindGroupLayout;
 
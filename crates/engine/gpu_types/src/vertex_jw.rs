@@ -0,0 +1,32 @@
+use std::mem;
+use crate::BufferLayout;
+
+// Joint indices and weights of a skinned vertex. A second vertex stream next to the
+// VertexPTNT one, so rigid meshes don't carry it. Weights add up to 1.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct VertexJW {
+    pub joints: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+impl BufferLayout for VertexJW {
+    fn buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<VertexJW>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Uint16x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[u16; 4]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
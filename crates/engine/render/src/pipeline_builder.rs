@@ -1,4 +1,4 @@
-use engine_gpu_types::{MaterialUniform, VertexPTN, CameraUniform, GlobalLightDataUniform, ModelMatrixUniform, BufferLayout, BindGroupLayout};
+use engine_gpu_types::{MaterialUniform, VertexJW, VertexPTNT, CameraUniform, GlobalLightDataUniform, ModelMatrixUniform, JointMatrixUniform, BufferLayout, BindGroupLayout};
 
 pub struct PipelineBuilder;
 
@@ -8,31 +8,75 @@ impl PipelineBuilder {
         surface_config: &wgpu::SurfaceConfiguration,
     ) -> wgpu::RenderPipeline {
         let shader = device.create_shader_module(wgpu::include_wgsl!("shaders/standard.wgsl"));
+        Self::build_pipeline(
+            device,
+            surface_config,
+            "Standard",
+            &shader,
+            "vs_main",
+            &[VertexPTNT::buffer_layout()],
+            &ModelMatrixUniform::bind_group_layout(device),
+        )
+    }
+
+    // Same shading as the standard pipeline, the vertex stage blends up to four joint
+    // matrices per vertex. Meshes need their joint weight buffer in slot 1, entities their
+    // joint matrices next to the model matrices in group 3.
+    pub fn build_skinned_pipeline(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+    ) -> wgpu::RenderPipeline {
+        // WGSL has no includes, the skinned vertex stage is appended to the standard shader
+        let source = format!("{}{}", include_str!("shaders/standard.wgsl"), include_str!("shaders/skinning.wgsl"));
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("skinned.wgsl"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        Self::build_pipeline(
+            device,
+            surface_config,
+            "Skinned",
+            &shader,
+            "vs_skinned",
+            &[VertexPTNT::buffer_layout(), VertexJW::buffer_layout()],
+            &JointMatrixUniform::bind_group_layout(device),
+        )
+    }
+
+    fn build_pipeline(
+        device: &wgpu::Device,
+        surface_config: &wgpu::SurfaceConfiguration,
+        label: &str,
+        shader: &wgpu::ShaderModule,
+        vertex_entry: &str,
+        vertex_buffers: &[wgpu::VertexBufferLayout],
+        model_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Standard Render Pipeline Layout"),
+            label: Some(&format!("{} Render Pipeline Layout", label)),
             bind_group_layouts: &[
                 &CameraUniform::bind_group_layout(device),
                 &GlobalLightDataUniform::bind_group_layout(device),
                 &MaterialUniform::bind_group_layout(device),
-                &ModelMatrixUniform::bind_group_layout(device),
+                model_layout,
             ],
             push_constant_ranges: &[],
         });
 
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Standard Render Pipeline"),
+            label: Some(&format!("{} Render Pipeline", label)),
             layout: Some(&render_pipeline_layout),
             
             vertex: wgpu::VertexState {
                 compilation_options: Default::default(),
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[VertexPTN::buffer_layout()],
+                module: shader,
+                entry_point: Some(vertex_entry),
+                buffers: vertex_buffers,
             },
 
             fragment: Some(wgpu::FragmentState {
                 compilation_options: Default::default(),
-                module: &shader,
+                module: shader,
                 entry_point: Some("fs_main"),
                 targets: &[Some(wgpu::ColorTargetState {
                     format: surface_config.format,
@@ -1,7 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use bevy_ecs::prelude::*;
 use engine_assets::AssetManager;
-use engine_ecs::{MeshHandle, MaterialHandle, Transform};
-use engine_gpu_types::{CameraUniform, GlobalLightDataUniform, ModelMatrixUniform, BindGroupLayout};
+use engine_ecs::{MeshHandle, MaterialHandle, Skeleton, Transform};
+use engine_gpu_types::{CameraUniform, GlobalLightDataUniform, ModelMatrixUniform, JointMatrixUniform, BindGroupLayout};
+
+// Joint matrices of one skinned entity, bound together with the shared model matrices
+struct JointBuffer {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    capacity: usize,
+}
 
 pub struct Renderer{
     camera_buffer: wgpu::Buffer,
@@ -10,11 +18,14 @@ pub struct Renderer{
 
     light_buffer: wgpu::Buffer,
     light_bind_group: wgpu::BindGroup,
-    light_bind_group_layout: wgpu::BindGroupLayout,
 
     model_buffer: wgpu::Buffer,
     model_bind_group: wgpu::BindGroup,
-    model_bind_group_layout: wgpu::BindGroupLayout,
+
+    joint_bind_group_layout: wgpu::BindGroupLayout,
+    joint_buffers: HashMap<Entity, JointBuffer>,
+    // Entities with a skinned material that were drawn unskinned, each is logged once
+    unskinned: HashSet<Entity>,
 }
 
 impl Renderer {
@@ -35,16 +46,18 @@ impl Renderer {
 
             light_buffer,
             light_bind_group,
-            light_bind_group_layout,
 
             model_buffer,
             model_bind_group,
-            model_bind_group_layout,
+
+            joint_bind_group_layout: JointMatrixUniform::bind_group_layout(device),
+            joint_buffers: HashMap::new(),
+            unskinned: HashSet::new(),
         }
 
     }
 
-    pub fn update_global_uniforms(&self, queue: &wgpu::Queue, world: &mut World) {
+    pub fn update_global_uniforms(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, world: &mut World) {
         if let Some(camera_data) = world.get_resource::<CameraUniform>() {
             queue.write_buffer(&self.camera_buffer, 0, bytemuck::bytes_of(camera_data));
         }
@@ -68,21 +81,93 @@ impl Renderer {
                 bytemuck::cast_slice(&model_data) 
             );
         }
+
+        self.update_joint_buffers(device, queue, world);
+    }
+
+    // One buffer per entity with a Skeleton, grown when its skin has more joints
+    fn update_joint_buffers(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, world: &mut World) {
+        self.joint_buffers.retain(|&entity, _| world.get::<Skeleton>(entity).is_some());
+        self.unskinned.retain(|&entity| world.get_entity(entity).is_ok());
+
+        let mut query = world.query::<(Entity, &Skeleton)>();
+        for (entity, skeleton) in query.iter(world) {
+            let matrices = &skeleton.joint_matrices;
+            if matrices.is_empty() {
+                continue;
+            }
+            if self.joint_buffers.get(&entity).is_none_or(|joints| joints.capacity < matrices.len()) {
+                let joints = self.create_joint_buffer(device, matrices.len());
+                self.joint_buffers.insert(entity, joints);
+            }
+            queue.write_buffer(&self.joint_buffers[&entity].buffer, 0, bytemuck::cast_slice(matrices));
+        }
+    }
+
+    fn create_joint_buffer(&self, device: &wgpu::Device, capacity: usize) -> JointBuffer {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Joint Matrices Storage Buffer"),
+            size: (std::mem::size_of::<JointMatrixUniform>() * capacity) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.joint_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.model_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("Joint Matrices Bind Group"),
+        });
+
+        JointBuffer { buffer, bind_group, capacity }
     }
 
     pub fn draw_world<'a>(
-        &self,
+        &mut self,
         render_pass: &mut wgpu::RenderPass<'a>,
         world: &mut World,
         asset_manager: &'a AssetManager,
     ) {
-        let mut query = world.query::<(&Transform, &MeshHandle, &MaterialHandle)>();
-        render_pass.set_bind_group(3, &self.model_bind_group, &[]);
-
-        for (i, (_transform, mesh_handle, mat_handle)) in query.iter(world).enumerate() {
-            let mesh = asset_manager.get_mesh(mesh_handle.0);
-            let material = asset_manager.get_material(mat_handle.0);
-            let pipeline = asset_manager.pipeline_cache.get(&material.pipeline_name)
+        // Matches the query in update_global_uniforms, Entity doesn't change the order
+        let mut query = world.query::<(Entity, &Transform, &MeshHandle, &MaterialHandle)>();
+
+        for (i, (entity, _transform, mesh_handle, mat_handle)) in query.iter(world).enumerate() {
+            // Handles keep their assets alive, a miss means the manager was cleared
+            let (Some(mesh), Some(material)) = (
+                asset_manager.get_mesh(mesh_handle.0.id()),
+                asset_manager.get_material(mat_handle.0.id()),
+            ) else {
+                continue;
+            };
+            // Skinned pipelines take the joint weights as a second vertex stream and the
+            // entity's joint matrices in group 3. Without a skin or a skeleton the mesh is
+            // drawn unposed with the standard pipeline.
+            let mut pipeline_name = material.pipeline_name.as_str();
+            match (pipeline_name, &mesh.skin, self.joint_buffers.get(&entity)) {
+                ("skinned", Some(skin), Some(joints)) => {
+                    render_pass.set_vertex_buffer(1, skin.weights_buffer.slice(..));
+                    render_pass.set_bind_group(3, &joints.bind_group, &[]);
+                }
+                (name, skin, _) => {
+                    if name == "skinned" {
+                        if self.unskinned.insert(entity) {
+                            let missing = if skin.is_none() { "its mesh has no skin" } else { "it has no Skeleton" };
+                            log::warn!("Entity {} uses a skinned material but {}, it is drawn unposed.", entity, missing);
+                        }
+                        pipeline_name = "standard";
+                    }
+                    render_pass.set_bind_group(3, &self.model_bind_group, &[]);
+                }
+            }
+            let pipeline = asset_manager.pipeline_cache.get(pipeline_name)
                 .expect("Pipeline not found in cache");
 
             render_pass.set_pipeline(pipeline);
@@ -90,7 +175,7 @@ impl Renderer {
             render_pass.set_bind_group(1, &self.light_bind_group, &[]);
             render_pass.set_bind_group(2, &material.bind_group, &[]);
             render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.set_index_buffer(mesh.index_buffer.slice(..), mesh.index_format);
             let instance_index = i as u32;
             render_pass.draw_indexed(0..mesh.num_indices, 0, instance_index..(instance_index + 1));
         }
@@ -0,0 +1,42 @@
+
+// --- Skinning: wird an standard.wgsl angehängt und nutzt dessen Fragment-Shader ---
+
+// Group 3, Binding 1: Joint-Matrizen der Entity, die gerade gezeichnet wird
+@group(3) @binding(1)
+var<storage, read> joint_matrices: array<mat4x4<f32>>;
+
+struct SkinnedVertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) tangent: vec4<f32>,
+    @location(4) joints: vec4<u32>,  // Zweiter Vertex-Buffer
+    @location(5) weights: vec4<f32>, // Summe ist 1
+};
+
+@vertex
+fn vs_skinned(
+    model: SkinnedVertexInput,
+    @builtin(instance_index) idx: u32
+) -> VertexOutput {
+    let model_data = model_matrices[idx];
+
+    // Linear Blend Skinning: gewichtete Summe der Joint-Matrizen
+    let skin = joint_matrices[model.joints.x] * model.weights.x
+        + joint_matrices[model.joints.y] * model.weights.y
+        + joint_matrices[model.joints.z] * model.weights.z
+        + joint_matrices[model.joints.w] * model.weights.w;
+    let world = model_data.model * skin;
+
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+
+    // Wie in vs_main ohne Inverse-Transpose
+    out.world_normal = (world * vec4<f32>(model.normal, 0.0)).xyz;
+    out.world_tangent = vec4<f32>((world * vec4<f32>(model.tangent.xyz, 0.0)).xyz, model.tangent.w);
+
+    let world_pos = world * vec4<f32>(model.position, 1.0);
+    out.world_position = world_pos.xyz;
+    out.clip_position = camera.view_proj * world_pos;
+    return out;
+}
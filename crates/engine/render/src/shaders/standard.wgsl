@@ -36,6 +36,7 @@ var s_diffuse: sampler;
 struct MaterialUniforms {
     roughness: f32,
     metallic: f32,
+    lod_bias: f32, // Aus der Sampler-Definition im Manifest
 };
 @group(2) @binding(2)
 var<uniform> material: MaterialUniforms;
@@ -56,6 +57,7 @@ struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) normal: vec3<f32>,
+    @location(3) tangent: vec4<f32>, // xyz = Tangente, w = Vorzeichen der Bitangente
 };
 
 struct VertexOutput {
@@ -63,6 +65,7 @@ struct VertexOutput {
     @location(0) tex_coords: vec2<f32>,
     @location(1) world_normal: vec3<f32>,
     @location(2) world_position: vec3<f32>, // Optional, nützlich für Lichtberechnungen
+    @location(3) world_tangent: vec4<f32>,
 };
 
 @vertex
@@ -78,6 +81,7 @@ fn vs_main(
     // Normalen müssen auch transformiert werden (hier vereinfacht ohne Inverse-Transpose)
     // Wir nehmen nur den 3x3 Teil der Matrix für die Rotation
     out.world_normal = (model_data.model * vec4<f32>(model.normal, 0.0)).xyz;
+    out.world_tangent = vec4<f32>((model_data.model * vec4<f32>(model.tangent.xyz, 0.0)).xyz, model.tangent.w);
     
     let world_pos = model_data.model * vec4<f32>(model.position, 1.0);
     out.world_position = world_pos.xyz;
@@ -90,10 +94,28 @@ fn vs_main(
 
 
 
+// Normal Map aus dem Tangentenraum in den Weltraum drehen
+fn perturbed_normal(in: VertexOutput) -> vec3<f32> {
+    let n = normalize(in.world_normal);
+    // textureSampleBias muss in uniformem Kontrollfluss stehen, daher vor jeder Verzweigung
+    let xy = textureSampleBias(t_normal, s_diffuse, in.tex_coords, material.lod_bias).xy * 2.0 - 1.0;
+    // z aus xy rekonstruieren, BC5 Normal Maps speichern nur zwei Kanäle
+    let sampled = vec3<f32>(xy, sqrt(max(1.0 - dot(xy, xy), 0.0)));
+
+    // Gram-Schmidt: Tangente nach der Interpolation wieder senkrecht zur Normalen machen
+    let t_raw = in.world_tangent.xyz - n * dot(n, in.world_tangent.xyz);
+    let t = normalize(t_raw);
+    let b = cross(n, t) * in.world_tangent.w;
+    let tbn = mat3x3<f32>(t, b, n);
+
+    // Degenerierte Tangente: Normal Map ignorieren
+    return select(normalize(tbn * sampled), n, dot(t_raw, t_raw) < 1e-8);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let diffuse_color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
-    let normal = normalize(in.world_normal);
+    let diffuse_color = textureSampleBias(t_diffuse, s_diffuse, in.tex_coords, material.lod_bias);
+    let normal = perturbed_normal(in);
 
     let ambient = global_light.ambient_color.rgb * global_light.ambient_color.a;
     let sun_dir = normalize(-global_light.sun_direction.xyz);
@@ -0,0 +1,443 @@
+// CPU decoders for the BCn block formats, used when the adapter can't sample them.
+// Every block covers 4x4 pixels. The output matches what the GPU returns when sampling:
+// BC4 fills only red, BC5 red and green, the other channels are 0 and alpha is opaque.
+
+type Block = [[u8; 4]; 16];
+
+// Decodes one `width` x `height` image into RGBA8. Returns None for formats that aren't
+// BC1/BC3/BC4/BC5/BC7 or when `data` holds fewer blocks than the image needs.
+pub fn decompress_bc(format: wgpu::TextureFormat, width: u32, height: u32, data: &[u8]) -> Option<Vec<u8>> {
+    use wgpu::TextureFormat as F;
+    let decode_block: fn(&[u8], &mut Block) = match format {
+        F::Bc1RgbaUnorm | F::Bc1RgbaUnormSrgb => |bytes, out| decode_bc1(bytes, out, true),
+        F::Bc3RgbaUnorm | F::Bc3RgbaUnormSrgb => decode_bc3,
+        F::Bc4RUnorm => decode_bc4,
+        F::Bc5RgUnorm => decode_bc5,
+        F::Bc7RgbaUnorm | F::Bc7RgbaUnormSrgb => decode_bc7,
+        _ => return None,
+    };
+    let block_size = format.block_copy_size(None)? as usize;
+    let (blocks_x, blocks_y) = (width.div_ceil(4) as usize, height.div_ceil(4) as usize);
+    if data.len() < blocks_x * blocks_y * block_size {
+        return None;
+    }
+
+    let (width, height) = (width as usize, height as usize);
+    let mut rgba = vec![0u8; width * height * 4];
+    let mut block = [[0u8; 4]; 16];
+    for (i, bytes) in data.chunks_exact(block_size).take(blocks_x * blocks_y).enumerate() {
+        decode_block(bytes, &mut block);
+        let (bx, by) = (i % blocks_x * 4, i / blocks_x * 4);
+        // Blocks on the right and bottom edge can reach past the image
+        for y in 0..4.min(height - by) {
+            for x in 0..4.min(width - bx) {
+                let offset = ((by + y) * width + bx + x) * 4;
+                rgba[offset..offset + 4].copy_from_slice(&block[y * 4 + x]);
+            }
+        }
+    }
+    Some(rgba)
+}
+
+fn expand_565(color: u16) -> [u8; 3] {
+    let (r, g, b) = ((color >> 11) & 31, (color >> 5) & 63, color & 31);
+    [(r << 3 | r >> 2) as u8, (g << 2 | g >> 4) as u8, (b << 3 | b >> 2) as u8]
+}
+
+// BC2/BC3 color blocks always use four colors, only BC1 has the punch-through alpha mode
+fn decode_bc1(bytes: &[u8], out: &mut Block, punch_through: bool) {
+    let c0 = u16::from_le_bytes([bytes[0], bytes[1]]);
+    let c1 = u16::from_le_bytes([bytes[2], bytes[3]]);
+    let (e0, e1) = (expand_565(c0), expand_565(c1));
+
+    let mut palette = [[0u8; 4]; 4];
+    palette[0] = [e0[0], e0[1], e0[2], 255];
+    palette[1] = [e1[0], e1[1], e1[2], 255];
+    if c0 > c1 || !punch_through {
+        for c in 0..3 {
+            let (a, b) = (e0[c] as u32, e1[c] as u32);
+            palette[2][c] = ((2 * a + b) / 3) as u8;
+            palette[3][c] = ((a + 2 * b) / 3) as u8;
+        }
+        palette[2][3] = 255;
+        palette[3][3] = 255;
+    } else {
+        for c in 0..3 {
+            palette[2][c] = ((e0[c] as u32 + e1[c] as u32) / 2) as u8;
+        }
+        palette[2][3] = 255;
+        // palette[3] stays transparent black
+    }
+
+    let indices = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = palette[(indices >> (2 * i) & 3) as usize];
+    }
+}
+
+// 8 byte block of one channel with two endpoints and 3 bit indices
+fn decode_channel(bytes: &[u8]) -> [u8; 16] {
+    let (r0, r1) = (bytes[0] as u32, bytes[1] as u32);
+    let mut palette = [0u8; 8];
+    palette[0] = r0 as u8;
+    palette[1] = r1 as u8;
+    if r0 > r1 {
+        for i in 1..7 {
+            palette[i + 1] = (((7 - i as u32) * r0 + i as u32 * r1 + 3) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i + 1] = (((5 - i as u32) * r0 + i as u32 * r1 + 2) / 5) as u8;
+        }
+        palette[6] = 0;
+        palette[7] = 255;
+    }
+
+    let mut bits = [0u8; 8];
+    bits[..6].copy_from_slice(&bytes[2..8]);
+    let indices = u64::from_le_bytes(bits);
+    std::array::from_fn(|i| palette[(indices >> (3 * i) & 7) as usize])
+}
+
+fn decode_bc3(bytes: &[u8], out: &mut Block) {
+    decode_bc1(&bytes[8..16], out, false);
+    for (pixel, alpha) in out.iter_mut().zip(decode_channel(&bytes[0..8])) {
+        pixel[3] = alpha;
+    }
+}
+
+fn decode_bc4(bytes: &[u8], out: &mut Block) {
+    for (pixel, red) in out.iter_mut().zip(decode_channel(bytes)) {
+        *pixel = [red, 0, 0, 255];
+    }
+}
+
+fn decode_bc5(bytes: &[u8], out: &mut Block) {
+    let (red, green) = (decode_channel(&bytes[0..8]), decode_channel(&bytes[8..16]));
+    for (i, pixel) in out.iter_mut().enumerate() {
+        *pixel = [red[i], green[i], 0, 255];
+    }
+}
+
+// Reads the 128 bit BC7 block from the least significant bit up
+struct Bits(u128);
+
+impl Bits {
+    fn take(&mut self, count: u32) -> u8 {
+        let value = (self.0 & ((1u128 << count) - 1)) as u8;
+        self.0 >>= count;
+        value
+    }
+}
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 3, index2_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_pbits: false, shared_pbits: true, index_bits: 3, index2_bits: 0 },
+    Bc7Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 3 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index2_bits: 2 },
+    Bc7Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_pbits: true, shared_pbits: false, index_bits: 4, index2_bits: 0 },
+    Bc7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index2_bits: 0 },
+];
+
+fn decode_bc7(bytes: &[u8], out: &mut Block) {
+    let mut bits = Bits(u128::from_le_bytes(bytes[..16].try_into().unwrap()));
+    // The mode is the number of zero bits before the first one
+    let Some(mode_index) = (0..8).find(|_| bits.take(1) == 1) else {
+        // Reserved mode, decoders return transparent black
+        *out = [[0; 4]; 16];
+        return;
+    };
+    let mode = &BC7_MODES[mode_index];
+
+    let partition = bits.take(mode.partition_bits) as usize;
+    let rotation = bits.take(mode.rotation_bits);
+    let index_selection = bits.take(mode.index_selection_bits);
+
+    // endpoints[subset * 2 + n], channels RGBA
+    let mut endpoints = [[0u8; 4]; 6];
+    let endpoint_count = mode.subsets * 2;
+    for channel in 0..3 {
+        for endpoint in &mut endpoints[..endpoint_count] {
+            endpoint[channel] = bits.take(mode.color_bits);
+        }
+    }
+    for endpoint in &mut endpoints[..endpoint_count] {
+        endpoint[3] = bits.take(mode.alpha_bits);
+    }
+
+    let mut pbits = [0u8; 6];
+    if mode.endpoint_pbits {
+        for pbit in &mut pbits[..endpoint_count] {
+            *pbit = bits.take(1);
+        }
+    } else if mode.shared_pbits {
+        for subset in 0..mode.subsets {
+            let pbit = bits.take(1);
+            pbits[subset * 2] = pbit;
+            pbits[subset * 2 + 1] = pbit;
+        }
+    }
+
+    let has_pbits = mode.endpoint_pbits || mode.shared_pbits;
+    for (endpoint, &pbit) in endpoints[..endpoint_count].iter_mut().zip(&pbits) {
+        for (channel, value) in endpoint.iter_mut().enumerate() {
+            let bits = if channel == 3 { mode.alpha_bits } else { mode.color_bits };
+            if bits == 0 {
+                *value = 255;
+                continue;
+            }
+            let (value_bits, precision) = if has_pbits { ((*value << 1) | pbit, bits + 1) } else { (*value, bits) };
+            *value = unquantize(value_bits, precision);
+        }
+    }
+
+    let subset_of = |pixel: usize| match mode.subsets {
+        2 => (BC7_PARTITIONS_2[partition] >> (2 * pixel) & 3) as usize,
+        3 => (BC7_PARTITIONS_3[partition] >> (2 * pixel) & 3) as usize,
+        _ => 0,
+    };
+    // Each subset's anchor pixel stores its index with one bit less, the top bit is 0
+    let is_anchor = |pixel: usize| match mode.subsets {
+        2 => pixel == 0 || pixel == BC7_ANCHORS_2[partition] as usize,
+        3 => pixel == 0 || pixel == BC7_ANCHORS_3A[partition] as usize || pixel == BC7_ANCHORS_3B[partition] as usize,
+        _ => pixel == 0,
+    };
+
+    let mut indices = [0u8; 16];
+    for (pixel, index) in indices.iter_mut().enumerate() {
+        *index = bits.take(mode.index_bits - is_anchor(pixel) as u32);
+    }
+    let mut indices2 = [0u8; 16];
+    if mode.index2_bits > 0 {
+        for (pixel, index) in indices2.iter_mut().enumerate() {
+            *index = bits.take(mode.index2_bits - (pixel == 0) as u32);
+        }
+    }
+
+    for (pixel, out) in out.iter_mut().enumerate() {
+        let subset = subset_of(pixel);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        let mut color = [0u8; 4];
+        if mode.index2_bits > 0 {
+            // Modes 4 and 5 interpolate color and alpha with separate indices, the index
+            // selection bit swaps which set is used for which
+            let (color_index, color_bits, alpha_index, alpha_bits) = if index_selection == 1 {
+                (indices2[pixel], mode.index2_bits, indices[pixel], mode.index_bits)
+            } else {
+                (indices[pixel], mode.index_bits, indices2[pixel], mode.index2_bits)
+            };
+            for c in 0..3 {
+                color[c] = interpolate(e0[c], e1[c], color_index, color_bits);
+            }
+            color[3] = interpolate(e0[3], e1[3], alpha_index, alpha_bits);
+        } else {
+            for c in 0..4 {
+                color[c] = interpolate(e0[c], e1[c], indices[pixel], mode.index_bits);
+            }
+        }
+
+        match rotation {
+            1 => color.swap(0, 3),
+            2 => color.swap(1, 3),
+            3 => color.swap(2, 3),
+            _ => {}
+        }
+        *out = color;
+    }
+}
+
+fn unquantize(value: u8, precision: u32) -> u8 {
+    let value = (value as u32) << (8 - precision);
+    (value | value >> precision) as u8
+}
+
+fn interpolate(e0: u8, e1: u8, index: u8, bits: u32) -> u8 {
+    const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+    const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+    const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+    let weight = match bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    };
+    (((64 - weight) * e0 as u32 + weight * e1 as u32 + 32) >> 6) as u8
+}
+
+// Subset of every pixel, two bits per pixel starting at the least significant bits
+const BC7_PARTITIONS_2: [u32; 64] = [
+    0x50505050, 0x40404040, 0x54545454, 0x54505040, 0x50404000, 0x55545450, 0x55545040, 0x54504000,
+    0x50400000, 0x55555450, 0x55544000, 0x54400000, 0x55555440, 0x55550000, 0x55555500, 0x55000000,
+    0x55150100, 0x00004054, 0x15010000, 0x00405054, 0x00004050, 0x15050100, 0x05010000, 0x40505054,
+    0x00404050, 0x05010100, 0x14141414, 0x05141450, 0x01155440, 0x00555500, 0x15014054, 0x05414150,
+    0x44444444, 0x55005500, 0x11441144, 0x05055050, 0x05500550, 0x11114444, 0x41144114, 0x44111144,
+    0x15055054, 0x01055040, 0x05041050, 0x05455150, 0x14414114, 0x50050550, 0x41411414, 0x00141400,
+    0x00041504, 0x00105410, 0x10541000, 0x04150400, 0x50410514, 0x41051450, 0x05415014, 0x14054150,
+    0x41050514, 0x41505014, 0x40011554, 0x54150140, 0x50505500, 0x00555050, 0x15151010, 0x54540404,
+];
+
+const BC7_PARTITIONS_3: [u32; 64] = [
+    0xAA685050, 0x6A5A5040, 0x5A5A4200, 0x5450A0A8, 0xA5A50000, 0xA0A05050, 0x5555A0A0, 0x5A5A5050,
+    0xAA550000, 0xAA555500, 0xAAAA5500, 0x90909090, 0x94949494, 0xA4A4A4A4, 0xA9A59450, 0x2A0A4250,
+    0xA5945040, 0x0A425054, 0xA5A5A500, 0x55A0A0A0, 0xA8A85454, 0x6A6A4040, 0xA4A45000, 0x1A1A0500,
+    0x0050A4A4, 0xAAA59090, 0x14696914, 0x69691400, 0xA08585A0, 0xAA821414, 0x50A4A450, 0x6A5A0200,
+    0xA9A58000, 0x5090A0A8, 0xA8A09050, 0x24242424, 0x00AA5500, 0x24924924, 0x24499224, 0x50A50A50,
+    0x500AA550, 0xAAAA4444, 0x66660000, 0xA5A0A5A0, 0x50A050A0, 0x69286928, 0x44AAAA44, 0x66666600,
+    0xAA444444, 0x54A854A8, 0x95809580, 0x96969600, 0xA85454A8, 0x80959580, 0xAA141414, 0x96960000,
+    0xAAAA1414, 0xA05050A0, 0xA0A5A5A0, 0x96000000, 0x40804080, 0xA9A8A9A8, 0xAAAAAA44, 0x2A4A5254,
+];
+
+// Anchor pixel of subset 1 (two subsets) and of subsets 1 and 2 (three subsets). Subset 0
+// is anchored at pixel 0.
+const BC7_ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+const BC7_ANCHORS_3A: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3,
+    3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15,
+    8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15,
+    3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+];
+
+const BC7_ANCHORS_3B: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8,
+    15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8,
+    15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8,
+    15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::TextureFormat as F;
+
+    // Golden blocks, the expected pixels were worked out from the format specs by hand
+    fn decode(format: wgpu::TextureFormat, block: &[u8]) -> Block {
+        let rgba = decompress_bc(format, 4, 4, block).unwrap();
+        std::array::from_fn(|i| rgba[i * 4..i * 4 + 4].try_into().unwrap())
+    }
+
+    // Every row of a block that repeats the same four pixels
+    fn rows(row: [[u8; 4]; 4]) -> Block {
+        std::array::from_fn(|i| row[i % 4])
+    }
+
+    #[test]
+    fn bc1_four_color_block() {
+        // c0 = red > c1 = blue, every row uses indices 0, 1, 2, 3
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let expected = rows([[255, 0, 0, 255], [0, 0, 255, 255], [170, 0, 85, 255], [85, 0, 170, 255]]);
+        assert_eq!(decode(F::Bc1RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc1_three_color_block_has_punch_through_alpha() {
+        // c0 = blue <= c1 = red: index 2 is the midpoint, index 3 transparent black
+        let block = [0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let expected = rows([[0, 0, 255, 255], [255, 0, 0, 255], [127, 0, 127, 255], [0, 0, 0, 0]]);
+        assert_eq!(decode(F::Bc1RgbaUnormSrgb, &block), expected);
+    }
+
+    #[test]
+    fn bc3_block_uses_four_colors_and_interpolated_alpha() {
+        // The BC1 three color order gives four colors in BC3, alpha runs 210 -> 0 in 8 steps
+        let block = [0xD2, 0x00, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA, 0x1F, 0x00, 0x00, 0xF8, 0xE4, 0xE4, 0xE4, 0xE4];
+        let expected: Block = [
+            [0, 0, 255, 210], [255, 0, 0, 0], [85, 0, 170, 180], [170, 0, 85, 150],
+            [0, 0, 255, 120], [255, 0, 0, 90], [85, 0, 170, 60], [170, 0, 85, 30],
+            [0, 0, 255, 210], [255, 0, 0, 0], [85, 0, 170, 180], [170, 0, 85, 150],
+            [0, 0, 255, 120], [255, 0, 0, 90], [85, 0, 170, 60], [170, 0, 85, 30],
+        ];
+        assert_eq!(decode(F::Bc3RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc4_eight_and_six_value_blocks() {
+        // r0 > r1 interpolates six values, pixel i uses index i % 8
+        let eight = [0xD2, 0x00, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+        let values = [210, 0, 180, 150, 120, 90, 60, 30];
+        let expected: Block = std::array::from_fn(|i| [values[i % 8], 0, 0, 255]);
+        assert_eq!(decode(F::Bc4RUnorm, &eight), expected);
+
+        // r0 <= r1 interpolates four values and adds 0 and 255
+        let six = [0x32, 0x64, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA];
+        let values = [50, 100, 60, 70, 80, 90, 0, 255];
+        let expected: Block = std::array::from_fn(|i| [values[i % 8], 0, 0, 255]);
+        assert_eq!(decode(F::Bc4RUnorm, &six), expected);
+    }
+
+    #[test]
+    fn bc5_block_fills_red_and_green() {
+        let block = [0xD2, 0x00, 0x88, 0xC6, 0xFA, 0x88, 0xC6, 0xFA, 0x32, 0x64, 0x77, 0x39, 0x05, 0x77, 0x39, 0x05];
+        let red = [210, 0, 180, 150, 120, 90, 60, 30];
+        let green = [255, 0, 90, 80, 70, 60, 100, 50];
+        let expected: Block = std::array::from_fn(|i| [red[i % 8], green[i % 8], 0, 255]);
+        assert_eq!(decode(F::Bc5RgUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_6_block() {
+        // One subset from opaque white to transparent black, pixel i uses index i
+        let block = [0xC0, 0x3F, 0xE0, 0x0F, 0xF8, 0x03, 0xFE, 0x80, 0x10, 0x32, 0x54, 0x76, 0x98, 0xBA, 0xDC, 0xFE];
+        let values = [255, 239, 219, 203, 187, 171, 151, 135, 120, 104, 84, 68, 52, 36, 16, 0];
+        let expected: Block = std::array::from_fn(|i| [values[i]; 4]);
+        assert_eq!(decode(F::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn bc7_mode_5_block_with_and_without_rotation() {
+        // Color indices run along the rows, alpha indices down the columns
+        let block = [0x20, 0x7F, 0x00, 0xE0, 0x0F, 0x04, 0xFE, 0x03, 0xC8, 0xC9, 0xC9, 0xC9, 0x01, 0x55, 0xAA, 0xFF];
+        let steps = [255, 171, 84, 0];
+        let expected: Block = std::array::from_fn(|i| [steps[i % 4], 255 - steps[i % 4], 129, steps[i / 4]]);
+        assert_eq!(decode(F::Bc7RgbaUnorm, &block), expected);
+
+        // Rotation 1 swaps red and alpha
+        let mut rotated = block;
+        rotated[0] = 0x60;
+        let expected: Block = std::array::from_fn(|i| [steps[i / 4], 255 - steps[i % 4], 129, steps[i % 4]]);
+        assert_eq!(decode(F::Bc7RgbaUnormSrgb, &rotated), expected);
+    }
+
+    #[test]
+    fn bc7_mode_1_block_with_two_subsets() {
+        // Partition 0 puts the two right columns into subset 1, the shared p-bit of subset 0
+        // is set. Pixel 15 is the anchor of subset 1.
+        let block = [0x02, 0x3F, 0x00, 0x00, 0x00, 0xF0, 0x03, 0x00, 0x00, 0xFC, 0x11, 0x8D, 0xF5, 0x11, 0x8D, 0xF5];
+        let expected: Block = [
+            [255, 2, 2, 255], [219, 2, 2, 255], [0, 182, 71, 255], [0, 146, 107, 255],
+            [109, 2, 2, 255], [73, 2, 2, 255], [0, 36, 217, 255], [0, 0, 253, 255],
+            [255, 2, 2, 255], [219, 2, 2, 255], [0, 182, 71, 255], [0, 146, 107, 255],
+            [109, 2, 2, 255], [73, 2, 2, 255], [0, 36, 217, 255], [0, 146, 107, 255],
+        ];
+        assert_eq!(decode(F::Bc7RgbaUnorm, &block), expected);
+    }
+
+    #[test]
+    fn partial_edge_blocks_and_short_data() {
+        // A 2x2 image keeps the top left of its block
+        let block = [0x00, 0xF8, 0x1F, 0x00, 0xE4, 0xE4, 0xE4, 0xE4];
+        let rgba = decompress_bc(F::Bc1RgbaUnorm, 2, 2, &block).unwrap();
+        assert_eq!(rgba, [255, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0, 255, 0, 0, 255, 255]);
+        assert!(decompress_bc(F::Bc1RgbaUnorm, 8, 4, &block).is_none());
+        assert!(decompress_bc(F::Rgba8Unorm, 4, 4, &block).is_none());
+    }
+}
@@ -1,6 +1,12 @@
 pub mod texture;
 pub use texture::Texture;
 
+pub mod mipmaps;
+pub use mipmaps::{mip_level_count, generate_mip_chain, generate_mip_chain_f32, write_mip_chain};
+
+pub mod bcn;
+pub use bcn::decompress_bc;
+
 pub mod model;
 pub use model::Vertex;
 pub use model::ModelVertex;
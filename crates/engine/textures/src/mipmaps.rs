@@ -0,0 +1,123 @@
+use image::RgbaImage;
+
+// Number of levels in a full mip chain down to 1x1
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+// Builds all levels below the RGBA8 `base` with a 2x2 box filter. sRGB data is averaged
+// in linear space, otherwise distant textures get visibly darker. Alpha is always linear.
+pub fn generate_mip_chain(base: &[u8], width: u32, height: u32, srgb: bool) -> Vec<RgbaImage> {
+    let levels = mip_level_count(width, height);
+    let mut chain: Vec<RgbaImage> = Vec::with_capacity(levels.saturating_sub(1) as usize);
+
+    for _ in 1..levels {
+        let level = match chain.last() {
+            Some(prev) => downsample(prev.as_raw(), prev.width(), prev.height(), srgb),
+            None => downsample(base, width, height, srgb),
+        };
+        chain.push(level);
+    }
+
+    chain
+}
+
+// Same box filter for linear RGBA32F pixels, used by HDR textures. Level n is
+// `(width >> n).max(1)` by `(height >> n).max(1)` pixels.
+pub fn generate_mip_chain_f32(base: &[f32], width: u32, height: u32) -> Vec<Vec<f32>> {
+    let levels = mip_level_count(width, height);
+    let mut chain: Vec<Vec<f32>> = Vec::with_capacity(levels.saturating_sub(1) as usize);
+    let (mut src_w, mut src_h) = (width, height);
+
+    for _ in 1..levels {
+        let src = chain.last().map_or(base, Vec::as_slice);
+        let (dst_w, dst_h) = ((src_w / 2).max(1), (src_h / 2).max(1));
+        let mut level = Vec::with_capacity((dst_w * dst_h * 4) as usize);
+        for y in 0..dst_h {
+            for x in 0..dst_w {
+                let mut sum = [0.0f32; 4];
+                for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (px_x, px_y) = ((x * 2 + sx).min(src_w - 1), (y * 2 + sy).min(src_h - 1));
+                    let offset = ((px_y * src_w + px_x) * 4) as usize;
+                    for c in 0..4 {
+                        sum[c] += src[offset + c];
+                    }
+                }
+                level.extend(sum.map(|c| c / 4.0));
+            }
+        }
+        chain.push(level);
+        (src_w, src_h) = (dst_w, dst_h);
+    }
+
+    chain
+}
+
+// Uploads the base level and the generated chain into a texture that was created with
+// `mip_level_count(width, height)` levels
+pub fn write_mip_chain(queue: &wgpu::Queue, texture: &wgpu::Texture, base: &[u8], width: u32, height: u32, chain: &[RgbaImage]) {
+    let levels = std::iter::once((base, width, height))
+        .chain(chain.iter().map(|level| (level.as_raw().as_slice(), level.width(), level.height())));
+
+    for (mip_level, (rgba, width, height)) in levels.enumerate() {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                aspect: wgpu::TextureAspect::All,
+                texture,
+                mip_level: mip_level as u32,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+fn downsample(src: &[u8], src_w: u32, src_h: u32, srgb: bool) -> RgbaImage {
+    let (dst_w, dst_h) = ((src_w / 2).max(1), (src_h / 2).max(1));
+    let to_linear: [f32; 256] = std::array::from_fn(|i| {
+        if srgb { srgb_to_linear(i as u8) } else { i as f32 / 255.0 }
+    });
+
+    RgbaImage::from_fn(dst_w, dst_h, |x, y| {
+        let mut sum = [0.0f32; 4];
+        for (sx, sy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            // Odd sizes and 1-pixel edges reuse the last row/column
+            let (px_x, px_y) = ((x * 2 + sx).min(src_w - 1), (y * 2 + sy).min(src_h - 1));
+            let offset = ((px_y * src_w + px_x) * 4) as usize;
+            let px = &src[offset..offset + 4];
+            for c in 0..3 {
+                sum[c] += to_linear[px[c] as usize];
+            }
+            sum[3] += px[3] as f32 / 255.0;
+        }
+
+        let mut out = [0u8; 4];
+        for c in 0..3 {
+            let value = sum[c] / 4.0;
+            out[c] = if srgb { linear_to_srgb(value) } else { (value * 255.0).round() as u8 };
+        }
+        out[3] = (sum[3] / 4.0 * 255.0).round() as u8;
+        image::Rgba(out)
+    })
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let c = value.clamp(0.0, 1.0);
+    let srgb = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (srgb * 255.0).round() as u8
+}
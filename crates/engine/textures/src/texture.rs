@@ -1,5 +1,6 @@
 use image::GenericImageView;
 use anyhow::*;
+use crate::mipmaps::{mip_level_count, generate_mip_chain, write_mip_chain};
 
 pub struct Texture {
     #[allow(unused)]
@@ -39,7 +40,7 @@ impl Texture {
             &wgpu::TextureDescriptor {
                 label,
                 size,
-                mip_level_count: 1,
+                mip_level_count: mip_level_count(dimensions.0, dimensions.1),
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -48,21 +49,8 @@ impl Texture {
             }
         );
 
-        queue.write_texture(
-            wgpu::TexelCopyTextureInfo {
-                aspect: wgpu::TextureAspect::All,
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::TexelCopyBufferLayout {
-                offset: 0,
-                bytes_per_row: Some(4 * dimensions.0),
-                rows_per_image: Some(dimensions.1),
-            },
-            size,
-        );
+        let mip_chain = generate_mip_chain(&rgba, dimensions.0, dimensions.1, true);
+        write_mip_chain(queue, &texture, &rgba, dimensions.0, dimensions.1, &mip_chain);
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = device.create_sampler(
@@ -71,8 +59,8 @@ impl Texture {
                 address_mode_v: wgpu::AddressMode::ClampToEdge,
                 address_mode_w: wgpu::AddressMode::ClampToEdge,
                 mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
                 ..Default::default()
             }
         );
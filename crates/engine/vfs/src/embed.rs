@@ -0,0 +1,40 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Build script helper: writes a Rust expression of type `&[EmbeddedFile]` with every file
+// below `root` to `output`, for `include!` and `Vfs::mount_embedded`. Paths are relative to
+// `root` with forward slashes, the build reruns when anything below it changes.
+pub fn write_embedded_files(root: &Path, output: &Path) -> io::Result<()> {
+    let root = fs::canonicalize(root)?;
+    let mut files = Vec::new();
+    collect_files(&root, &mut files)?;
+    files.sort();
+
+    let mut code = String::from("&[\n");
+    for file in &files {
+        let Some(path) = file.strip_prefix(&root).ok().and_then(Path::to_str) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("'{}' is not valid UTF-8", file.display())));
+        };
+        let path = path.replace('\\', "/");
+        let _ = writeln!(code, "    engine_vfs::EmbeddedFile {{ path: {:?}, data: include_bytes!({:?}) }},", path, file);
+    }
+    code.push(']');
+    fs::write(output, code)?;
+
+    println!("cargo:rerun-if-changed={}", root.display());
+    Ok(())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use memmap2::Mmap;
+
+pub mod embed;
+mod zip;
+
+pub use zip::ZipArchive;
+
+// Layered virtual file system. Relative paths are looked up in the mounts, the most recently
+// mounted one that has the file wins, so mods and patches override the files below them.
+// Absolute paths bypass the mounts and are read from disk as they are. Clones share the
+// mounts, so loader threads get their own handle cheaply.
+#[derive(Default, Clone)]
+pub struct Vfs {
+    mounts: Vec<Arc<Mount>>,
+}
+
+struct Mount {
+    // Prefix the mount's files appear under, empty for the root
+    point: PathBuf,
+    source: MountSource,
+    // Paths starting with ".." can't leave the mount. Only the working directory of the
+    // tools allows them, like plain file access.
+    confined: bool,
+}
+
+enum MountSource {
+    Directory(PathBuf),
+    Zip(ZipArchive),
+    Embedded(HashMap<&'static str, &'static [u8]>),
+}
+
+// A file compiled into the binary, see `embed::write_embedded_files`
+pub struct EmbeddedFile {
+    pub path: &'static str,
+    pub data: &'static [u8],
+}
+
+// Contents of a file, memory-mapped when it comes straight from disk
+pub enum FileData {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+    Static(&'static [u8]),
+}
+
+impl Deref for FileData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileData::Mapped(map) => map,
+            FileData::Owned(bytes) => bytes,
+            FileData::Static(bytes) => bytes,
+        }
+    }
+}
+
+enum Location<'a> {
+    Disk(PathBuf),
+    Zip(&'a ZipArchive, String),
+    Embedded(&'static [u8]),
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The working directory at the root, which resolves paths like plain file access does.
+    // Used by the tools and until a game mounts its own layers.
+    pub fn working_dir() -> Self {
+        let mut vfs = Self::new();
+        vfs.mounts.push(Arc::new(Mount { point: PathBuf::new(), source: MountSource::Directory(PathBuf::from(".")), confined: false }));
+        vfs
+    }
+
+    pub fn mount_dir(&mut self, point: impl AsRef<Path>, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = fs::canonicalize(dir)?;
+        if !dir.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::NotADirectory, format!("'{}' is not a directory", dir.display())));
+        }
+        log::info!("Mounted directory '{}' at '{}'", dir.display(), point.as_ref().display());
+        self.mount(point.as_ref(), MountSource::Directory(dir));
+        Ok(())
+    }
+
+    pub fn mount_zip(&mut self, point: impl AsRef<Path>, archive: impl AsRef<Path>) -> io::Result<()> {
+        let zip = ZipArchive::open(archive.as_ref())?;
+        log::info!("Mounted zip archive '{}' at '{}'", archive.as_ref().display(), point.as_ref().display());
+        self.mount(point.as_ref(), MountSource::Zip(zip));
+        Ok(())
+    }
+
+    pub fn mount_embedded(&mut self, point: impl AsRef<Path>, files: &'static [EmbeddedFile]) {
+        log::info!("Mounted {} embedded files at '{}'", files.len(), point.as_ref().display());
+        let files = files.iter().map(|file| (file.path, file.data)).collect();
+        self.mount(point.as_ref(), MountSource::Embedded(files));
+    }
+
+    fn mount(&mut self, point: &Path, source: MountSource) {
+        self.mounts.push(Arc::new(Mount { point: normalize(point), source, confined: true }));
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        match self.locate(path.as_ref())? {
+            Location::Disk(file) => fs::read(file),
+            Location::Zip(zip, name) => zip.read(&name),
+            Location::Embedded(data) => Ok(data.to_vec()),
+        }
+    }
+
+    pub fn read_to_string(&self, path: impl AsRef<Path>) -> io::Result<String> {
+        String::from_utf8(self.read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    // Like `read`, but files on disk are mapped instead of copied
+    pub fn open(&self, path: impl AsRef<Path>) -> io::Result<FileData> {
+        match self.locate(path.as_ref())? {
+            Location::Disk(file) => {
+                let file = File::open(file)?;
+                // SAFETY: The mapping is only read. Callers that can't handle a file being
+                // replaced while mapped have to verify its contents.
+                Ok(FileData::Mapped(unsafe { Mmap::map(&file) }?))
+            }
+            Location::Zip(zip, name) => zip.read(&name).map(FileData::Owned),
+            Location::Embedded(data) => Ok(FileData::Static(data)),
+        }
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.locate(path.as_ref()).is_ok()
+    }
+
+    // The canonical file on disk a path resolves to, None when it comes from an archive or
+    // the binary. Hot reload watches these.
+    pub fn real_path(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        match self.locate(path.as_ref()).ok()? {
+            Location::Disk(file) => fs::canonicalize(file).ok(),
+            _ => None,
+        }
+    }
+
+    fn locate(&self, path: &Path) -> io::Result<Location<'_>> {
+        if path.is_absolute() {
+            return if path.is_file() { Ok(Location::Disk(path.to_path_buf())) } else { Err(not_found(path)) };
+        }
+
+        let path = normalize(path);
+        // A leading ".." would be joined onto directory mounts and leave them
+        let leaves_mount = path.starts_with(Component::ParentDir);
+        for mount in self.mounts.iter().rev().filter(|mount| !(leaves_mount && mount.confined)) {
+            let Ok(relative) = path.strip_prefix(&mount.point) else { continue };
+            match &mount.source {
+                MountSource::Directory(dir) => {
+                    let file = dir.join(relative);
+                    if file.is_file() {
+                        return Ok(Location::Disk(file));
+                    }
+                }
+                MountSource::Zip(zip) => {
+                    if let Some(name) = archive_name(relative)
+                        && zip.contains(&name)
+                    {
+                        return Ok(Location::Zip(zip, name));
+                    }
+                }
+                MountSource::Embedded(files) => {
+                    if let Some(data) = archive_name(relative).and_then(|name| files.get(name.as_str())) {
+                        return Ok(Location::Embedded(data));
+                    }
+                }
+            }
+        }
+        if leaves_mount && self.mounts.iter().all(|mount| mount.confined) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("'{}' points outside of the mounts", path.display()),
+            ));
+        }
+        Err(not_found(&path))
+    }
+}
+
+// Resolves "." and ".." without touching the disk, so paths that don't exist (yet) or only
+// exist in an archive normalize the same way
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+// Searches the executable's directory and its parents for `name`. Finds a directory shipped
+// next to the binary as well as one in the workspace of a `cargo run` build.
+pub fn find_near_exe(name: impl AsRef<Path>) -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors().skip(1)
+        .map(|dir| dir.join(name.as_ref()))
+        .find(|candidate| candidate.exists())
+}
+
+// Archives and embedded files always use forward slashes. Paths that leave the mount can't
+// be in there.
+fn archive_name(path: &Path) -> Option<String> {
+    let parts = path.components()
+        .map(|component| match component {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("'{}' was not found in any mount", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_keeps_leading_parent_dirs() {
+        assert_eq!(normalize(Path::new("a/./b/../c")), Path::new("a/c"));
+        assert_eq!(normalize(Path::new("a/../../b")), Path::new("../b"));
+    }
+
+    #[test]
+    fn rejects_paths_leaving_the_mounts() {
+        let root = std::env::temp_dir().join(format!("engine_vfs_{}_mount", std::process::id()));
+        fs::create_dir_all(root.join("inner")).unwrap();
+        fs::write(root.join("outside.txt"), b"outside").unwrap();
+        fs::write(root.join("inner/inside.txt"), b"inside").unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.mount_dir("", root.join("inner")).unwrap();
+        assert_eq!(vfs.read("inside.txt").unwrap(), b"inside");
+        assert_eq!(vfs.read("../outside.txt").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(vfs.read("x/../../outside.txt").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+        assert!(!vfs.exists("../outside.txt"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn working_dir_allows_parent_dirs() {
+        // Tests run in the crate's directory
+        let vfs = Vfs::working_dir();
+        assert!(vfs.read("../vfs/Cargo.toml").unwrap().starts_with(b"[package]"));
+        assert_eq!(vfs.read("../vfs/missing.toml").unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
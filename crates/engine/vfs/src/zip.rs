@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use memmap2::Mmap;
+
+// Minimal reader for the archives mods and patches ship as: stored and deflated entries of
+// a single-disk archive. Zip64, encryption and multi-disk archives are rejected.
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const LOCAL_HEADER_LEN: usize = 30;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_LEN: usize = 22;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+const FLAG_ENCRYPTED: u16 = 1;
+
+struct ZipEntry {
+    header_offset: usize,
+    method: u16,
+    flags: u16,
+    compressed_size: usize,
+    size: usize,
+    crc: u32,
+}
+
+// The archive stays mapped, entries are inflated when they are read
+pub struct ZipArchive {
+    map: Mmap,
+    entries: HashMap<String, ZipEntry>,
+}
+
+impl ZipArchive {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: The mapping is only read. Entries are checked against their CRC, so an
+        // archive that is replaced while mounted gives errors, not garbage.
+        let map = unsafe { Mmap::map(&file) }?;
+
+        let end = find_end_of_central_directory(&map)
+            .ok_or_else(|| invalid("end of central directory not found, this is not a zip archive"))?;
+        if read_u16(&map, end + 4) != 0 || read_u16(&map, end + 6) != 0 {
+            return Err(invalid("multi-disk archives are not supported"));
+        }
+        let count = read_u16(&map, end + 10) as usize;
+        let directory_offset = read_u32(&map, end + 16);
+        if count == 0xFFFF || directory_offset == u32::MAX {
+            return Err(invalid("zip64 archives are not supported"));
+        }
+
+        let mut entries = HashMap::with_capacity(count);
+        let mut offset = directory_offset as usize;
+        for _ in 0..count {
+            let header = map.get(offset..offset + CENTRAL_HEADER_LEN)
+                .ok_or_else(|| invalid("central directory is out of bounds"))?;
+            if read_u32(header, 0) != CENTRAL_HEADER {
+                return Err(invalid("corrupt central directory"));
+            }
+            let name_len = read_u16(header, 28) as usize;
+            let extra_len = read_u16(header, 30) as usize;
+            let comment_len = read_u16(header, 32) as usize;
+            let entry = ZipEntry {
+                header_offset: read_u32(header, 42) as usize,
+                method: read_u16(header, 10),
+                flags: read_u16(header, 8),
+                compressed_size: read_u32(header, 20) as usize,
+                size: read_u32(header, 24) as usize,
+                crc: read_u32(header, 16),
+            };
+            if entry.compressed_size == u32::MAX as usize || entry.size == u32::MAX as usize {
+                return Err(invalid("zip64 archives are not supported"));
+            }
+
+            let name_start = offset + CENTRAL_HEADER_LEN;
+            let name = map.get(name_start..name_start + name_len)
+                .ok_or_else(|| invalid("central directory is out of bounds"))?;
+            // Some Windows tools write backslashes
+            let name = String::from_utf8_lossy(name).replace('\\', "/");
+            if !name.ends_with('/') {
+                entries.insert(name, entry);
+            }
+            offset = name_start + name_len + extra_len + comment_len;
+        }
+
+        Ok(Self { map, entries })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    pub fn read(&self, name: &str) -> io::Result<Vec<u8>> {
+        let entry = self.entries.get(name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("'{}' is not in the archive", name)))?;
+        if entry.flags & FLAG_ENCRYPTED != 0 {
+            return Err(unsupported(format!("'{}' is encrypted", name)));
+        }
+
+        let header = self.map.get(entry.header_offset..entry.header_offset + LOCAL_HEADER_LEN)
+            .filter(|header| read_u32(header, 0) == LOCAL_HEADER)
+            .ok_or_else(|| invalid("corrupt local file header"))?;
+        let data_start = entry.header_offset + LOCAL_HEADER_LEN + read_u16(header, 26) as usize + read_u16(header, 28) as usize;
+        let data = self.map.get(data_start..data_start + entry.compressed_size)
+            .ok_or_else(|| invalid("file data is out of bounds"))?;
+
+        let bytes = match entry.method {
+            METHOD_STORED => data.to_vec(),
+            METHOD_DEFLATED => miniz_oxide::inflate::decompress_to_vec_with_limit(data, entry.size)
+                .map_err(|e| invalid(format!("'{}' could not be inflated: {}", name, e)))?,
+            method => return Err(unsupported(format!("'{}' uses compression method {}, only stored and deflated entries are supported", name, method))),
+        };
+        if bytes.len() != entry.size || crc32fast::hash(&bytes) != entry.crc {
+            return Err(invalid(format!("checksum mismatch in '{}'", name)));
+        }
+        Ok(bytes)
+    }
+}
+
+// The record sits at the very end, followed only by a comment of up to 64 KiB
+fn find_end_of_central_directory(map: &[u8]) -> Option<usize> {
+    let last = map.len().checked_sub(END_OF_CENTRAL_DIRECTORY_LEN)?;
+    let first = last.saturating_sub(u16::MAX as usize);
+    (first..=last).rev().find(|&offset| read_u32(map, offset) == END_OF_CENTRAL_DIRECTORY)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn invalid(reason: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.into())
+}
+
+fn unsupported(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestEntry<'a> {
+        name: &'a str,
+        data: &'a [u8],
+        method: u16,
+        flags: u16,
+    }
+
+    impl<'a> TestEntry<'a> {
+        fn new(name: &'a str, data: &'a [u8], method: u16) -> Self {
+            Self { name, data, method, flags: 0 }
+        }
+    }
+
+    // Writes local headers, the central directory and its end record like an archiver would
+    fn build_zip(entries: &[TestEntry]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+        for entry in entries {
+            let compressed = match entry.method {
+                METHOD_DEFLATED => miniz_oxide::deflate::compress_to_vec(entry.data, 6),
+                _ => entry.data.to_vec(),
+            };
+            let crc = crc32fast::hash(entry.data);
+            let header_offset = bytes.len() as u32;
+
+            bytes.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+            bytes.extend_from_slice(&20u16.to_le_bytes());
+            bytes.extend_from_slice(&entry.flags.to_le_bytes());
+            bytes.extend_from_slice(&entry.method.to_le_bytes());
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(&crc.to_le_bytes());
+            bytes.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(entry.name.as_bytes());
+            bytes.extend_from_slice(&compressed);
+
+            directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes());
+            directory.extend_from_slice(&entry.flags.to_le_bytes());
+            directory.extend_from_slice(&entry.method.to_le_bytes());
+            directory.extend_from_slice(&[0; 4]);
+            directory.extend_from_slice(&crc.to_le_bytes());
+            directory.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&header_offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_offset = bytes.len() as u32;
+        bytes.extend_from_slice(&directory);
+        bytes.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&directory_offset.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes
+    }
+
+    fn open(name: &str, bytes: &[u8]) -> io::Result<ZipArchive> {
+        let path = std::env::temp_dir().join(format!("engine_vfs_{}_{}.zip", std::process::id(), name));
+        std::fs::write(&path, bytes).unwrap();
+        let archive = ZipArchive::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        archive
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        let text = b"stored entry".to_vec();
+        let repeated = b"deflated entry ".repeat(64);
+        let bytes = build_zip(&[
+            TestEntry::new("a/stored.txt", &text, METHOD_STORED),
+            TestEntry::new("a/deflated.txt", &repeated, METHOD_DEFLATED),
+        ]);
+        let archive = open("entries", &bytes).unwrap();
+        assert!(archive.contains("a/stored.txt"));
+        assert_eq!(archive.read("a/stored.txt").unwrap(), text);
+        assert_eq!(archive.read("a/deflated.txt").unwrap(), repeated);
+        assert_eq!(archive.read("missing.txt").unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn rejects_crc_mismatch() {
+        let mut bytes = build_zip(&[TestEntry::new("file.txt", b"original", METHOD_STORED)]);
+        let data_start = LOCAL_HEADER_LEN + "file.txt".len();
+        bytes[data_start] ^= 0xFF;
+        let archive = open("crc", &bytes).unwrap();
+        assert_eq!(archive.read("file.txt").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_missing_or_truncated_end_record() {
+        let bytes = build_zip(&[TestEntry::new("file.txt", b"data", METHOD_STORED)]);
+        let truncated = &bytes[..bytes.len() - 4];
+        assert_eq!(open("truncated", truncated).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        let without_end = &bytes[..bytes.len() - END_OF_CENTRAL_DIRECTORY_LEN];
+        assert_eq!(open("no_end", without_end).err().unwrap().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(open("empty", &[]).err().unwrap().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_zip64_archives() {
+        let mut bytes = build_zip(&[TestEntry::new("file.txt", b"data", METHOD_STORED)]);
+        let directory_offset = bytes.len() - 6;
+        bytes[directory_offset..directory_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = open("zip64", &bytes).err().unwrap();
+        assert!(error.to_string().contains("zip64"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_encrypted_entries() {
+        let entry = TestEntry { flags: FLAG_ENCRYPTED, ..TestEntry::new("secret.txt", b"data", METHOD_STORED) };
+        let archive = open("encrypted", &build_zip(&[entry])).unwrap();
+        assert_eq!(archive.read("secret.txt").unwrap_err().kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,12 @@
+use std::path::PathBuf;
+
+// With the "embed-assets" feature the ressources directory is compiled into the binary, as
+// the lowest layer of the game's VFS
+fn main() {
+    if std::env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_none() {
+        return;
+    }
+    let root = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../ressources");
+    let output = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    engine_vfs::embed::write_embedded_files(&root, &output).expect("embedded assets could not be written");
+}
@@ -1,38 +1,59 @@
 use engine_app::GameLogic;
 use engine_ecs::{ECSManager, EngineSet, fly_camera_controller_system, GameStateConfig, FrameContext, GameState};
-use engine_ecs::ecs_bundles::{PointLightBundle, FlyCameraBundle, Sprite3DBundle};
-use engine_ecs::ecs_components::{PointLight};
-use engine_assets::AssetManager;
+use engine_ecs::ecs_bundles::{FlyCameraBundle, Sprite3DBundle};
+use engine_ecs::ecs_components::{MeshLods, PointLight};
+use engine_assets::{AssetManager, LoadProgress};
+use engine_assets::memory::MemoryStats;
 use engine_gpu_types::CameraUniform;
+use engine_vfs::Vfs;
 use winit::event::WindowEvent;
-use crate::ui::{main_menu, hud, pause_menu, stats};
+use crate::ui::{main_menu, loading_screen, hud, pause_menu, stats};
 use bevy_ecs::prelude::*;
 use crate::systems::switch_game_state_system;
 
 
 pub const STATE_CONFIG: &[GameStateConfig] = &[
     GameStateConfig { name: "main_menu", cursor_visible: true },
+    GameStateConfig { name: "loading",   cursor_visible: true },
     GameStateConfig { name: "playing",   cursor_visible: false },
     GameStateConfig { name: "paused",    cursor_visible: true },
 ];
 
 pub const INTIAL_STATE: &str = "main_menu";
 
+// Searched next to the executable and in its parent directories
+const RESSOURCES_DIR: &str = "ressources";
+// Zip archives in the ressources' mods directory override its files, in name order
+const MODS_DIR: &str = "mods";
+// Paths below are relative to the ressources
+const ASSET_MANIFEST: &str = "assets/asset_manifest.json";
+// Written by `cargo run -p asset_cooker` from the workspace root
+const ASSET_BUNDLE: &str = "assets/assets.bundle";
+const KEYBINDINGS: &str = "keybindings/keybindings.json";
+#[cfg(feature = "embed-assets")]
+const EMBEDDED_RESSOURCES: &[engine_vfs::EmbeddedFile] = include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+// GPU memory the assets may use before a warning is logged
+const ASSET_MEMORY_BUDGET: u64 = 512 * 1024 * 1024;
+
 pub struct Game {
     pub ecs_manager : ECSManager,
     last_update: std::time::Instant,
     tick_count: u64,
     total_time: f64,
+    load_progress: LoadProgress,
+    memory_stats: MemoryStats,
 }
 
 impl Game {
     pub fn new() -> Self {
-        let mut ecs_manager = ECSManager::new();
+        let ecs_manager = ECSManager::new();
         Self {
-            ecs_manager: ecs_manager,
+            ecs_manager,
             last_update: std::time::Instant::now(),
             tick_count: 0,
             total_time: 0.0,
+            load_progress: LoadProgress::default(),
+            memory_stats: MemoryStats::default(),
         }
     }
 
@@ -46,40 +67,42 @@ impl Game {
         state_res.set_state(new_state);
     }
 
+    pub fn load_progress(&self) -> &LoadProgress {
+        &self.load_progress
+    }
+
+    pub fn memory_stats(&self) -> &MemoryStats {
+        &self.memory_stats
+    }
+
 }
 
 impl GameLogic for Game {
-    fn init(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, asset_manager: &mut AssetManager) {
-        asset_manager.initialize_assets("../ressources/assets/asset_manifest.json", device, queue);
-        self.ecs_manager.load_input_bindings("../ressources/keybindings/keybindings.json");
+    fn init(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, asset_manager: &mut AssetManager) -> anyhow::Result<()> {
+        // The assets load in the background while the main menu is shown. Release builds use
+        // the cooked bundle when there is one.
+        asset_manager.set_vfs(mount_ressources()?);
+        let assets = if !cfg!(debug_assertions) && asset_manager.vfs().exists(ASSET_BUNDLE) {
+            ASSET_BUNDLE
+        } else {
+            ASSET_MANIFEST
+        };
+        asset_manager.set_memory_budget(Some(ASSET_MEMORY_BUDGET));
+        asset_manager.begin_loading(assets, device, queue)?;
+        self.ecs_manager.load_input_bindings(asset_manager.vfs(), KEYBINDINGS)?;
         self.ecs_manager.set_game_state_config(STATE_CONFIG, INTIAL_STATE);
         self.ecs_manager.set_ambient_light_color([0.1, 0.1, 0.1, 1.0]);
 
         let camera = FlyCameraBundle::new();
 
-        for i in 0..10 {
-            for j in 0..10 {
-                let val_i = (i as f32 - 5.0) * 4.0;
-                let val_j = (j as f32 - 5.0) * 4.0;
-                
-                let cube = Sprite3DBundle::new(
-                    "cube_mesh",
-                    "cube_material",
-                    glam::Vec3::new(val_i, 0.0, val_j),
-                    asset_manager
-                );
-                self.ecs_manager.world.spawn(cube);
-            }
-        }
-
         let position = glam::Vec3::new(0.0, 5.0, 0.0);
         self.ecs_manager.world.spawn((
             Sprite3DBundle::new(
                 "internal:sphere", 
                 "internal:white", 
                 position, 
-                &asset_manager
-            ),
+                asset_manager
+            )?,
             PointLight {
                 color: glam::Vec3::new(1.0, 1.0, 1.0),
                 intensity: 10.0,
@@ -95,6 +118,42 @@ impl GameLogic for Game {
             ).in_set(EngineSet::Logic) 
         );
 
+        Ok(())
+    }
+
+    fn on_load_progress(&mut self, progress: &LoadProgress) {
+        self.load_progress.clone_from(progress);
+    }
+
+    fn on_memory_stats(&mut self, stats: &MemoryStats) {
+        self.memory_stats.clone_from(stats);
+    }
+
+    fn on_assets_loaded(&mut self, asset_manager: &mut AssetManager) -> anyhow::Result<()> {
+        if cfg!(debug_assertions) && let Err(e) = asset_manager.enable_hot_reload() {
+            log::warn!("Asset hot reloading disabled: {}", e);
+        }
+
+        for i in 0..10 {
+            for j in 0..10 {
+                let val_i = (i as f32 - 5.0) * 4.0;
+                let val_j = (j as f32 - 5.0) * 4.0;
+                
+                let cube = Sprite3DBundle::new(
+                    "cube_mesh",
+                    "cube_material",
+                    glam::Vec3::new(val_i, 0.0, val_j),
+                    asset_manager
+                )?;
+                let lods = MeshLods::for_mesh(&cube.mesh, asset_manager);
+                let mut entity = self.ecs_manager.world.spawn(cube);
+                if let Some(lods) = lods {
+                    entity.insert(lods);
+                }
+            }
+        }
+
+        Ok(())
     }
 
     fn on_device_input(&mut self, event: &winit::event::DeviceEvent) {
@@ -130,6 +189,9 @@ impl GameLogic for Game {
 
         self.ecs_manager.update(ctx);
 
+        if self.state() == "loading" && self.load_progress.is_complete() {
+            self.set_state("playing");
+        }
 
     }
 
@@ -143,6 +205,7 @@ impl GameLogic for Game {
                 
                 match self.state() {
                     "main_menu" => main_menu::draw(ui, self),
+                    "loading"  => loading_screen::draw(ui, self),
                     "playing"  => hud::draw(ui),
                     "paused"   => pause_menu::draw(ui, self),
                     _ => {}
@@ -154,7 +217,7 @@ impl GameLogic for Game {
         self.ecs_manager.world
             .get_resource::<CameraUniform>()
             .cloned()
-            .unwrap_or_else(|| CameraUniform::new())
+            .unwrap_or_else(CameraUniform::new)
     }
 
     fn is_cursor_visible(&self) -> bool {
@@ -164,3 +227,28 @@ impl GameLogic for Game {
                 .unwrap_or(true)
         }
 }
+
+// Layers of the game's files, each overriding the ones before it: the ressources compiled
+// into the binary, the ressources directory and the zip archives in its mods directory
+fn mount_ressources() -> anyhow::Result<Vfs> {
+    let mut vfs = Vfs::new();
+    #[cfg(feature = "embed-assets")]
+    vfs.mount_embedded("", EMBEDDED_RESSOURCES);
+
+    let Some(dir) = engine_vfs::find_near_exe(RESSOURCES_DIR) else {
+        log::warn!("No '{}' directory found next to the executable.", RESSOURCES_DIR);
+        return Ok(vfs);
+    };
+    vfs.mount_dir("", &dir)?;
+
+    let mut mods: Vec<std::path::PathBuf> = std::fs::read_dir(dir.join(MODS_DIR)).into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")))
+        .collect();
+    mods.sort();
+    for archive in &mods {
+        vfs.mount_zip("", archive)?;
+    }
+    Ok(vfs)
+}
@@ -6,17 +6,13 @@ pub fn switch_game_state_system(
     mut game_state: ResMut<GameState>,
 ) {
     match game_state.active_state.as_str() {
-        "playing" => {
-            if actions.just_pressed("toggle_pause") {
-                game_state.set_state("paused");
-                println!("Game Paused");
-            }
+        "playing" if actions.just_pressed("toggle_pause") => {
+            game_state.set_state("paused");
+            println!("Game Paused");
         },
-        "paused" => {
-            if actions.just_pressed("toggle_pause") {
-                game_state.set_state("playing");
-                println!("Game Resumed");
-            }
+        "paused" if actions.just_pressed("toggle_pause") => {
+            game_state.set_state("playing");
+            println!("Game Resumed");
         },
         _ => {},
     }
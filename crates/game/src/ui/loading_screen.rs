@@ -0,0 +1,42 @@
+use crate::game::Game;
+use egui::{Color32, RichText};
+
+pub fn draw(ui: &mut egui::Ui, game: &Game) {
+    // Gleicher dunkler Hintergrund wie im Hauptmenü
+    let bg_rect = ui.available_rect_before_wrap();
+    ui.painter().rect_filled(
+        bg_rect,
+        0.0,
+        Color32::from_rgb(10, 10, 15)
+    );
+
+    let progress = game.load_progress();
+
+    ui.vertical_centered(|ui| {
+        ui.add_space(bg_rect.height() * 0.4);
+
+        ui.label(
+            RichText::new("LOADING")
+                .size(40.0)
+                .color(Color32::from_rgb(180, 0, 0))
+        );
+        ui.add_space(20.0);
+
+        ui.add(
+            egui::ProgressBar::new(progress.fraction())
+                .desired_width(400.0)
+                .fill(Color32::from_rgb(120, 0, 0))
+                .text(format!("{} / {}", progress.loaded, progress.total))
+        );
+        ui.add_space(10.0);
+
+        // Zuletzt hochgeladenes Asset, blass wie der Highscore im Menü
+        if let Some(current) = &progress.current {
+            ui.label(
+                RichText::new(current)
+                    .size(14.0)
+                    .color(Color32::from_white_alpha(50))
+            );
+        }
+    });
+}
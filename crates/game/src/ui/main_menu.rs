@@ -95,7 +95,7 @@ pub fn draw(ui: &mut egui::Ui, game: &mut Game) {
 
         // --- Die eigentlichen Buttons ---
         if horror_button(ui, "START GAME") {
-            game.set_state("playing");
+            game.set_state("loading");
         }
         ui.add_space(15.0);
 
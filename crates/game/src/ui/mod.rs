@@ -1,4 +1,5 @@
 pub mod hud;
+pub mod loading_screen;
 pub mod main_menu;
 pub mod stats;
 pub mod pause_menu;
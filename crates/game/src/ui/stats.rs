@@ -1,7 +1,8 @@
 use egui::{Color32, RichText, Align2, vec2};
+use engine_assets::memory::format_bytes;
 use crate::game::Game;
 
-pub fn draw(ctx: &egui::Context, _game: &Game) {
+pub fn draw(ctx: &egui::Context, game: &Game) {
     // Area allows us to float the UI above the rest of the game
     egui::Area::new(egui::Id::new("fps_overlay"))
         // RIGHT_TOP pins it to the top-right corner.
@@ -34,6 +35,15 @@ pub fn draw(ctx: &egui::Context, _game: &Game) {
                             .strong()
                             .color(color)
                     );
+
+                    // GPU memory of the loaded assets, red once it exceeds the budget
+                    let memory = game.memory_stats();
+                    let text = match memory.budget {
+                        Some(budget) => format!("VRAM: {} / {}", format_bytes(memory.total.total()), format_bytes(budget)),
+                        None => format!("VRAM: {}", format_bytes(memory.total.total())),
+                    };
+                    let color = if memory.over_budget() { Color32::RED } else { Color32::LIGHT_GRAY };
+                    ui.label(RichText::new(text).size(12.0).color(color));
                 });
         });
 }
@@ -0,0 +1,34 @@
+use std::path::Path;
+use std::time::Instant;
+use engine_assets::bundle::{BUNDLE_VERSION, cook_manifest};
+use engine_vfs::Vfs;
+
+// Cooks an asset manifest into the bundle release builds load:
+//   asset-cooker [manifest] [output]
+// Both paths default to the game's assets when run from the workspace root.
+const DEFAULT_MANIFEST: &str = "ressources/assets/asset_manifest.json";
+const DEFAULT_OUTPUT: &str = "ressources/assets/assets.bundle";
+
+fn main() -> anyhow::Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut args = std::env::args().skip(1);
+    let manifest = args.next().unwrap_or_else(|| DEFAULT_MANIFEST.to_string());
+    let output = args.next().unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+
+    let start = Instant::now();
+    let summary = cook_manifest(&Vfs::working_dir(), Path::new(&manifest), Path::new(&output))?;
+    log::info!(
+        "Cooked '{}' into '{}' (format v{}) in {:.2?}: {} textures, {} meshes, {} materials, {} models, {:.1} MiB",
+        manifest,
+        output,
+        BUNDLE_VERSION,
+        start.elapsed(),
+        summary.textures,
+        summary.meshes,
+        summary.materials,
+        summary.models,
+        summary.bytes as f64 / (1024.0 * 1024.0),
+    );
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::path::Path;
+use std::process::ExitCode;
+use engine_assets::validation::validate_manifest;
+use engine_vfs::Vfs;
+
+// Checks an asset manifest without a GPU and exits non-zero if it would fail to load:
+//   manifest-validator [manifest] [--pipeline <name>]... [--deny-warnings]
+// The manifest defaults to the game's one when run from the workspace root, the pipelines
+// to the ones the engine registers.
+const DEFAULT_MANIFEST: &str = "ressources/assets/asset_manifest.json";
+const DEFAULT_PIPELINES: &[&str] = &["standard", "skinned"];
+
+fn main() -> anyhow::Result<ExitCode> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let mut manifest = None;
+    let mut pipelines = Vec::new();
+    let mut deny_warnings = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--pipeline" => pipelines.push(args.next().ok_or_else(|| anyhow::anyhow!("--pipeline needs a name"))?),
+            "--deny-warnings" => deny_warnings = true,
+            _ if arg.starts_with("--") => anyhow::bail!("unknown option '{}'", arg),
+            _ => manifest = Some(arg),
+        }
+    }
+    let manifest = manifest.unwrap_or_else(|| DEFAULT_MANIFEST.to_string());
+    let mut pipelines: Vec<&str> = pipelines.iter().map(String::as_str).collect();
+    if pipelines.is_empty() {
+        pipelines.extend(DEFAULT_PIPELINES);
+    }
+
+    let report = validate_manifest(&Vfs::working_dir(), Path::new(&manifest), &pipelines)?;
+    println!("Validating '{}'\n{}", manifest, report);
+
+    let failed = report.errors() > 0 || (deny_warnings && report.warnings() > 0);
+    Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}